@@ -0,0 +1,64 @@
+// Cross-contract calls into a DEX router that converts a winner's prize into another PSP22
+// token on its way out, for members who would rather hold e.g. a stablecoin than the
+// fund's currency.
+
+use ink::env::call::{build_call, ExecutionInput, Selector};
+use ink::primitives::AccountId;
+
+use crate::CustomEnvironment;
+
+type Balance = <CustomEnvironment as ink::env::Environment>::Balance;
+
+// What a router contract has to implement. Funds paying in the native currency send the
+// amount as the call's value; token funds approve the router to pull it.
+#[ink::trait_definition]
+pub trait DexRouter {
+    // Swap `amount_in` of `token_in`, the native currency when None, for `token_out` sent
+    // to `to`. Returns the amount sent, or None without swapping anything if it would be
+    // less than `min_amount_out`.
+    #[ink(message, payable)]
+    fn swap_exact_in(
+        &mut self,
+        token_in: Option<AccountId>,
+        token_out: AccountId,
+        amount_in: Balance,
+        min_amount_out: Balance,
+        to: AccountId,
+    ) -> Option<Balance>;
+}
+
+// Why a call into the router failed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SwapError {
+    // The router could not meet the minimum.
+    Slippage,
+    // The call itself could not be made or its reply could not be decoded.
+    CallFailed,
+}
+
+pub fn swap_exact_in(
+    router: AccountId,
+    token_in: Option<AccountId>,
+    token_out: AccountId,
+    amount_in: Balance,
+    min_amount_out: Balance,
+    to: AccountId,
+) -> Result<Balance, SwapError> {
+    let value = if token_in.is_none() { amount_in } else { 0 };
+    build_call::<CustomEnvironment>()
+        .call(router)
+        .transferred_value(value)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("DexRouter::swap_exact_in")))
+                .push_arg(token_in)
+                .push_arg(token_out)
+                .push_arg(amount_in)
+                .push_arg(min_amount_out)
+                .push_arg(to),
+        )
+        .returns::<Option<Balance>>()
+        .try_invoke()
+        .map_err(|_| SwapError::CallFailed)?
+        .map_err(|_| SwapError::CallFailed)?
+        .ok_or(SwapError::Slippage)
+}
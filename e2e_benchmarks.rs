@@ -0,0 +1,95 @@
+// Gas benchmarks for the calls whose cost grows with the membership, run with the other
+// end-to-end tests (`cargo test --features e2e-tests` and a node on the default port).
+// `join`, `deposit` and `draw` are dry-run by the last member of funds of 10, 50 and 250
+// members, and the `ref_time` each needs has to stay under a ceiling, so refactors and new
+// features cannot quietly push a large fund past what fits in a block. The ceilings can be
+// set with the CHIT_FUND_MAX_GAS_JOIN, CHIT_FUND_MAX_GAS_DEPOSIT and CHIT_FUND_MAX_GAS_DRAW
+// environment variables.
+
+use ink_e2e::{AccountKeyring, Keypair, MessageBuilder};
+
+use crate::{ChitFundRef, CustomEnvironment};
+
+type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const CONTRIBUTION: u128 = 1_000_000;
+
+const FUND_SIZES: [u32; 3] = [10, 50, 250];
+
+// Default ceiling for each call: a tenth of the 2 * 10^12 `ref_time` a 2 second block
+// allows, leaving room for the other extrinsics in the block.
+const DEFAULT_MAX_GAS: u64 = 200_000_000_000;
+
+fn max_gas(call: &str) -> u64 {
+    std::env::var(format!("CHIT_FUND_MAX_GAS_{call}"))
+        .ok()
+        .and_then(|ceiling| ceiling.parse().ok())
+        .unwrap_or(DEFAULT_MAX_GAS)
+}
+
+fn fund_at(fund: ink::primitives::AccountId) -> MessageBuilder<CustomEnvironment, ChitFundRef> {
+    MessageBuilder::from_account_id(fund)
+}
+
+#[ink_e2e::test(environment = crate::CustomEnvironment)]
+async fn gas_stays_under_ceilings(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    for participants in FUND_SIZES {
+        let admin = ink_e2e::account_id(AccountKeyring::Alice);
+        let constructor = ChitFundRef::new(admin, participants, CONTRIBUTION);
+        let fund = client
+            .instantiate("chit_fund", &ink_e2e::alice(), constructor, 0, None)
+            .await
+            .expect("instantiate failed")
+            .account_id;
+        let mut members: Vec<Keypair> = Vec::new();
+        for _ in 0..participants {
+            members.push(client.create_and_fund_account(&ink_e2e::alice(), 100 * CONTRIBUTION).await);
+        }
+        let (last, others) = members.split_last().expect("funds have members");
+
+        // The last member joins, and later pays, after everyone else, which is when the
+        // calls cost the most.
+        for member in others {
+            let join = fund_at(fund).call(|fund| fund.join());
+            client.call(member, join, 0, None).await.expect("join failed");
+        }
+        let join = fund_at(fund).call(|fund| fund.join());
+        let join_gas = client.call_dry_run(last, &join, 0, None).await.exec_result.gas_required.ref_time();
+        client.call(last, join, 0, None).await.expect("join failed");
+
+        let start = fund_at(fund).call(|fund| fund.start_fund());
+        client.call(&ink_e2e::alice(), start, 0, None).await.expect("start_fund failed");
+        for member in others {
+            let deposit = fund_at(fund).call(|fund| fund.deposit());
+            client.call(member, deposit, CONTRIBUTION, None).await.expect("deposit failed");
+        }
+        let deposit = fund_at(fund).call(|fund| fund.deposit());
+        let deposit_gas = client
+            .call_dry_run(last, &deposit, CONTRIBUTION, None)
+            .await
+            .exec_result
+            .gas_required
+            .ref_time();
+        client.call(last, deposit, CONTRIBUTION, None).await.expect("deposit failed");
+
+        let end_cycle = fund_at(fund).call(|fund| fund.end_cycle());
+        client.call(&ink_e2e::alice(), end_cycle, 0, None).await.expect("end_cycle failed");
+        let draw = fund_at(fund).call(|fund| fund.draw());
+        let draw_gas = client
+            .call_dry_run(&ink_e2e::alice(), &draw, 0, None)
+            .await
+            .exec_result
+            .gas_required
+            .ref_time();
+        client.call(&ink_e2e::alice(), draw, 0, None).await.expect("draw failed");
+
+        for (call, gas) in [("JOIN", join_gas), ("DEPOSIT", deposit_gas), ("DRAW", draw_gas)] {
+            assert!(
+                gas <= max_gas(call),
+                "{call} in a fund of {participants} members needs {gas} ref_time, over the ceiling of {}",
+                max_gas(call)
+            );
+        }
+    }
+    Ok(())
+}
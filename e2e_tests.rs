@@ -0,0 +1,89 @@
+// End-to-end tests run against a substrate-contracts-node, where caller, transferred value
+// and balance transfers behave as they do on chain. Run them with
+// `cargo test --features e2e-tests` and a node on the default port.
+// Tests in which a malicious token calls back into the fund during a payout live with
+// that token, in `reentrant_token/e2e_tests.rs`.
+
+use ink_e2e::{AccountKeyring, MessageBuilder};
+
+use crate::my_contract::{Error, FundPhase};
+use crate::{ChitFundRef, CustomEnvironment};
+
+type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const CONTRIBUTION: u128 = 1_000_000;
+
+fn fund_at(fund: ink::primitives::AccountId) -> MessageBuilder<CustomEnvironment, ChitFundRef> {
+    MessageBuilder::from_account_id(fund)
+}
+
+#[ink_e2e::test(environment = crate::CustomEnvironment)]
+async fn full_fund_lifecycle(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    let admin = ink_e2e::account_id(AccountKeyring::Alice);
+    let constructor = ChitFundRef::new(admin, 3, CONTRIBUTION);
+    let fund = client
+        .instantiate("chit_fund", &ink_e2e::alice(), constructor, 0, None)
+        .await
+        .expect("instantiate failed")
+        .account_id;
+    let members = [ink_e2e::bob(), ink_e2e::charlie(), ink_e2e::dave()];
+
+    for member in &members {
+        let join = fund_at(fund).call(|fund| fund.join());
+        client.call(member, join, 0, None).await.expect("join failed");
+    }
+
+    // Joining twice is rejected.
+    let join = fund_at(fund).call(|fund| fund.join());
+    let result = client.call_dry_run(&ink_e2e::bob(), &join, 0, None).await;
+    assert_eq!(result.return_value(), Err(Error::AlreadyJoined));
+
+    let start = fund_at(fund).call(|fund| fund.start_fund());
+    client.call(&ink_e2e::alice(), start, 0, None).await.expect("start_fund failed");
+
+    // Only members can deposit.
+    let deposit = fund_at(fund).call(|fund| fund.deposit());
+    let result = client.call_dry_run(&ink_e2e::eve(), &deposit, CONTRIBUTION, None).await;
+    assert_eq!(result.return_value(), Err(Error::NotParticipant));
+
+    let fund_balance = client.balance(fund).await?;
+    for round in 1..=3u32 {
+        for member in &members {
+            let deposit = fund_at(fund).call(|fund| fund.deposit());
+            let result = client
+                .call(member, deposit, CONTRIBUTION, None)
+                .await
+                .expect("deposit failed");
+            assert!(result.contains_event("Contracts", "ContractEmitted"));
+        }
+        assert_eq!(client.balance(fund).await?, fund_balance + 3 * CONTRIBUTION);
+
+        // The round has to be ended before it can be drawn.
+        let draw = fund_at(fund).call(|fund| fund.draw());
+        let result = client.call_dry_run(&ink_e2e::alice(), &draw, 0, None).await;
+        assert_eq!(result.return_value(), Err(Error::ChitFundNotFinished));
+
+        let end_cycle = fund_at(fund).call(|fund| fund.end_cycle());
+        client.call(&ink_e2e::alice(), end_cycle, 0, None).await.expect("end_cycle failed");
+        let draw = fund_at(fund).call(|fund| fund.draw());
+        let result = client.call(&ink_e2e::alice(), draw, 0, None).await.expect("draw failed");
+        assert!(result.contains_event("Contracts", "ContractEmitted"));
+        assert!(result.contains_event("Balances", "Transfer"));
+
+        // The whole pot went to the round's winner.
+        assert_eq!(client.balance(fund).await?, fund_balance);
+        let winners = fund_at(fund).call(|fund| fund.get_winners());
+        let winners = client.call_dry_run(&ink_e2e::alice(), &winners, 0, None).await.return_value();
+        assert_eq!(winners.len(), round as usize);
+
+        if round < 3 {
+            let begin_cycle = fund_at(fund).call(|fund| fund.begin_cycle());
+            client.call(&ink_e2e::alice(), begin_cycle, 0, None).await.expect("begin_cycle failed");
+        }
+    }
+
+    let phase = fund_at(fund).call(|fund| fund.get_phase());
+    let phase = client.call_dry_run(&ink_e2e::alice(), &phase, 0, None).await.return_value();
+    assert_eq!(phase, FundPhase::Completed);
+    Ok(())
+}
@@ -0,0 +1,190 @@
+// Stable codes and descriptions for the contract's errors, so dapps can show why a call
+// failed without repeating the contract's checks.
+
+use scale::Decode;
+
+use crate::types::Error;
+
+impl Error {
+    // The error's code, which is also the byte it is encoded as.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::decode(&mut &[code][..]).ok()
+    }
+
+    pub fn description(self) -> &'static str {
+        use Error::*;
+        match self {
+            ParticipantsAlreadyFull => "The fund already has its maximum number of members.",
+            ChitFundHasFinished => "The round is not open.",
+            AlreadyJoined => "The account is already a member.",
+            CannotJoinMidCycle => "Members cannot join while a round is running.",
+            OnlyOwnerCanBeginCycle => "Only the organizer can begin a round.",
+            OnlyOwnerCanEndCycle => "Only the organizer can end a round.",
+            ChitFundNotFinished => "The round has to be ended before this can be done.",
+            NotParticipant => "The account is not a member of the fund.",
+            OnlyAdminCanDraw => "Only the organizer can run the draw.",
+            ChitFundAlreadyFinished => "The round has already been closed.",
+            FailedToGetWinner => "No eligible member could be drawn as the winner.",
+            OnlyAdminCanWithdraw => "Only the treasurer can withdraw funds.",
+            FundNotCancelled => "The fund has not been cancelled.",
+            TransferFailed => "Sending funds from the contract failed.",
+            OnlyAdminCanChangeContribution => "Only the organizer can change the contribution.",
+            CannotChangeMidCycle => "The contribution can only be changed between rounds.",
+            InvalidContributionAmount => "The contribution has to be greater than zero.",
+            OnlyAdminCanRegister => "Only the organizer can register members on their behalf.",
+            IncorrectContributionAmount => "The amount sent does not match what is due.",
+            AlreadyDeposited => "The member has already paid for this round.",
+            ContributionsIncomplete => "Not every member has paid for the round yet.",
+            AlreadyCommitted => "A randomness commitment was already made this round.",
+            NoCommitment => "There is no randomness commitment to reveal.",
+            AlreadyRevealed => "The commitment has already been revealed.",
+            InvalidReveal => "The revealed value does not match the commitment.",
+            RevealPeriodOver => "The reveal period has ended.",
+            RevealPeriodOpen => "The draw has to wait until the reveal period ends.",
+            CommitRevealDisabled => "The fund does not use commit-reveal randomness.",
+            RandomnessUnavailable => "The chain could not provide randomness for the draw.",
+            ChitFundTerminated => "The fund has been terminated.",
+            PayoutTransferFailed => "Sending the prize failed; the winner can claim it later.",
+            NoPrizeToClaim => "There is no prize to claim.",
+            ArithmeticOverflow => "An amount was too large to calculate with.",
+            WrongFundMode => "This is not available in the fund's mode.",
+            AlreadyWon => "The member has already won a round.",
+            InvalidBid => "The bid has to be greater than zero.",
+            BidTooHigh => "The bid is larger than the prize.",
+            NoBids => "Nobody has bid in this round.",
+            NoCommissionToWithdraw => "There is no commission to withdraw.",
+            RoundExpired => "The round's deadline has passed.",
+            RoundNotExpired => "The round's deadline has not passed yet.",
+            OnlyAdminCanExpel => "Only the organizer can expel members.",
+            NotEnoughMissedRounds => "The member has not missed enough rounds to be expelled.",
+            CannotLeaveMidCycle => "Members cannot leave once the fund has started.",
+            OnlyAdminCanCancel => "Only the admin can cancel the fund.",
+            FundCancelled => "The fund has been cancelled.",
+            RefundAlreadyClaimed => "The refund has already been claimed.",
+            NothingToRefund => "There is nothing to refund.",
+            OnlyAdminCanTransferAdmin => "Only the admin can hand over the admin role.",
+            NotPendingAdmin => "The account has not been offered the admin role.",
+            RequiresMultisig => "This action needs the co-admins' approval.",
+            MultisigDisabled => "The fund has no co-admins.",
+            NotCoAdmin => "The account is not a co-admin.",
+            ProposalNotFound => "There is no such proposal.",
+            ProposalExpired => "The proposal has expired.",
+            ProposalAlreadyExecuted => "The proposal has already been carried out.",
+            AlreadyApproved => "The proposal has already been approved by this account.",
+            NotEnoughApprovals => "The proposal does not have enough approvals yet.",
+            NativeValueNotAccepted => "The fund collects tokens, so no native currency may be sent.",
+            InsufficientAllowance => "The fund is not allowed to take enough tokens.",
+            TokenTransferFailed => "The token transfer failed.",
+            NotAllowlisted => "The account has not been invited to join.",
+            OnlyAdminCanManageAllowlist => "Only the organizer can manage invitations.",
+            IncorrectCollateralAmount => "The amount sent does not match the collateral required.",
+            NoCollateral => "There is no collateral to return.",
+            CollateralLocked => "Collateral is only returned once the fund has completed.",
+            ContractPaused => "The fund is paused.",
+            OnlyAdminCanPause => "Only the admin can pause the fund.",
+            AlreadyPaused => "The fund is already paused.",
+            NotPaused => "The fund is not paused.",
+            OnlyAdminCanUpgrade => "Only the admin can upgrade the contract.",
+            UpgradeFailed => "The contract code could not be replaced.",
+            AlreadyMigrated => "Storage has already been migrated.",
+            NoDividends => "There are no dividends to claim.",
+            OnlyAdminCanStart => "Only the organizer can start the fund.",
+            FundNotStarted => "The fund has not started yet.",
+            FundAlreadyStarted => "The fund has already started.",
+            NotEnoughParticipants => "The fund does not have enough members to start.",
+            MembershipLocked => "Membership is closed once the fund has started.",
+            RoundAlreadyDrawn => "The round already has a winner.",
+            NotPaidUp => "Only members who paid the last round can do this.",
+            ExpulsionAlreadyProposed => "An expulsion vote is already open for the member.",
+            CannotVoteOnOwnExpulsion => "Members cannot vote on their own expulsion.",
+            AlreadyVoted => "The account has already voted.",
+            QuorumNotReached => "Not enough members have voted yet.",
+            ReentrantCall => "The fund cannot be called back into during a payout.",
+            OnlyAdminCanManageYield => "Only the admin can manage the yield strategy.",
+            NoYieldStrategy => "No yield strategy is set.",
+            YieldStrategyKilled => "The yield strategy has been switched off.",
+            YieldStrategyInUse => "Funds are still invested in the current strategy.",
+            YieldStrategyFailed => "The yield strategy call failed.",
+            NothingToInvest => "There is nothing in the pot to invest.",
+            OnlyAdminCanManageRoles => "Only the admin can grant or revoke roles.",
+            RoleAlreadyGranted => "The account already has the role.",
+            RoleNotGranted => "The account does not have the role.",
+            TooEarlyToPoke => "Reminders can only be sent once half the round has passed.",
+            AlreadyPoked => "Reminders have already been sent this round.",
+            NoDefaulters => "Every member has paid this round.",
+            NoTerms => "The fund has no terms to accept.",
+            TermsMismatch => "The terms accepted are not the fund's current terms.",
+            TermsNotAccepted => "The account has to accept the fund's terms first.",
+            NotInDefault => "The member has not missed any rounds.",
+            GracePeriodActive => "The member's grace period to cure the default has not ended.",
+            SeatAlreadyWon => "A seat that has won cannot be transferred.",
+            SeatTransferred => "The account transferred its seat and cannot take part any more.",
+            NoSeatTransfer => "No transfer of the seat has been requested.",
+            OnlyAdminCanApproveTransfer => "Only the organizer can approve seat transfers.",
+            CannotVoteOnOwnTransfer => "Members cannot vote on transferring their own seat.",
+            OnlyAdminCanCatchUp => "Only the organizer can schedule a catch-up.",
+            RoundNotPayable => "The rounds given cannot be paid for now.",
+            DepositsClosed => "Contributions are only accepted while a round is open.",
+            NoSurplus => "The contract holds nothing beyond what the fund owes.",
+            Blacklisted => "The organizer has blacklisted the account after an earlier fund.",
+            RegistryUnavailable => "The registry the fund checks joiners against could not be reached.",
+            ReputationTooLow => "The account's reputation is below what the fund requires.",
+            OnlyAdminCanVoid => "Only the organizer can void a round.",
+            RoundStillDrawable => "A member can still win the round, so it cannot be voided.",
+            RoundVoided => "The round has been voided.",
+            FundNotCompleted => "The fund has not completed yet.",
+            PenaltyPoolAlreadyDistributed => "The penalty pool has already been distributed.",
+            PayoutPending => "The drawn winner's payout is waiting for the dispute window to close.",
+            NoPendingPayout => "No payout is waiting for the dispute window to close.",
+            DisputeWindowClosed => "The window for disputing the draw has closed.",
+            DisputeWindowOpen => "The draw can still be disputed.",
+            DisputeAlreadyRaised => "A dispute has already been raised against this winner.",
+            NoDispute => "No dispute has been raised against this winner.",
+            CannotDisputeOwnWin => "Winners cannot dispute their own draw.",
+            OnlyAdminCanTerminate => "Only the treasurer can terminate the contract.",
+            FundStillRunning => "The fund has not completed or been cancelled.",
+            ClaimsOutstanding => "Members or the organizer still have funds to claim.",
+            OnlyAdminCanSetRotation => "Only the organizer can set the rotation.",
+            InvalidRotation => "The rotation has to list every member exactly once.",
+            OnlyAdminCanSetParameters => "Only the organizer can change the fund's parameters.",
+            ParametersLocked => "The fund's parameters can only change before it starts or holds any money.",
+            InvalidParticipantLimit => "The member limit has to be at least one and no lower than the current membership.",
+            AdminCannotParticipate => "The fund's admin cannot hold a seat in it.",
+            OnlyAdminCanFundBonus => "Only the organizer can fund the bonus pool.",
+            BonusFundingClosed => "The bonus pool can only be funded while the fund is recruiting.",
+            IncorrectBonusAmount => "The bonus has to be more than zero and match the value sent.",
+            InvalidTicketCount => "A member has to hold at least one ticket and no more than the fund allows.",
+            InvalidBatch => "The batch is empty or its amounts do not add up to the value sent.",
+            OnlyAdminCanSetHook => "Only the admin can register the lifecycle hook.",
+            WrongRound => "The payment is for a round that is not open.",
+            MigrationPending => "The fund's storage has to be migrated to the current version first.",
+            UnsupportedStorageVersion => "There is no migration from the stored layout version.",
+            SealedPrizeExpired => "The claim period of the sealed prize has passed.",
+            SealedPrizeNotExpired => "The sealed prize can still be claimed by its winner.",
+            EscrowNotExpired => "The round can still be drawn, so its contributions cannot be reclaimed yet.",
+            EscrowExpired => "The round waited too long for its draw and its contributions can be reclaimed.",
+            AdvancesDisabled => "The fund does not offer advances against prizes.",
+            InvalidAdvanceAmount => "The advance has to be more than zero and within the fund's limit.",
+            AdvanceOutstanding => "The member has an advance that is not repaid yet.",
+            NoAdvanceRequest => "The member has not requested an advance.",
+            CannotVoteOnOwnAdvance => "Members cannot vote on their own advance.",
+            InsufficientAdvanceFunds => "The commission collected so far cannot cover the advance.",
+            NoDexRouter => "The fund has no DEX router to convert prizes through.",
+            InvalidPayoutPreference => "Prizes can only be converted into a token other than the fund's own.",
+            SwapFailed => "The DEX router could not convert the prize at the accepted rate.",
+            DrawDeferred => "The draw landed on a ticket that cannot win and has to be run again in a later block.",
+            InvalidReferrer => "The referrer has to be a member other than the caller.",
+            UnknownMessage => "The call matched no message, and the fund does not take donations.",
+            WinnerPayoutPending => "The member's prize is awaiting the end of its dispute window, so their seat cannot change hands or be given up.",
+            SealingDisabled => "The fund names its winners openly, so there is no round secret to commit to.",
+            RoundSecretMissing => "A sealed round cannot be drawn before the organizer has committed to its secret.",
+            WrongClaimSecret => "The secret does not match the sealed prize's commitment.",
+            NothingToRecover => "Nothing is stranded with the yield strategy.",
+            CommissionNotWithdrawn => "Withdraw the commission before renouncing the admin role.",
+        }
+    }
+}
@@ -0,0 +1,458 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Deploys ChitFund contracts from one uploaded code hash, so a single deployment can serve
+// many community groups, and keeps a registry of them for UIs to discover. Each organizer
+// also keeps a blacklist here that their funds check when someone joins, and every
+// account's reputation with each organizer is built up here from the rounds the
+// organizer's funds report.
+// The message decoder ink generates holds a whole `FundConfig` by value.
+#[allow(clippy::large_enum_variant)]
+#[ink::contract]
+mod factory {
+    use chit_fund::{ChitFundRef, FundConfig};
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+    use ink::ToAccountId;
+
+    // Reputation gained for each round paid on time and lost for each round defaulted on.
+    // A default weighs more so one missed round is not paid off by a single good one.
+    pub const ON_TIME_REPUTATION: i32 = 1;
+    pub const DEFAULT_REPUTATION: i32 = -3;
+
+    #[ink(storage)]
+    pub struct ChitFundFactory {
+        pub owner: AccountId,
+        // Code hash of the uploaded ChitFund contract that new funds are instantiated from.
+        pub fund_code_hash: Hash,
+        // Every fund created here by position, oldest first, and how many there are. Kept
+        // one entry per fund so creating a fund writes a fixed amount however many exist.
+        pub funds: Mapping<u32, AccountId>,
+        pub fund_count: u32,
+        // Funds created by each organizer by (organizer, position), oldest first, and how many
+        // each organizer has created.
+        pub funds_by_organizer: Mapping<(AccountId, u32), AccountId>,
+        pub organizer_fund_count: Mapping<AccountId, u32>,
+        // Organizer of each fund created here, so a fund's calls act on its organizer's list.
+        pub fund_organizer: Mapping<AccountId, AccountId>,
+        // Accounts each organizer's funds turn away, keyed by (organizer, account).
+        pub blacklist: Mapping<(AccountId, AccountId), bool>,
+        // Reputation of each account with each organizer, keyed by (organizer, account), built
+        // from the rounds of that organizer's funds only. An organizer running funds of sock
+        // puppets can inflate reputation with themselves but not with anyone else. Accounts
+        // start at zero.
+        pub reputation: Mapping<(AccountId, AccountId), i32>,
+    }
+
+    #[ink(event)]
+    pub struct FundCreated {
+        #[ink(topic)]
+        organizer: Option<AccountId>,
+        #[ink(topic)]
+        fund: Option<AccountId>,
+        max_participants: u32,
+        monthly_contribution: Balance,
+    }
+
+    #[ink(event)]
+    pub struct FundCodeHashChanged {
+        #[ink(topic)]
+        owner: Option<AccountId>,
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct AccountBlacklisted {
+        #[ink(topic)]
+        organizer: Option<AccountId>,
+        #[ink(topic)]
+        account: Option<AccountId>,
+        // Fund that expelled the account, or None if the organizer added it by hand.
+        fund: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct AccountUnblacklisted {
+        #[ink(topic)]
+        organizer: Option<AccountId>,
+        #[ink(topic)]
+        account: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct RoundReported {
+        #[ink(topic)]
+        fund: Option<AccountId>,
+        on_time: u32,
+        defaulted: u32,
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        OnlyOwnerCanSetCodeHash,
+        InstantiationFailed,
+        NotAFund,
+        AlreadyBlacklisted,
+        NotBlacklisted,
+        UnrecognizedFundCode,
+    }
+
+    impl ChitFundFactory {
+        #[ink(constructor)]
+        pub fn new(fund_code_hash: Hash) -> Self {
+            Self {
+                owner: Self::env().caller(),
+                fund_code_hash,
+                funds: Mapping::default(),
+                fund_count: 0,
+                funds_by_organizer: Mapping::default(),
+                organizer_fund_count: Mapping::default(),
+                fund_organizer: Mapping::default(),
+                blacklist: Mapping::default(),
+                reputation: Mapping::default(),
+            }
+        }
+
+        // Instantiate a new fund with the caller as its admin and record it in the registry.
+        #[ink(message)]
+        pub fn create_fund(
+            &mut self,
+            max_participants: u32,
+            monthly_contribution: Balance,
+            config: FundConfig,
+        ) -> Result<AccountId, Error> {
+            let organizer = self.env().caller();
+            // Salting with the organizer and the registry size gives every fund its own address.
+            let salt = scale::Encode::encode(&(organizer, self.fund_count));
+            let config = FundConfig {
+                blacklist_registry: Some(self.env().account_id()),
+                ..config
+            };
+            let fund: ChitFundRef = ChitFundRef::with_config(organizer, max_participants, monthly_contribution, config)
+                .code_hash(self.fund_code_hash)
+                .endowment(0)
+                .salt_bytes(salt)
+                .try_instantiate()
+                .map_err(|_| Error::InstantiationFailed)?
+                .map_err(|_| Error::InstantiationFailed)?;
+            let fund = fund.to_account_id();
+
+            let organized = self.organizer_fund_count.get(organizer).unwrap_or(0);
+            self.funds_by_organizer.insert((organizer, organized), &fund);
+            self.organizer_fund_count.insert(organizer, &(organized + 1));
+            self.funds.insert(self.fund_count, &fund);
+            self.fund_count += 1;
+            self.fund_organizer.insert(fund, &organizer);
+            self.env().emit_event(FundCreated {
+                organizer: Some(organizer),
+                fund: Some(fund),
+                max_participants,
+                monthly_contribution,
+            });
+            Ok(fund)
+        }
+
+        // Point new funds at different ChitFund code. Funds already created are unaffected.
+        #[ink(message)]
+        pub fn set_fund_code_hash(&mut self, code_hash: Hash) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if sender != self.owner {
+                return Err(Error::OnlyOwnerCanSetCodeHash);
+            }
+            self.fund_code_hash = code_hash;
+            self.env().emit_event(FundCodeHashChanged {
+                owner: Some(sender),
+                code_hash,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_fund_code_hash(&self) -> Hash {
+            self.fund_code_hash
+        }
+
+        #[ink(message)]
+        pub fn get_funds_by_organizer(&self, organizer: AccountId) -> Vec<AccountId> {
+            let count = self.organizer_fund_count.get(organizer).unwrap_or(0);
+            (0..count).filter_map(|index| self.funds_by_organizer.get((organizer, index))).collect()
+        }
+
+        #[ink(message)]
+        pub fn get_all_funds(&self) -> Vec<AccountId> {
+            self.get_funds(0, self.fund_count)
+        }
+
+        #[ink(message)]
+        pub fn get_fund_count(&self) -> u32 {
+            self.fund_count
+        }
+
+        // Up to `limit` funds from position `start`, oldest first, for UIs paging through a
+        // registry too large to read in one call.
+        #[ink(message)]
+        pub fn get_funds(&self, start: u32, limit: u32) -> Vec<AccountId> {
+            let end = start.saturating_add(limit).min(self.fund_count);
+            (start..end).filter_map(|index| self.funds.get(index)).collect()
+        }
+
+        // Turn the account away from the caller's funds.
+        #[ink(message)]
+        pub fn blacklist_account(&mut self, account: AccountId) -> Result<(), Error> {
+            let organizer = self.env().caller();
+            self.add_to_blacklist(organizer, account, None)
+        }
+
+        #[ink(message)]
+        pub fn unblacklist_account(&mut self, account: AccountId) -> Result<(), Error> {
+            let organizer = self.env().caller();
+            if self.blacklist.take((organizer, account)).is_none() {
+                return Err(Error::NotBlacklisted);
+            }
+            self.env().emit_event(AccountUnblacklisted {
+                organizer: Some(organizer),
+                account: Some(account),
+            });
+            Ok(())
+        }
+
+        // Called by a fund created here when it expels the account, blacklisting it with the
+        // fund's organizer.
+        #[ink(message)]
+        pub fn report_default(&mut self, account: AccountId) -> Result<(), Error> {
+            let fund = self.env().caller();
+            let organizer = self.fund_organizer.get(fund).ok_or(Error::NotAFund)?;
+            if self.blacklist.contains((organizer, account)) {
+                return Ok(());
+            }
+            self.add_to_blacklist(organizer, account, Some(fund))
+        }
+
+        // Whether the account is on the blacklist of the calling fund's organizer, or of the
+        // caller themselves when the caller is not a fund.
+        #[ink(message)]
+        pub fn is_blacklisted(&self, account: AccountId) -> bool {
+            let caller = self.env().caller();
+            let organizer = self.fund_organizer.get(caller).unwrap_or(caller);
+            self.blacklist.contains((organizer, account))
+        }
+
+        #[ink(message)]
+        pub fn is_blacklisted_by(&self, organizer: AccountId, account: AccountId) -> bool {
+            self.blacklist.contains((organizer, account))
+        }
+
+        // Called by a fund created here when it closes a round, with the members who paid it
+        // on time and those who did not pay at all. A fund can `upgrade` to any code, so the
+        // report only counts while the fund still runs the code new funds are created from.
+        #[ink(message)]
+        pub fn report_round(&mut self, on_time: Vec<AccountId>, defaulted: Vec<AccountId>) -> Result<(), Error> {
+            let fund = self.env().caller();
+            let organizer = self.fund_organizer.get(fund).ok_or(Error::NotAFund)?;
+            if self.code_hash_of(fund) != Some(self.fund_code_hash) {
+                return Err(Error::UnrecognizedFundCode);
+            }
+            for account in &on_time {
+                self.adjust_reputation(organizer, *account, ON_TIME_REPUTATION);
+            }
+            for account in &defaulted {
+                self.adjust_reputation(organizer, *account, DEFAULT_REPUTATION);
+            }
+            self.env().emit_event(RoundReported {
+                fund: Some(fund),
+                on_time: on_time.len() as u32,
+                defaulted: defaulted.len() as u32,
+            });
+            Ok(())
+        }
+
+        // The account's reputation with the calling fund's organizer, or with the caller
+        // themselves when the caller is not a fund.
+        #[ink(message)]
+        pub fn get_reputation(&self, account: AccountId) -> i32 {
+            let caller = self.env().caller();
+            let organizer = self.fund_organizer.get(caller).unwrap_or(caller);
+            self.get_reputation_with(organizer, account)
+        }
+
+        #[ink(message)]
+        pub fn get_reputation_with(&self, organizer: AccountId, account: AccountId) -> i32 {
+            self.reputation.get((organizer, account)).unwrap_or(0)
+        }
+
+        fn adjust_reputation(&mut self, organizer: AccountId, account: AccountId, change: i32) {
+            let reputation = self.get_reputation_with(organizer, account).saturating_add(change);
+            self.reputation.insert((organizer, account), &reputation);
+        }
+
+        // Code the fund runs now, None if it is not a contract.
+        #[cfg(not(test))]
+        fn code_hash_of(&self, fund: AccountId) -> Option<Hash> {
+            self.env().code_hash(&fund).ok()
+        }
+
+        // The off-chain environment cannot look code hashes up, so tests set them.
+        #[cfg(test)]
+        fn code_hash_of(&self, fund: AccountId) -> Option<Hash> {
+            crate::tests::code_hash_of(fund)
+        }
+
+        fn add_to_blacklist(&mut self, organizer: AccountId, account: AccountId, fund: Option<AccountId>) -> Result<(), Error> {
+            if self.blacklist.contains((organizer, account)) {
+                return Err(Error::AlreadyBlacklisted);
+            }
+            self.blacklist.insert((organizer, account), &true);
+            self.env().emit_event(AccountBlacklisted {
+                organizer: Some(organizer),
+                account: Some(account),
+                fund,
+            });
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::factory::{ChitFundFactory, Error, DEFAULT_REPUTATION, ON_TIME_REPUTATION};
+    use ink::env::{test, DefaultEnvironment};
+    use ink::primitives::{AccountId, Hash};
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    thread_local! {
+        static CODE_HASHES: RefCell<BTreeMap<AccountId, Hash>> = const { RefCell::new(BTreeMap::new()) };
+    }
+
+    pub fn code_hash_of(account: AccountId) -> Option<Hash> {
+        CODE_HASHES.with(|hashes| hashes.borrow().get(&account).copied())
+    }
+
+    fn set_code_hash(account: AccountId, code_hash: Hash) {
+        CODE_HASHES.with(|hashes| hashes.borrow_mut().insert(account, code_hash));
+    }
+
+    #[ink::test]
+    fn test_new_factory_is_empty() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let factory = ChitFundFactory::new(Hash::from([0x01; 32]));
+        assert_eq!(factory.owner, accounts.alice);
+        assert_eq!(factory.get_fund_code_hash(), Hash::from([0x01; 32]));
+        assert!(factory.get_all_funds().is_empty());
+        assert!(factory.get_funds_by_organizer(accounts.alice).is_empty());
+    }
+
+    #[ink::test]
+    fn test_registry_pages_through_funds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut factory = ChitFundFactory::new(Hash::from([0x01; 32]));
+        // Funds cannot be instantiated off-chain, so register them by hand.
+        let funds: Vec<AccountId> = (0..3u8).map(|byte| AccountId::from([0x10 + byte; 32])).collect();
+        for (index, fund) in funds.iter().enumerate() {
+            factory.funds.insert(index as u32, fund);
+        }
+        factory.fund_count = 3;
+        factory.funds_by_organizer.insert((accounts.bob, 0), &funds[1]);
+        factory.organizer_fund_count.insert(accounts.bob, &1);
+
+        assert_eq!(factory.get_fund_count(), 3);
+        assert_eq!(factory.get_all_funds(), funds);
+        assert_eq!(factory.get_funds(1, 5), funds[1..].to_vec());
+        assert!(factory.get_funds(3, 5).is_empty());
+        assert_eq!(factory.get_funds_by_organizer(accounts.bob), vec![funds[1]]);
+    }
+
+    #[ink::test]
+    fn test_only_owner_sets_code_hash() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut factory = ChitFundFactory::new(Hash::from([0x01; 32]));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(factory.set_fund_code_hash(Hash::from([0x02; 32])), Err(Error::OnlyOwnerCanSetCodeHash));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(factory.set_fund_code_hash(Hash::from([0x02; 32])), Ok(()));
+        assert_eq!(factory.get_fund_code_hash(), Hash::from([0x02; 32]));
+    }
+
+    #[ink::test]
+    fn test_blacklist_is_per_organizer() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut factory = ChitFundFactory::new(Hash::from([0x01; 32]));
+        // Funds cannot be instantiated off-chain, so register one by hand.
+        let fund = AccountId::from([0x10; 32]);
+        factory.fund_organizer.insert(fund, &accounts.bob);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(factory.blacklist_account(accounts.eve), Ok(()));
+        assert_eq!(factory.blacklist_account(accounts.eve), Err(Error::AlreadyBlacklisted));
+        assert!(factory.is_blacklisted(accounts.eve));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert!(!factory.is_blacklisted(accounts.eve));
+        assert!(factory.is_blacklisted_by(accounts.bob, accounts.eve));
+
+        // Only funds created here can report, and their reports land on their organizer's list.
+        assert_eq!(factory.report_default(accounts.django), Err(Error::NotAFund));
+        test::set_caller::<DefaultEnvironment>(fund);
+        assert_eq!(factory.report_default(accounts.django), Ok(()));
+        assert_eq!(factory.report_default(accounts.django), Ok(()));
+        assert!(factory.is_blacklisted(accounts.django));
+        assert!(factory.is_blacklisted_by(accounts.bob, accounts.django));
+        assert!(!factory.is_blacklisted_by(accounts.charlie, accounts.django));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(factory.unblacklist_account(accounts.django), Ok(()));
+        assert_eq!(factory.unblacklist_account(accounts.django), Err(Error::NotBlacklisted));
+        assert!(!factory.is_blacklisted(accounts.django));
+    }
+
+    #[ink::test]
+    fn test_reputation_from_reported_rounds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut factory = ChitFundFactory::new(Hash::from([0x01; 32]));
+        let fund = AccountId::from([0x10; 32]);
+        factory.fund_organizer.insert(fund, &accounts.bob);
+        set_code_hash(fund, Hash::from([0x01; 32]));
+        assert_eq!(factory.get_reputation(accounts.charlie), 0);
+
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(factory.report_round(vec![accounts.charlie], Vec::new()), Err(Error::NotAFund));
+
+        test::set_caller::<DefaultEnvironment>(fund);
+        assert_eq!(factory.report_round(vec![accounts.charlie, accounts.django], vec![accounts.eve]), Ok(()));
+        assert_eq!(factory.report_round(vec![accounts.charlie], vec![accounts.django]), Ok(()));
+        assert_eq!(factory.get_reputation(accounts.charlie), 2 * ON_TIME_REPUTATION);
+        assert_eq!(factory.get_reputation(accounts.django), ON_TIME_REPUTATION + DEFAULT_REPUTATION);
+        assert_eq!(factory.get_reputation(accounts.eve), DEFAULT_REPUTATION);
+    }
+
+    #[ink::test]
+    fn test_reputation_is_scoped_to_organizer_and_trusted_code() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut factory = ChitFundFactory::new(Hash::from([0x01; 32]));
+        let fund = AccountId::from([0x10; 32]);
+        let other_fund = AccountId::from([0x11; 32]);
+        factory.fund_organizer.insert(fund, &accounts.bob);
+        factory.fund_organizer.insert(other_fund, &accounts.charlie);
+        set_code_hash(fund, Hash::from([0x01; 32]));
+        set_code_hash(other_fund, Hash::from([0x01; 32]));
+
+        // Reputation earned in bob's funds counts with bob only.
+        test::set_caller::<DefaultEnvironment>(fund);
+        assert_eq!(factory.report_round(vec![accounts.eve], Vec::new()), Ok(()));
+        assert_eq!(factory.get_reputation_with(accounts.bob, accounts.eve), ON_TIME_REPUTATION);
+        assert_eq!(factory.get_reputation_with(accounts.charlie, accounts.eve), 0);
+        test::set_caller::<DefaultEnvironment>(other_fund);
+        assert_eq!(factory.get_reputation(accounts.eve), 0);
+
+        // A fund upgraded to code the factory does not deploy cannot report.
+        set_code_hash(fund, Hash::from([0x02; 32]));
+        test::set_caller::<DefaultEnvironment>(fund);
+        assert_eq!(factory.report_round(vec![accounts.eve], Vec::new()), Err(Error::UnrecognizedFundCode));
+        assert_eq!(factory.get_reputation(accounts.eve), ON_TIME_REPUTATION);
+    }
+}
@@ -0,0 +1,71 @@
+// Cross-contract notifications to a hook contract the admin registers with `set_hook`, so
+// insurance, credit-scoring or notification contracts can follow the fund. A hook
+// implements these messages under the `ChitFundHook` trait's selectors:
+//
+//     on_round_settled(round: u32, winner: AccountId, prize: Balance)
+//     on_member_default(account: AccountId, round: u32)
+//     on_fund_completed(rounds_completed: u32)
+//
+// The fund calls them after its own state has changed and never depends on the outcome.
+
+use ink::env::call::{build_call, ExecutionInput, Selector};
+use ink::primitives::AccountId;
+
+use crate::CustomEnvironment;
+
+type Balance = <CustomEnvironment as ink::env::Environment>::Balance;
+
+// Which notification a failed call was for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Notification {
+    RoundSettled,
+    MemberDefault,
+    FundCompleted,
+}
+
+// The call failed or the hook reverted.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HookError;
+
+pub fn on_round_settled(hook: AccountId, round: u32, winner: AccountId, prize: Balance) -> Result<(), HookError> {
+    build_call::<CustomEnvironment>()
+        .call(hook)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("ChitFundHook::on_round_settled")))
+                .push_arg(round)
+                .push_arg(winner)
+                .push_arg(prize),
+        )
+        .returns::<()>()
+        .try_invoke()
+        .map_err(|_| HookError)?
+        .map_err(|_| HookError)
+}
+
+pub fn on_member_default(hook: AccountId, account: AccountId, round: u32) -> Result<(), HookError> {
+    build_call::<CustomEnvironment>()
+        .call(hook)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("ChitFundHook::on_member_default")))
+                .push_arg(account)
+                .push_arg(round),
+        )
+        .returns::<()>()
+        .try_invoke()
+        .map_err(|_| HookError)?
+        .map_err(|_| HookError)
+}
+
+pub fn on_fund_completed(hook: AccountId, rounds_completed: u32) -> Result<(), HookError> {
+    build_call::<CustomEnvironment>()
+        .call(hook)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("ChitFundHook::on_fund_completed")))
+                .push_arg(rounds_completed),
+        )
+        .returns::<()>()
+        .try_invoke()
+        .map_err(|_| HookError)?
+        .map_err(|_| HookError)
+}
@@ -1,10 +1,124 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-#[ink::contract]
+use ink::env::{DefaultEnvironment, Environment};
+
+mod errors;
+mod dex_router;
+mod hooks;
+mod migrations;
+mod psp22;
+mod psp34;
+mod registry;
+mod scheduler;
+pub mod types;
+mod yield_strategy;
+
+#[cfg(test)]
+mod simulation;
+
+#[cfg(all(test, feature = "e2e-tests"))]
+mod e2e_tests;
+
+#[cfg(all(test, feature = "e2e-tests"))]
+mod e2e_benchmarks;
+
+// Chain extension for chains that expose a VRF or randomness pallet to contracts.
+#[ink::chain_extension]
+pub trait RandomnessExtension {
+    type ErrorCode = RandomnessError;
+
+    // Returns 32 random bytes for the given subject.
+    #[ink(extension = 1101)]
+    fn fetch_random(subject: [u8; 32]) -> [u8; 32];
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RandomnessError {
+    FailGetRandomSource,
+}
+
+impl ink::env::chain_extension::FromStatusCode for RandomnessError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            _ => Err(Self::FailGetRandomSource),
+        }
+    }
+}
+
+// The default environment with the randomness chain extension attached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum CustomEnvironment {}
+
+impl Environment for CustomEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = RandomnessExtension;
+}
+
+pub use self::my_contract::{
+    BonusSplit, ChitFund, ChitFundRef, FundConfig, FundMode, PayoutSchedule, RandomnessSource, RedrawPolicy, RemainderPolicy, Role,
+    Rounding,
+};
+pub use self::dex_router::DexRouter;
+pub use self::scheduler::{Deadline, ScheduleSource};
+pub use self::yield_strategy::YieldStrategy;
+
+// The constructor decoder ink generates holds a whole `FundConfig` by value.
+#[allow(clippy::large_enum_variant)]
+#[ink::contract(env = crate::CustomEnvironment)]
 mod my_contract {
-    use ink::{prelude::vec::Vec};
-    use ink_env::emit_event;
-    use ink::prelude::*;
+    use ink::prelude::{string::String, vec::Vec};
+    use ink::env::hash::{Blake2x256, HashOutput};
+    use ink::storage::{Lazy, Mapping};
+    use crate::psp22::{self, PSP22Error, TokenError};
+    use crate::dex_router;
+    use crate::hooks::{self, HookError, Notification};
+    use crate::migrations::{self, Step};
+    use crate::psp34;
+    use crate::registry;
+    use crate::scheduler::{Deadline, ScheduleSource};
+    pub use crate::types::{Error, FundPhase, MemberInfo, RoundRecord, RoundSnapshot, RoundTally};
+    use crate::yield_strategy;
+
+    // Number of blocks a multisig proposal stays open for.
+    pub const PROPOSAL_LIFETIME: BlockNumber = 100;
+
+    // Basis points in 100%.
+    pub const MAX_BPS: u16 = 10_000;
+
+    // Most decimals a currency can have for one whole unit to still fit in a `Balance`.
+    pub const MAX_DECIMALS: u8 = 38;
+
+    // Number of blocks after `end_cycle` during which committed secrets can be revealed.
+    pub const REVEAL_PERIOD: BlockNumber = 10;
+
+    // Version of the storage layout this code reads and writes. Bump it, and add a step
+    // converting the previous layout to `migrations`, whenever the storage changes. Fields
+    // added since version 1 are kept out of the root cell, in a `Lazy` or a `Mapping`, so
+    // the root cell older code wrote still decodes; add new fields the same way.
+    pub const STORAGE_VERSION: u32 = 2;
+
+    // Blocks a closed round can wait for its draw before its contributors can take their
+    // contributions back with `reclaim_if_expired`, about 30 days at 6 second blocks.
+    pub const ESCROW_TIMEOUT: BlockNumber = 432_000;
+
+    // Seeds `RedrawPolicy::Rehash` tries before walking forward like `LinearProbe`.
+    pub const MAX_REHASHES: u32 = 8;
+
+    // Units of the fund's currency a `PayoutPreference::min_rate` is quoted for.
+    pub const RATE_SCALE: Balance = 1_000_000_000_000;
+
+    // Release of this contract, reported by `get_contract_info`.
+    pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
     #[ink(storage)]
     pub struct ChitFund {
@@ -12,28 +126,726 @@ mod my_contract {
         pub max_participants: u32,
         pub monthly_contribution: Balance,
         pub current_round: u32,
+        // Deposits collected during the open round. Moved into `total_amount` by `end_cycle`.
         pub pot: Balance,
+        // Collected amount waiting to be paid out by the next `draw`. Left untouched by
+        // `begin_cycle` and cleared once the winner has been paid.
         pub total_amount: Balance,
-        pub participants: Vec<AccountId>,
-        pub used_indexes: Vec<AccountId>,
-        pub finished: bool,
+        // Participants by account, plus a dense index over them for the few places that
+        // have to walk the whole membership.
+        pub members: Mapping<AccountId, MemberInfo>,
+        pub member_index: Mapping<u32, AccountId>,
+        pub member_count: u32,
+        // Seats held across the membership. A member wins once per ticket, so the fund runs
+        // one round per ticket.
+        pub ticket_count: u32,
+        // Payouts made so far in this chit cycle, one per ticket won; `wins` in `members`
+        // records whose.
+        pub winner_count: u32,
+        // Where the fund is in its lifecycle; see `FundPhase`.
+        pub phase: FundPhase,
+        // Contribution ledger: amount paid by each participant in a given round.
+        pub deposits: Mapping<(AccountId, u32), Balance>,
+        // Consecutive rounds each participant has deposited in.
+        pub deposit_streaks: Mapping<AccountId, u32>,
+        // Total amount each participant has deposited over the life of the fund.
+        pub total_contributed: Mapping<AccountId, Balance>,
+        // Hashed secrets committed by participants for a round's draw.
+        pub commitments: Mapping<(AccountId, u32), Hash>,
+        // Participants whose secret for a round has been revealed.
+        pub revealed: Mapping<(AccountId, u32), bool>,
+        // Number of commitments and reveals made for each round.
+        pub commit_count: Mapping<u32, u32>,
+        pub reveal_count: Mapping<u32, u32>,
+        // XOR of all secrets revealed for each round, mixed into the draw seed.
+        pub revealed_entropy: Mapping<u32, [u8; 32]>,
+        // Last block at which secrets for the round awaiting the draw can be revealed.
+        pub reveal_deadline: BlockNumber,
+        pub randomness_source: RandomnessSource,
+        // Number of rounds that have been drawn and paid out.
+        pub rounds_completed: u32,
+        // Prizes whose direct transfer failed in `draw`, waiting for `claim_prize`.
+        pub unclaimed_prizes: Mapping<AccountId, Balance>,
+        pub mode: FundMode,
+        // Auction bids placed by each participant per round, and the lowest bid of each round.
+        pub bids: Mapping<(AccountId, u32), Balance>,
+        pub lowest_bids: Mapping<u32, (AccountId, Balance)>,
+        // Auction discounts credited to each participant and not yet claimed.
+        pub dividends: Mapping<AccountId, Balance>,
+        pub commission_bps: u16,
+        // Commission collected from prizes and not yet withdrawn by the admin.
+        pub commission_balance: Balance,
+        pub schedule: ScheduleSource,
+        // Tick of the schedule at which the current round opened, set by the constructor
+        // and `begin_cycle`.
+        pub round_start: u64,
+        pub late_fee_bps: u16,
+        pub max_missed_rounds: u32,
+        // Late fees collected so far, kept apart from the prize pot.
+        pub penalty_pool: Balance,
+        // Late fees paid by each participant.
+        pub penalties_paid: Mapping<AccountId, Balance>,
+        // Rounds each participant has failed to pay for.
+        pub missed_rounds: Mapping<AccountId, u32>,
+        // Gross prize each participant has been awarded.
+        pub prizes_won: Mapping<AccountId, Balance>,
+        // Set aside by `cancel_fund`: the funds to refund and the sum of the participants'
+        // net contributions they are shared by.
+        pub refund_pool: Balance,
+        pub refund_basis: Balance,
+        // Refund paid out to each participant of a cancelled fund.
+        pub refunds_claimed: Mapping<AccountId, Balance>,
+        // Account nominated by `transfer_admin`, waiting to call `accept_admin`.
+        pub pending_admin: Option<AccountId>,
+        pub co_admins: Vec<AccountId>,
+        pub approval_threshold: u32,
+        pub proposals: Mapping<u32, Proposal>,
+        // Co-admins who have approved each proposal.
+        pub approvals: Mapping<(u32, AccountId), bool>,
+        pub next_proposal_id: u32,
+        pub contribution_token: Option<AccountId>,
+        pub invite_only: bool,
+        pub allowlist: Mapping<AccountId, bool>,
+        pub collateral_required: Balance,
+        // Security deposit each participant still has held by the fund.
+        pub collateral: Mapping<AccountId, Balance>,
+        // Set by `pause`; blocks joining, deposits and the cycle lifecycle until `unpause`.
+        pub paused: bool,
+        // Layout version of the data in storage, brought up to `STORAGE_VERSION` by `migrate`.
+        pub storage_version: u32,
+        // Settled rounds, by round number.
+        pub round_history: Mapping<u32, RoundRecord>,
+        // The round awaiting settlement's part of its record.
+        pub round_tally: Lazy<RoundTally>,
+        // Membership of each closed round, by round number.
+        pub round_snapshots: Mapping<u32, RoundSnapshot>,
+        pub min_participants: u32,
+        // Where each participant wants prizes sent, when not to the participant's own account.
+        pub payout_addresses: Mapping<AccountId, AccountId>,
+        // Instalments paid towards a round's contribution that do not cover it yet.
+        pub partial_deposits: Mapping<(AccountId, u32), Balance>,
+        // Members' votes to expel a defaulter, keyed by the round they were opened in and
+        // the member they target.
+        pub expulsion_votes: Mapping<(u32, AccountId), ExpulsionVote>,
+        // Last round in which each voter backed expelling each target, by (target, voter).
+        pub expulsion_ballots: Mapping<(AccountId, AccountId), u32>,
+        pub paid_up_winners_only: bool,
+        // Held while a draw, refund, dividend or prize payout is in progress.
+        pub reentrancy_lock: bool,
+        pub payout_schedule: PayoutSchedule,
+        // Prizes being paid out in installments, by winner.
+        pub payout_plans: Mapping<AccountId, PayoutPlan>,
+        // Contract the open round's pot can be parked in to earn yield, see `invest_pot`.
+        pub yield_strategy: Option<AccountId>,
+        // Kill switch: set by `kill_yield_strategy` to stop investing until a strategy is set again.
+        pub yield_killed: bool,
+        // Part of the pot currently held by the strategy.
+        pub invested: Balance,
+        // Roles the admin has granted, see `Role`.
+        pub roles: Mapping<(AccountId, Role), bool>,
+        pub poke_bounty: Balance,
+        // Last round `poke_defaulters` issued reminders for.
+        pub last_poked_round: u32,
+        pub terms_hash: Option<Hash>,
+        pub terms_uri: Vec<u8>,
+        // Terms hash each account has accepted.
+        pub terms_accepted: Mapping<AccountId, Hash>,
+        // Changes to the terms put to the members, and who has voted for each.
+        pub terms_amendments: Mapping<u32, TermsAmendment>,
+        pub amendment_votes: Mapping<(u32, AccountId), bool>,
+        pub next_amendment_id: u32,
+        pub grace_period: u64,
+        // Missed contributions neither collateral nor prize installments covered, per member.
+        pub arrears: Mapping<AccountId, Balance>,
+        // Tick at which each member reached `max_missed_rounds` and became liable to expulsion.
+        pub defaulted_at: Mapping<AccountId, u64>,
+        // Pending requests to hand a seat to another account, keyed by the current holder,
+        // the id of each, and the id of the request each member last voted for, by holder
+        // and voter. Ids start at 1; requests made before they were handed out read as 0.
+        pub seat_transfers: Mapping<AccountId, SeatTransfer>,
+        pub seat_transfer_ids: Mapping<AccountId, u32>,
+        pub seat_transfer_votes: Mapping<(AccountId, AccountId), u32>,
+        pub last_seat_transfer_id: Lazy<u32>,
+        // Account each transferred seat went to. The old account cannot rejoin.
+        pub transferred_seats: Mapping<AccountId, AccountId>,
+        // First round members may still pay for with `deposit_many` after a catch-up was
+        // scheduled. Cleared when the round closes.
+        pub catch_up_since: Option<u32>,
+        pub draw_records: Mapping<u32, DrawRecord>,
+        // What the contract should hold: everything taken in through `collect` less
+        // everything sent out through `pay_out`. Anything beyond it was sent by mistake.
+        pub held: Balance,
+        // Every dividend credited to each member, claimed or not, and every round each
+        // member has failed to pay on time, cured or not. Kept for members' statements.
+        pub dividends_earned: Mapping<AccountId, Balance>,
+        pub rounds_defaulted: Mapping<AccountId, u32>,
+        pub decimals: u8,
+        pub commission_rounding: Rounding,
+        pub remainder_policy: RemainderPolicy,
+        pub blacklist_registry: Option<AccountId>,
+        pub min_reputation: Option<i32>,
+        // Rounds voided because no one could win them, with what was refunded for each.
+        pub voided_rounds: Mapping<u32, Balance>,
+        // Contributions to voided rounds each member can take back with `claim_refund`.
+        pub round_refunds: Mapping<AccountId, Balance>,
+        // Whether `final_distribution` has shared out the penalty pool.
+        pub penalty_pool_distributed: bool,
+        pub dispute_period: BlockNumber,
+        // Winner drawn and waiting out the dispute window before being paid.
+        pub pending_payout: Option<PendingPayout>,
+        // Latest dispute raised against a drawn winner, keyed by (round, winner), and the
+        // dispute deadline of the announcement each member last backed a dispute against,
+        // keyed by (winner, member).
+        pub disputes: Mapping<(u32, AccountId), Dispute>,
+        pub dispute_votes: Mapping<(AccountId, AccountId), BlockNumber>,
+        pub treasury: Option<AccountId>,
+        pub receipt_collection: Option<AccountId>,
+        // In rotation mode, the order members win in, and the position the next draw starts
+        // looking from.
+        pub rotation: Vec<AccountId>,
+        pub rotation_position: u32,
+        pub admin_participates: bool,
+        // Promotional bonus put in by the organizer and not yet paid out. It is not part of
+        // any member's contribution, so refunds never draw on it.
+        pub bonus_pool: Balance,
+        pub bonus_split: BonusSplit,
+        pub max_tickets: u32,
+        // One bit per member index, 128 to a word, set while `is_eligible` holds for the
+        // member there, so the draw can skip members who cannot win without loading their
+        // records. Kept up to date by `refresh_eligibility` wherever eligibility can change.
+        pub eligibility: Mapping<u32, u128>,
+        // Safety officer independent of the organizer: can pause the fund, void a round and
+        // cancel the fund, and nothing else. The members replace it by majority vote.
+        pub guardian: Lazy<Option<AccountId>>,
+        pub guardian_elections: Mapping<u32, GuardianElection>,
+        pub guardian_votes: Mapping<(u32, AccountId), bool>,
+        pub next_guardian_election_id: Lazy<u32>,
+        // Contract notified of settled rounds, defaults and completion; see `hooks`.
+        pub hook: Lazy<Option<AccountId>>,
+        // Index of the next member a partly run `migrate` converts.
+        pub migration_cursor: Lazy<u32>,
+        pub sealed_claim_period: Lazy<BlockNumber>,
+        // Prizes of sealed rounds not claimed or forfeited yet, by round number.
+        pub sealed_prizes: Mapping<u32, SealedPrize>,
+        // Commitments to the secrets of sealed rounds not drawn yet, by round number.
+        pub round_secrets: Mapping<u32, Hash>,
+        pub escrow_timeout: Lazy<BlockNumber>,
+        // Block in which the round awaiting its draw was closed.
+        pub draw_pending_since: Lazy<BlockNumber>,
+        pub max_advance_bps: Lazy<u16>,
+        // Each member's advance, requested or outstanding.
+        pub advances: Mapping<AccountId, Advance>,
+        // Request id each member last voted for, by borrower and voter.
+        pub advance_ballots: Mapping<(AccountId, AccountId), u32>,
+        pub next_advance_id: Lazy<u32>,
+        pub dex_router: Lazy<Option<AccountId>>,
+        // Token each member wants prizes converted into, if any.
+        pub payout_preferences: Mapping<AccountId, PayoutPreference>,
+        pub redraw_policy: Lazy<RedrawPolicy>,
+        // Who referred each member who joined with `join_with_referral`, and how many
+        // members each account has referred.
+        pub referrers: Mapping<AccountId, AccountId>,
+        pub referral_counts: Mapping<AccountId, u32>,
+        pub referral_bonus_bps: Lazy<u16>,
+        // Referrers of members who have not made their first contribution yet, by member.
+        pub unrewarded_referrals: Mapping<AccountId, AccountId>,
+        // Bonuses earned while the commission could not cover them, oldest first.
+        pub referral_bonuses_due: Lazy<Vec<ReferralBonus>>,
+        pub accept_donations: Lazy<bool>,
+        // Everything donated through `fallback`.
+        pub donations: Lazy<Balance>,
+        // Invested funds the strategy failed to return when the round closed or the fund was
+        // cancelled. They were taken out of the pot then, see `recover_invested`.
+        pub stranded: Lazy<Balance>,
+        // Set by `renounce_admin`. `admin` keeps the account the commission is owed to but no
+        // longer has any powers.
+        pub admin_renounced: Lazy<bool>,
     } 
 
-    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    // Where the draw gets its randomness from.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum RandomnessSource {
+        // Secrets committed and revealed by the participants, mixed with block entropy.
+        #[default]
+        CommitReveal,
+        // Random bytes provided by the chain through `RandomnessExtension`.
+        ChainExtension,
+    }
+
+    // What the draw does when the ticket the seed lands on cannot win.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum RedrawPolicy {
+        // Walk forward to the next ticket that can win.
+        #[default]
+        LinearProbe,
+        // Hash the seed with nonces 1, 2, ... up to `MAX_REHASHES` and take the first ticket
+        // that can win, then walk forward if none could.
+        Rehash,
+        // Fail with `DrawDeferred`, so the draw is run again in a later block with fresh
+        // randomness.
+        DeferToNextBlock,
+    }
+
+    // How each round's prize is awarded.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum FundMode {
+        // A random draw among the participants who have not won yet.
+        #[default]
+        Fixed,
+        // The participant bidding the lowest prize wins; the discount is paid out as dividends.
+        Auction,
+        // Members win in a fixed order set when the fund starts, as in a classic ROSCA.
+        Rotation,
+    }
+
+    // Which way a share that does not come out even is rounded to a whole base unit.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum Rounding {
+        #[default]
+        Down,
+        Up,
+        // Halves round up.
+        Nearest,
+    }
+
+    // Who gets the base units left over when an amount is shared out as dividends and does
+    // not divide evenly between the members. Whatever the policy, the shares and the
+    // remainder always add up to the amount shared.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum RemainderPolicy {
+        // The round's winner, or the pot when there is no winner, as with yield.
+        #[default]
+        Winner,
+        // Carried over in the pot to the next round. On the final round there is none, so
+        // it goes to the winner.
+        Pot,
+        // Added to the organizer's commission.
+        Organizer,
+    }
+
+    // How a winner receives the prize.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum PayoutSchedule {
+        // The whole prize is paid at the draw.
+        #[default]
+        LumpSum,
+        // The prize is split into this many equal tranches: the first can be claimed at once
+        // and one more after each later round. Fewer than two tranches pays a lump sum.
+        Installments(u32),
+    }
+
+    // How the organizer's bonus pool is paid out on top of the prizes.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum BonusSplit {
+        // All of it goes to the first round's winner.
+        #[default]
+        FirstRound,
+        // An equal share goes to every round's winner, the last taking what is left.
+        EvenlyAcrossRounds,
+    }
+
+    // Optional settings picked at construction, see `with_config`.
+    #[derive(Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FundConfig {
+        pub randomness_source: RandomnessSource,
+        pub mode: FundMode,
+        // Organizer's cut of each round's prize, in basis points (capped at 10_000).
+        pub commission_bps: u16,
+        // Whether rounds are timed in blocks or milliseconds, and how long each one is.
+        // The default, zero milliseconds, leaves rounds open until the admin ends them.
+        pub schedule: ScheduleSource,
+        // Fee on top of the contribution for deposits made after the round deadline, in
+        // basis points of `monthly_contribution`. Zero rejects late deposits outright.
+        pub late_fee_bps: u16,
+        // Missed rounds after which the admin may expel a member. Zero disables expulsion.
+        pub max_missed_rounds: u32,
+        // Co-admins who approve lifecycle actions, and how many approvals each action
+        // needs. A threshold of zero keeps the single `admin` in charge.
+        pub co_admins: Vec<AccountId>,
+        pub approval_threshold: u32,
+        // PSP22 token the fund collects and pays out in. None uses the native currency.
+        pub contribution_token: Option<AccountId>,
+        // Only accounts the admin has put on the allowlist may join.
+        pub invite_only: bool,
+        // Members needed before the admin can start the fund with `start_fund`. Zero lets
+        // the fund run straight away.
+        pub min_participants: u32,
+        // Security deposit paid on joining, slashed to cover missed contributions and
+        // returned once the fund completes. Zero requires none.
+        pub collateral: Balance,
+        // Only members who have paid every round so far can win a draw. Defaulters are
+        // skipped instead of holding the draw up.
+        pub paid_up_winners_only: bool,
+        pub payout_schedule: PayoutSchedule,
+        // Paid from the penalty pool to whoever calls `poke_defaulters`. Zero pays nothing.
+        pub poke_bounty: Balance,
+        // Hash of the group's off-chain agreement, which members accept with `accept_terms`
+        // before joining, and where to find the document. None requires no acceptance.
+        pub terms_hash: Option<Hash>,
+        pub terms_uri: Vec<u8>,
+        // Time, in the schedule's unit, a member who has missed `max_missed_rounds` rounds
+        // has to cure the default with `cure_default` before they can be expelled.
+        pub grace_period: u64,
+        // Decimals of the fund's currency, so `set_monthly_contribution_units` and UIs can
+        // work in whole units. Amounts in storage and messages are always in base units.
+        pub decimals: u8,
+        pub commission_rounding: Rounding,
+        pub remainder_policy: RemainderPolicy,
+        // Factory whose blacklist `join` checks and expulsions are reported to. The factory
+        // fills this in for the funds it creates. It also keeps every member's reputation
+        // with the organizer, which each round's payments and defaults are reported to.
+        pub blacklist_registry: Option<AccountId>,
+        // Reputation an account needs in the registry to join. None lets anyone join.
+        pub min_reputation: Option<i32>,
+        // Blocks a drawn winner's payout is held for while members can dispute the draw
+        // with `raise_dispute`. Zero pays the winner at the draw.
+        pub dispute_period: BlockNumber,
+        // Where `terminate_contract` sends whatever dust is left. None sends it to the admin.
+        pub treasury: Option<AccountId>,
+        // PSP34 collection that mints members a receipt for every round they pay, which the
+        // fund must be allowed to mint on. None issues no receipts.
+        pub receipt_collection: Option<AccountId>,
+        // Whether the admin may also hold a seat. A participating admin pays, defaults and
+        // wins on the same terms as every other member; otherwise they cannot join at all.
+        pub admin_participates: bool,
+        // How a bonus paid in with the constructor or `fund_bonus` reaches the winners.
+        pub bonus_split: BonusSplit,
+        // Most tickets one member can hold with `join_with_tickets`. Each ticket is a seat:
+        // it takes a place in `max_participants`, owes `monthly_contribution` and collateral
+        // every round, and gives one more chance to win, up to one win per ticket. Zero or
+        // one keeps every member to a single seat.
+        pub max_tickets: u32,
+        // Account that may pause, void a round or cancel, but not draw or move money.
+        pub guardian: Option<AccountId>,
+        // Blocks a sealed winner has to claim their prize in. When set, a draw names no
+        // winner: the prize can only be claimed with the round's secret, see
+        // `commit_round_secret`. Sealed draws are not held for disputes, as nobody could
+        // dispute a winner they cannot see. Zero names winners as usual.
+        pub sealed_claim_period: BlockNumber,
+        // Blocks a closed round can wait for its draw before it can be reclaimed, see
+        // `reclaim_if_expired`. Zero uses `ESCROW_TIMEOUT`.
+        pub escrow_timeout: BlockNumber,
+        // Largest advance a member who has not won can take against their prize, in basis
+        // points of `scheduled_prize`. Zero offers no advances.
+        pub max_advance_bps: u16,
+        // DEX router winners can have their prize converted through, see
+        // `set_payout_preference`. None pays every prize in the fund's currency.
+        pub dex_router: Option<AccountId>,
+        // What the draw does when the ticket it lands on cannot win.
+        pub redraw_policy: RedrawPolicy,
+        // Credited to whoever referred a member, in basis points of the member's first
+        // contribution and out of the commission, see `join_with_referral`. Zero pays none.
+        pub referral_bonus_bps: u16,
+        // Whether value sent to the fund with no matching message is kept as a donation
+        // rather than refused, see `fallback`.
+        pub accept_donations: bool,
+    }
+
+    // Duties the admin can delegate. The admin holds every role.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum Role {
+        // Runs the fund: membership, contributions and the round lifecycle.
+        Organizer,
+        // Withdraws the organizer's commission. Emergency withdrawals stay with the admin.
+        Treasurer,
+        // Read-only oversight; grants no access to any message that changes state.
+        Auditor,
+    }
+
+    // Lifecycle actions that need m-of-n co-admin approval when multisig is configured.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub enum AdminAction {
+        BeginCycle,
+        EndCycle,
+        Draw,
+        CancelFund,
+        Upgrade(Hash),
+        StartFund,
+        SetMonthlyContribution(Balance),
+    }
+
+    // Everything that went into a round's draw, so members can recompute the winner: hash
+    // (entropy, block_number, timestamp) with Blake2x256, pass it through the chain extension
+    // if that is the source, read the first 8 bytes as a little-endian seed and walk forward
+    // from `seed % ticket_count` over the members' tickets, laid out in join order with each
+    // member's tickets side by side, past tickets that could not win: those of members who
+    // cannot, and the first `wins` of every member's. The ticket landed on wins. Under
+    // another `redraw_policy` the first ticket is picked again as that policy says.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct DrawRecord {
+        pub round: u32,
+        pub source: RandomnessSource,
+        pub block_number: BlockNumber,
+        pub timestamp: Timestamp,
+        // XOR of the secrets revealed for the round, and who revealed them out of how many
+        // committed.
+        pub entropy: [u8; 32],
+        pub revealed_by: Vec<AccountId>,
+        pub commitments: u32,
+        // Hash of the inputs above, and the randomness the seed was read from: the hash
+        // itself, or what the chain extension returned for it.
+        pub input_hash: [u8; 32],
+        pub randomness: [u8; 32],
+        pub seed: u64,
+        pub member_count: u32,
+        pub ticket_count: u32,
+        // Ticket positions the walk started from and stopped at.
+        pub start_index: u32,
+        pub winner_index: u32,
+        pub winner: AccountId,
+        pub redraw_policy: RedrawPolicy,
+        // Tickets tried after the first before one could win.
+        pub probes: u32,
+    }
+
+    // A member's dealings with the fund, returned by `get_member_statement` for settlements
+    // and accounting.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct MemberStatement {
+        pub account: AccountId,
+        pub is_member: bool,
+        pub total_contributed: Balance,
+        pub penalties_paid: Balance,
+        // Dividends credited over the life of the fund, and the part not claimed yet.
+        pub dividends_earned: Balance,
+        pub dividends_pending: Balance,
+        // Round the member won, with the prize before and after commission.
+        pub won_round: Option<u32>,
+        pub prize: Balance,
+        pub prize_after_commission: Balance,
+        pub collateral_held: Balance,
+        // Rounds not paid on time, and those of them still owed.
+        pub rounds_defaulted: u32,
+        pub rounds_outstanding: u32,
+        pub arrears: Balance,
+        pub refund_received: Balance,
+    }
+
+    // A prize being paid out in installments.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct PayoutPlan {
+        pub round: u32,
+        // Prize after commission, split into `tranches` equal parts.
+        pub amount: Balance,
+        pub tranches: u32,
+        pub claimed: Balance,
+        // Taken back from the unclaimed tranches to cover contributions missed after winning.
+        pub slashed: Balance,
+        // `rounds_completed` once the prize was drawn; one more tranche unlocks per round after.
+        pub won_at: u32,
+    }
+
+    // New terms proposed by a member, adopted once a majority of the members vote for them.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct TermsAmendment {
+        pub terms_hash: Hash,
+        pub terms_uri: Vec<u8>,
+        pub proposer: AccountId,
+        pub votes: u32,
+        // Last block at which the amendment can still be voted on.
+        pub expires_at: BlockNumber,
+        pub executed: bool,
+    }
+
+    // A new guardian, or none, proposed by a member and appointed once a majority of the
+    // members vote for it.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct GuardianElection {
+        pub candidate: Option<AccountId>,
+        pub proposer: AccountId,
+        pub votes: u32,
+        // Last block at which the election can still be voted on.
+        pub expires_at: BlockNumber,
+        pub executed: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Proposal {
+        pub action: AdminAction,
+        pub proposer: AccountId,
+        pub approvals: u32,
+        // Last block at which the proposal can still be approved or executed.
+        pub expires_at: BlockNumber,
+        pub executed: bool,
+    }
+
+    // A members' vote to expel a defaulter. It only counts for the round it was opened in.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct ExpulsionVote {
+        pub proposer: AccountId,
+        pub votes: u32,
+        // Paid-up members other than the target when the vote opened. Expelling takes a
+        // strict majority of them.
+        pub electorate: u32,
+        pub executed: bool,
+    }
+
+    // A drawn winner whose payout waits for the dispute window to close.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct PendingPayout {
+        pub round: u32,
+        pub winner: AccountId,
+        pub prize: Balance,
+        // Last block in which the draw can be disputed.
+        pub dispute_deadline: BlockNumber,
+    }
+
+    // A challenge to a drawn winner. Once a strict majority of the other members back it,
+    // the payout is dropped and the round is drawn again.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Dispute {
+        pub raised_by: AccountId,
+        // Hash of the reason, published off-chain.
+        pub reason_hash: Hash,
+        pub votes: u32,
+        // Members other than the winner when the dispute was raised.
+        pub electorate: u32,
+        pub upheld: bool,
+    }
+
+    // A member's request to hand their seat to another account. It goes through once the
+    // admin approves it or a strict majority of the other members vote for it.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct SeatTransfer {
+        pub new_account: AccountId,
+        pub votes: u32,
+        // Other members when the request was made.
+        pub electorate: u32,
+    }
+
+    // An interest-free advance against a member's prize, lent out of the organizer's
+    // commission the way foremen lend against chits. It is paid once a strict majority of
+    // the other members vote for it, and deducted from the member's next prize.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Advance {
+        pub id: u32,
+        // Requested, and once paid, what is still owed.
+        pub amount: Balance,
+        pub votes: u32,
+        // Other members when the advance was requested.
+        pub electorate: u32,
+        pub paid: bool,
+    }
+
+    // A referral bonus waiting for commission to pay it.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct ReferralBonus {
+        pub referrer: AccountId,
+        pub referee: AccountId,
+        pub amount: Balance,
+    }
+
+    // A member's wish to receive prizes in another PSP22 token. `min_rate` is the worst
+    // price they accept: the least of `token` per `RATE_SCALE` units of the fund's
+    // currency. A swap that would return less is not made.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct PayoutPreference {
+        pub token: AccountId,
+        pub min_rate: Balance,
+    }
+
+    // Prize of a round whose winner is sealed. `commitment` is the organizer's commitment to
+    // the round's secret, which they hand the winner privately; the winner claims with it by
+    // `expires_at`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct SealedPrize {
+        pub commitment: Hash,
+        // Prize less commission, plus any bonus.
+        pub amount: Balance,
+        pub expires_at: BlockNumber,
+    }
+
+    // Snapshot of the fund returned by `get_fund_info`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum Error {
-        ParticipantsAlreadyFull,
-        ChitFundHasFinished,
-        AlreadyJoined,
-        CannotJoinMidCycle,
-        OnlyOwnerCanBeginCycle,
-        OnlyOwnerCanEndCycle,
-        ChitFundNotFinished,
-        NotParticipant,
-        OnlyAdminCanDraw,
-        ChitFundAlreadyFinished,
-        FailedToGetWinner,
+    pub struct FundInfo {
+        pub admin: AccountId,
+        pub max_participants: u32,
+        pub participant_count: u32,
+        pub monthly_contribution: Balance,
+        pub current_round: u32,
+        pub pot: Balance,
+        pub total_amount: Balance,
+        pub phase: FundPhase,
+        pub rounds_completed: u32,
+        pub winners: Vec<AccountId>,
+    }
+
+    // Totals over the settled rounds, returned by `get_fund_summary`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct FundSummary {
+        pub total_collected: Balance,
+        // Prizes less commission, plus bonuses.
+        pub total_paid_out: Balance,
+        pub commission_earned: Balance,
+        pub penalties_collected: Balance,
+        pub dividends_distributed: Balance,
+        pub defaults: u32,
+        pub rounds_completed: u32,
+        pub rounds_remaining: u32,
+        // When the last round's contributions are due if every round runs to its deadline.
+        // None before the fund starts, once it is over, and when rounds have no deadline.
+        pub expected_completion: Option<Deadline>,
+    }
+
+    // What kind of fund an instance is, returned by `get_contract_info` so indexers can
+    // classify deployments with one call.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractInfo {
+        pub version: String,
+        pub mode: FundMode,
+        pub schedule: ScheduleSource,
+        // PSP22 token contributions are paid in; `None` for the native currency.
+        pub token: Option<AccountId>,
+        pub commission_bps: u16,
+        pub phase: FundPhase,
     }
+
+    // What settling the round awaiting its draw would do, returned by `preview_draw`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct DrawPreview {
+        pub round: u32,
+        pub winner: AccountId,
+        // Before commission; the bonus comes on top.
+        pub prize: Balance,
+        pub commission: Balance,
+        pub bonus: Balance,
+        // What the winner would receive: prize less commission, plus the bonus.
+        pub payout: Balance,
+        // Dividend credited for every ticket, from an auction discount and, in the last
+        // round, from what the fund has left over.
+        pub dividend_per_ticket: Balance,
+        // Total of those dividends. Split remainders follow the remainder policy.
+        pub dividends: Balance,
+    }
+
     // pub type Result<T> = core::result::Result<T, Error>;
 
     #[ink(event)]
@@ -43,267 +855,9572 @@ mod my_contract {
     }
 
     #[ink(event)]
-    pub struct FundDeposited {
+    pub struct LeftChitFund {
         #[ink(topic)]
         account: Option<AccountId>,
+        refund: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CollateralSlashed {
         #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
         amount: Balance,
     }
 
     #[ink(event)]
-    pub struct NewCycleBegan {
+    pub struct CollateralReturned {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CodeUpgraded {
         #[ink(topic)]
         admin: Option<AccountId>,
+        code_hash: Hash,
     }
 
     #[ink(event)]
-    pub struct CycleEnded {
+    pub struct StorageMigrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
         #[ink(topic)]
         admin: Option<AccountId>,
     }
 
     #[ink(event)]
-    pub struct DrawWinner {
+    pub struct Unpaused {
         #[ink(topic)]
-        victor: Option<AccountId>,
+        admin: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct AllowlistUpdated {
         #[ink(topic)]
-        amount_won: Balance,
+        account: Option<AccountId>,
+        allowed: bool,
     }
 
-    impl ChitFund {
-        #[ink(constructor)]
-        pub fn new( admin : AccountId, max_participants: u32, monthly_contribution: Balance)
-           
-            -> Self {
-            Self {
-                admin : admin,
-                max_participants,
-                monthly_contribution,
-                current_round: 1,
-                pot: 0,
-                total_amount: Default::default(),
-                participants: Default::default(),
-                used_indexes: Default::default(),
-                finished: false,
-            }
-        }
+    #[ink(event)]
+    pub struct FundDeposited {
+        #[ink(topic)]
+        pub account: Option<AccountId>,
+        #[ink(topic)]
+        pub amount: Balance,
+        // Who paid, when someone else contributed on the member's behalf.
+        #[ink(topic)]
+        pub payer: Option<AccountId>,
+        pub round: u32,
+        // Paid towards the round so far, and by the member over the life of the fund.
+        pub paid_towards_round: Balance,
+        pub total_contributed: Balance,
+    }
 
-        // The join function allows participants to join the chit fund.
-        #[ink(message)]
-        pub fn join(&mut self) -> Result<(), Error> {
-            let participant = self.env().caller();
-  
-            if self.participants.len() >= self.max_participants.try_into().unwrap() {
-            return Err(Error::ParticipantsAlreadyFull);
-            }
-            if self.finished { 
-                return Err(Error::ChitFundHasFinished);
-            }
-            if self.participants.contains(&participant) { 
-                return Err(Error::AlreadyJoined);
-            }
+    #[ink(event)]
+    pub struct SecretCommitted {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        round: u32,
+    }
 
-            self.participants.push(participant);
-            self.env().emit_event(JoinedChitFund {
-                account: Some(participant),
-            });
-            Ok(())
-        }
+    #[ink(event)]
+    pub struct SecretRevealed {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        round: u32,
+    }
 
-        #[ink(message)] 
-        pub fn begin_cycle(&mut self) -> Result<(), Error> {
-            let sender = self.env().caller();
-            if sender != self.admin { 
-            return Err(Error::OnlyOwnerCanBeginCycle);
-            }     
-            if !self.finished {
-            return Err(Error::ChitFundNotFinished);
-            }
-            self.total_amount = self.pot;
-            self.pot = 0;
-            self.finished = false;
-            self.env().emit_event(NewCycleBegan {
-                admin: Some(sender), 
-            });
-            Ok(())
-        }
+    #[ink(event)]
+    pub struct PhaseChanged {
+        pub from: FundPhase,
+        #[ink(topic)]
+        pub to: FundPhase,
+    }
 
-        // The deposit function allows participants to deposit their 
-        // monthly contribution into the chit fund's pot.
-        #[ink(message, payable)]
-        pub fn deposit(&mut self) -> Result<(), Error> {
-            let sender = self.env().caller();
-            if !self.participants.contains(&sender) { 
-            return Err(Error::NotParticipant);
-            }
-            if self.finished { 
-            return Err(Error::ChitFundHasFinished);
-            }
-            let transferred_balance = self.env().transferred_value();
-            self.pot += transferred_balance;
+    #[ink(event)]
+    pub struct FundStarted {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        participant_count: u32,
+    }
 
-            self.env().emit_event(FundDeposited {
-                account: Some(sender),
-                amount: transferred_balance,
-            });
-            Ok(())
-        }
-        // The draw function allows the admin to get a winner after the cycle is ended.
-        #[ink(message, payable)]
-            pub fn draw(&mut self) -> Result<(), Error> {
-            if self.used_indexes.len() == self.participants.len() {
-                self.used_indexes.clear();
-            }
-            let sender = self.env().caller();
-            if sender != self.admin { 
-                return Err(Error::OnlyAdminCanDraw);
-            }
-            if !self.finished {
-                return Err(Error::ChitFundNotFinished);
-            }
-            let block_number = Self::env().block_number(); 
-            if let Some(winner) = ChitFund::get_random_account(&mut self.participants, &mut self.used_indexes, block_number) {
-                let amount = self.total_amount - self.pot;
-               
-                Self::env().transfer(winner, amount);
-                self.env().emit_event(DrawWinner {
-                    victor: Some(winner),
-                    amount_won: amount,
-                    
-                });
-                return Ok(())
-            }
-            return Err(Error::FailedToGetWinner);
-        }
-        
-        //  To get a random account number for the winner
-        fn get_random_account(participants: &mut Vec<AccountId>, used_indexes: &mut Vec<AccountId> ,block_number: u32) -> Option<AccountId> {
-            if participants.is_empty() {
-                return None;
-            }
-        
-            let idx = (block_number as usize) % participants.len();
-            let account_id = participants[idx];
-            if used_indexes.contains(&account_id) {
-                return None;
-            }
-            else {
-                used_indexes.push(account_id);
-                Some(account_id)
-            }
-        }
+    #[ink(event)]
+    pub struct NewCycleBegan {
+        #[ink(topic)]
+        pub admin: Option<AccountId>,
+        pub round: u32,
+        // Tick of the schedule at which contributions for the round are due. None when
+        // rounds have no deadline.
+        pub deadline: Option<u64>,
+    }
 
-        // End a particular round after its completion
-        #[ink(message)] 
-        pub fn end_cycle(&mut self) -> Result<(), Error> {
-            let sender = self.env().caller();
-            if sender != self.admin { 
-            return Err(Error::OnlyOwnerCanEndCycle);
-            }     
-            if self.finished {  
-            return Err(Error::ChitFundAlreadyFinished);
-            }
-            self.total_amount = self.pot;
-            self.pot = 0;
-            self.current_round += 1;
-                self.finished = true;
-                self.env().emit_event(CycleEnded {
-                    admin: Some(sender), 
-                });
-                Ok(())
-        }
+    #[ink(event)]
+    pub struct CycleEnded {
+        #[ink(topic)]
+        pub admin: Option<AccountId>,
+        pub round: u32,
+        // Last block at which secrets for the round's draw can be revealed.
+        pub reveal_deadline: BlockNumber,
     }
-}
-    
 
-#[cfg(test)]
-mod tests {
-    use crate::my_contract::{ChitFund, Error};
-    // use ink_prelude::*;
-    use super::*;
-    use ink::primitives::AccountId;
-    // use scale::{Encode, Decode};
-    pub type Result<T> = core::result::Result<T, Error>;
+    #[ink(event)]
+    pub struct RoundSummary {
+        #[ink(topic)]
+        pub round: u32,
+        pub total_collected: Balance,
+        pub participant_count: u32,
+        pub depositors: u32,
+    }
 
-    // Helper function to create a random account ID for testing purposes.
-    fn random_account_id() -> AccountId {
-        AccountId::from([0x42; 32])
+    #[ink(event)]
+    pub struct DrawWinner {
+        #[ink(topic)]
+        pub victor: Option<AccountId>,
+        #[ink(topic)]
+        pub amount_won: Balance,
+        // Account the prize was sent to.
+        pub nominee: Option<AccountId>,
+        pub round: u32,
+        // Members who could have won this round, the winner included.
+        pub eligible: u32,
+        // Members still to win after this round.
+        pub remaining: u32,
     }
 
-    #[test]
-    fn test_new() {
-        let admin = random_account_id();
-        let max_participants = 5;
-        let monthly_contribution = 100;
-        let chit_fund = ChitFund::new(admin, max_participants, monthly_contribution);
+    #[ink(event)]
+    pub struct PayoutAddressSet {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        payout_address: Option<AccountId>,
+    }
 
-        assert_eq!(chit_fund.admin, admin);
-        assert_eq!(chit_fund.max_participants, max_participants);
+    #[ink(event)]
+    pub struct PayoutPreferenceSet {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        preference: Option<PayoutPreference>,
+    }
+
+    #[ink(event)]
+    pub struct PrizeSwapped {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        amount: Balance,
+        token: AccountId,
+        received: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ReferralRecorded {
+        #[ink(topic)]
+        referrer: Option<AccountId>,
+        #[ink(topic)]
+        referee: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct ReferralBonusCredited {
+        #[ink(topic)]
+        referrer: Option<AccountId>,
+        #[ink(topic)]
+        referee: Option<AccountId>,
+        amount: Balance,
+    }
+
+    // The swap was refused or failed, so the prize was paid in the fund's currency.
+    #[ink(event)]
+    pub struct PrizeSwapFailed {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        amount: Balance,
+        token: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct BidPlaced {
+        #[ink(topic)]
+        bidder: Option<AccountId>,
+        #[ink(topic)]
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct BiddingClosed {
+        #[ink(topic)]
+        round: u32,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        winning_bid: Balance,
+        dividend_per_member: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DividendCredited {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DividendsClaimed {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct CommissionWithdrawn {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        #[ink(topic)]
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct MemberExpelled {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        missed_rounds: u32,
+        refund: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ExpulsionProposed {
+        #[ink(topic)]
+        round: u32,
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        proposer: Option<AccountId>,
+        electorate: u32,
+    }
+
+    #[ink(event)]
+    pub struct ExpulsionVoted {
+        #[ink(topic)]
+        round: u32,
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        voter: Option<AccountId>,
+        votes: u32,
+    }
+
+    #[ink(event)]
+    pub struct MemberVotedOut {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
+        votes: u32,
+        // Collateral added to the penalty pool instead of being returned.
+        forfeited: Balance,
+        refund: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PayoutPlanned {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+        tranches: u32,
+    }
+
+    #[ink(event)]
+    pub struct InstallmentClaimed {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        amount: Balance,
+        // Still owed to the winner, unlocked or not.
+        remaining: Balance,
+    }
+
+    #[ink(event)]
+    pub struct InstallmentSlashed {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct YieldStrategyChanged {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        strategy: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct PotInvested {
+        #[ink(topic)]
+        strategy: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct YieldHarvested {
+        #[ink(topic)]
+        strategy: Option<AccountId>,
+        round: u32,
+        principal: Balance,
+        returned: Balance,
+    }
+
+    #[ink(event)]
+    pub struct YieldStranded {
+        #[ink(topic)]
+        strategy: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        role: Role,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        role: Role,
+    }
+
+    #[ink(event)]
+    pub struct ReminderIssued {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
+        // Tick of the schedule at which the contribution is due, if rounds have a deadline.
+        deadline: Option<u64>,
+    }
+
+    #[ink(event)]
+    pub struct DefaultersPoked {
+        #[ink(topic)]
+        caller: Option<AccountId>,
+        round: u32,
+        reminders: u32,
+        bounty: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TermsAccepted {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        terms_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct TermsAmendmentProposed {
+        #[ink(topic)]
+        amendment_id: u32,
+        #[ink(topic)]
+        proposer: Option<AccountId>,
+        terms_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct TermsAmendmentVoted {
+        #[ink(topic)]
+        amendment_id: u32,
+        #[ink(topic)]
+        voter: Option<AccountId>,
+        votes: u32,
+    }
+
+    #[ink(event)]
+    pub struct TermsAmended {
+        #[ink(topic)]
+        amendment_id: u32,
+        terms_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct DefaultCured {
+        #[ink(topic)]
+        pub account: Option<AccountId>,
+        pub missed_rounds: u32,
+        pub arrears: Balance,
+        pub penalty: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SeatTransferRequested {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        new_account: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct SeatTransferVoted {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        voter: Option<AccountId>,
+        votes: u32,
+    }
+
+    #[ink(event)]
+    pub struct MembershipTransferred {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        // The admin who approved the transfer, or None if the members voted it through.
+        approved_by: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct CatchUpScheduled {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        since_round: u32,
+        round: u32,
+        deadline: Option<u64>,
+    }
+
+    #[ink(event)]
+    pub struct RoundsPaid {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        rounds: Vec<u32>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SurplusSwept {
+        #[ink(topic)]
+        to: Option<AccountId>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DonationReceived {
+        #[ink(topic)]
+        donor: Option<AccountId>,
+        amount: Balance,
+        donations: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RoundVoided {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        round: u32,
+        refunded: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RefundCredited {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PenaltyPoolDistributed {
+        amount: Balance,
+        recipients: u32,
+    }
+
+    #[ink(event)]
+    pub struct WinnerAnnounced {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        round: u32,
+        prize: Balance,
+        dispute_deadline: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct DisputeRaised {
+        #[ink(topic)]
+        round: u32,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        #[ink(topic)]
+        raised_by: Option<AccountId>,
+        reason_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct DisputeBacked {
+        #[ink(topic)]
+        round: u32,
+        #[ink(topic)]
+        voter: Option<AccountId>,
+        votes: u32,
+    }
+
+    #[ink(event)]
+    pub struct DisputeUpheld {
+        #[ink(topic)]
+        round: u32,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        votes: u32,
+    }
+
+    #[ink(event)]
+    pub struct ContractTerminated {
+        #[ink(topic)]
+        treasury: Option<AccountId>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ReceiptIssued {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+        // False when the collection could not mint the receipt. The payment stands either way.
+        minted: bool,
+    }
+
+    #[ink(event)]
+    pub struct RotationSet {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        order: Vec<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct ParametersUpdated {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        max_participants: u32,
+        monthly_contribution: Balance,
+        schedule: ScheduleSource,
+        commission_bps: u16,
+    }
+
+    #[ink(event)]
+    pub struct BonusFunded {
+        #[ink(topic)]
+        organizer: Option<AccountId>,
+        amount: Balance,
+        bonus_pool: Balance,
+    }
+
+    #[ink(event)]
+    pub struct GuardianProposed {
+        #[ink(topic)]
+        election_id: u32,
+        #[ink(topic)]
+        proposer: Option<AccountId>,
+        candidate: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct GuardianVoted {
+        #[ink(topic)]
+        election_id: u32,
+        #[ink(topic)]
+        voter: Option<AccountId>,
+        votes: u32,
+    }
+
+    #[ink(event)]
+    pub struct GuardianChanged {
+        #[ink(topic)]
+        election_id: u32,
+        guardian: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct HookSet {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        hook: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct HookFailed {
+        #[ink(topic)]
+        hook: Option<AccountId>,
+        notification: Notification,
+    }
+
+    #[ink(event)]
+    pub struct WinnerSealed {
+        round: u32,
+        commitment: Hash,
+        amount: Balance,
+        expires_at: BlockNumber,
+    }
+
+    #[ink(event)]
+    pub struct SealedPrizeClaimed {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SealedPrizeForfeited {
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AdvanceRequested {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AdvanceVoted {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        voter: Option<AccountId>,
+        votes: u32,
+    }
+
+    #[ink(event)]
+    pub struct AdvancePaid {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AdvanceRepaid {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        round: u32,
+        amount: Balance,
+        // Still owed after this prize.
+        outstanding: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PrizeUnclaimed {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        #[ink(topic)]
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PrizeClaimed {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        #[ink(topic)]
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ChitFundCompleted {
+        #[ink(topic)]
+        rounds_completed: u32,
+    }
+
+    #[ink(event)]
+    pub struct AdminTransferStarted {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        #[ink(topic)]
+        pending_admin: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct AdminTransferred {
+        #[ink(topic)]
+        previous_admin: Option<AccountId>,
+        // None when the admin has renounced the role.
+        #[ink(topic)]
+        new_admin: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct ActionProposed {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        proposer: Option<AccountId>,
+        action: AdminAction,
+    }
+
+    #[ink(event)]
+    pub struct ActionApproved {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        approver: Option<AccountId>,
+        approvals: u32,
+    }
+
+    #[ink(event)]
+    pub struct ActionExecuted {
+        #[ink(topic)]
+        proposal_id: u32,
+        #[ink(topic)]
+        executor: Option<AccountId>,
+        action: AdminAction,
+    }
+
+    #[ink(event)]
+    pub struct FundCancelled {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        refund_pool: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RefundClaimed {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct EmergencyWithdrawal {
+        #[ink(topic)]
+        to: Option<AccountId>,
+        #[ink(topic)]
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ContributionChanged {
+        #[ink(topic)]
+        admin: Option<AccountId>,
+        #[ink(topic)]
+        amount: Balance,
+    }
+
+    impl ChitFund {
+        #[ink(constructor)]
+        pub fn new( admin : AccountId, max_participants: u32, monthly_contribution: Balance)
+           
+            -> Self {
+            Self::with_config(admin, max_participants, monthly_contribution, FundConfig::default())
+        }
+
+        // Same as `new`, picking where the draw gets its randomness from.
+        #[ink(constructor)]
+        pub fn with_randomness_source(
+            admin: AccountId,
+            max_participants: u32,
+            monthly_contribution: Balance,
+            randomness_source: RandomnessSource,
+        ) -> Self {
+            let config = FundConfig {
+                randomness_source,
+                ..Default::default()
+            };
+            Self::with_config(admin, max_participants, monthly_contribution, config)
+        }
+
+        // Same as `new`, with every optional setting spelled out in `config`. A native fund
+        // can be sent a bonus for its winners along with the deployment; a token fund is
+        // seeded with `fund_bonus` instead.
+        #[ink(constructor, payable)]
+        pub fn with_config(
+            admin: AccountId,
+            max_participants: u32,
+            monthly_contribution: Balance,
+            config: FundConfig,
+        ) -> Self {
+            let bonus = Self::env().transferred_value();
+            assert!(
+                bonus == 0 || config.contribution_token.is_none(),
+                "a token fund cannot be sent native value"
+            );
+            let mut fund = Self {
+                admin,
+                max_participants,
+                monthly_contribution,
+                current_round: 1,
+                pot: 0,
+                total_amount: Default::default(),
+                members: Mapping::default(),
+                member_index: Mapping::default(),
+                member_count: 0,
+                ticket_count: 0,
+                winner_count: 0,
+                phase: FundPhase::Recruiting,
+                deposits: Mapping::default(),
+                deposit_streaks: Mapping::default(),
+                total_contributed: Mapping::default(),
+                commitments: Mapping::default(),
+                revealed: Mapping::default(),
+                commit_count: Mapping::default(),
+                reveal_count: Mapping::default(),
+                revealed_entropy: Mapping::default(),
+                reveal_deadline: 0,
+                randomness_source: config.randomness_source,
+                rounds_completed: 0,
+                unclaimed_prizes: Mapping::default(),
+                mode: config.mode,
+                bids: Mapping::default(),
+                lowest_bids: Mapping::default(),
+                dividends: Mapping::default(),
+                commission_bps: config.commission_bps.min(MAX_BPS),
+                commission_balance: 0,
+                schedule: config.schedule,
+                round_start: config.schedule.now(Self::env().block_number(), Self::env().block_timestamp()),
+                late_fee_bps: config.late_fee_bps.min(MAX_BPS),
+                max_missed_rounds: config.max_missed_rounds,
+                penalty_pool: 0,
+                penalties_paid: Mapping::default(),
+                missed_rounds: Mapping::default(),
+                prizes_won: Mapping::default(),
+                refund_pool: 0,
+                refund_basis: 0,
+                refunds_claimed: Mapping::default(),
+                pending_admin: None,
+                approval_threshold: config.approval_threshold.min(config.co_admins.len() as u32),
+                co_admins: config.co_admins,
+                proposals: Mapping::default(),
+                approvals: Mapping::default(),
+                next_proposal_id: 0,
+                contribution_token: config.contribution_token,
+                invite_only: config.invite_only,
+                allowlist: Mapping::default(),
+                collateral_required: config.collateral,
+                collateral: Mapping::default(),
+                paused: false,
+                storage_version: STORAGE_VERSION,
+                round_history: Mapping::default(),
+                round_tally: Lazy::new(),
+                round_snapshots: Mapping::default(),
+                min_participants: config.min_participants,
+                payout_addresses: Mapping::default(),
+                partial_deposits: Mapping::default(),
+                expulsion_votes: Mapping::default(),
+                expulsion_ballots: Mapping::default(),
+                paid_up_winners_only: config.paid_up_winners_only,
+                reentrancy_lock: false,
+                payout_schedule: config.payout_schedule,
+                payout_plans: Mapping::default(),
+                yield_strategy: None,
+                yield_killed: false,
+                invested: 0,
+                roles: Mapping::default(),
+                poke_bounty: config.poke_bounty,
+                last_poked_round: 0,
+                terms_hash: config.terms_hash,
+                terms_uri: config.terms_uri,
+                terms_accepted: Mapping::default(),
+                terms_amendments: Mapping::default(),
+                amendment_votes: Mapping::default(),
+                next_amendment_id: 0,
+                grace_period: config.grace_period,
+                arrears: Mapping::default(),
+                defaulted_at: Mapping::default(),
+                seat_transfers: Mapping::default(),
+                seat_transfer_ids: Mapping::default(),
+                seat_transfer_votes: Mapping::default(),
+                last_seat_transfer_id: Lazy::new(),
+                transferred_seats: Mapping::default(),
+                catch_up_since: None,
+                draw_records: Mapping::default(),
+                held: bonus,
+                dividends_earned: Mapping::default(),
+                rounds_defaulted: Mapping::default(),
+                decimals: config.decimals.min(MAX_DECIMALS),
+                commission_rounding: config.commission_rounding,
+                remainder_policy: config.remainder_policy,
+                blacklist_registry: config.blacklist_registry,
+                min_reputation: config.min_reputation,
+                voided_rounds: Mapping::default(),
+                round_refunds: Mapping::default(),
+                penalty_pool_distributed: false,
+                dispute_period: config.dispute_period,
+                pending_payout: None,
+                disputes: Mapping::default(),
+                dispute_votes: Mapping::default(),
+                treasury: config.treasury,
+                receipt_collection: config.receipt_collection,
+                rotation: Vec::new(),
+                rotation_position: 0,
+                admin_participates: config.admin_participates,
+                bonus_pool: bonus,
+                bonus_split: config.bonus_split,
+                max_tickets: config.max_tickets,
+                eligibility: Mapping::default(),
+                guardian: Lazy::new(),
+                guardian_elections: Mapping::default(),
+                guardian_votes: Mapping::default(),
+                next_guardian_election_id: Lazy::new(),
+                hook: Lazy::new(),
+                migration_cursor: Lazy::new(),
+                sealed_claim_period: Lazy::new(),
+                sealed_prizes: Mapping::default(),
+                round_secrets: Mapping::default(),
+                escrow_timeout: Lazy::new(),
+                draw_pending_since: Lazy::new(),
+                max_advance_bps: Lazy::new(),
+                advances: Mapping::default(),
+                advance_ballots: Mapping::default(),
+                next_advance_id: Lazy::new(),
+                dex_router: Lazy::new(),
+                payout_preferences: Mapping::default(),
+                redraw_policy: Lazy::new(),
+                referrers: Mapping::default(),
+                referral_counts: Mapping::default(),
+                referral_bonus_bps: Lazy::new(),
+                unrewarded_referrals: Mapping::default(),
+                referral_bonuses_due: Lazy::new(),
+                accept_donations: Lazy::new(),
+                donations: Lazy::new(),
+                stranded: Lazy::new(),
+                admin_renounced: Lazy::new(),
+            };
+            fund.guardian.set(&config.guardian);
+            fund.sealed_claim_period.set(&config.sealed_claim_period);
+            fund.escrow_timeout
+                .set(&if config.escrow_timeout == 0 { ESCROW_TIMEOUT } else { config.escrow_timeout });
+            fund.max_advance_bps.set(&config.max_advance_bps.min(MAX_BPS));
+            fund.dex_router.set(&config.dex_router);
+            fund.redraw_policy.set(&config.redraw_policy);
+            fund.referral_bonus_bps.set(&config.referral_bonus_bps.min(MAX_BPS));
+            fund.accept_donations.set(&config.accept_donations);
+            fund
+        }
+
+        // The join function allows participants to join the chit fund, paying the
+        // security deposit if one is required.
+        #[ink(message, payable)]
+        pub fn join(&mut self) -> Result<(), Error> {
+            self.join_with_tickets(1)
+        }
+
+        // Join holding `tickets` seats, up to `max_tickets`, paying the security deposit for
+        // each. Every ticket owes a contribution each round and can win once.
+        #[ink(message, payable)]
+        pub fn join_with_tickets(&mut self, tickets: u32) -> Result<(), Error> {
+            let participant = self.env().caller();
+            if self.invite_only && !self.allowlist.contains(participant) {
+                return Err(Error::NotAllowlisted);
+            }
+            if self.terms_hash.is_some() && self.terms_accepted.get(participant) != self.terms_hash {
+                return Err(Error::TermsNotAccepted);
+            }
+            // Joining is refused while the registry cannot be asked rather than let a
+            // blacklisted or unproven account slip in.
+            if let Some(registry) = self.blacklist_registry {
+                if registry::is_blacklisted(registry, participant).map_err(|_| Error::RegistryUnavailable)? {
+                    return Err(Error::Blacklisted);
+                }
+            }
+            if let Some(minimum) = self.min_reputation {
+                let registry = self.blacklist_registry.ok_or(Error::RegistryUnavailable)?;
+                let reputation =
+                    registry::get_reputation(registry, participant).map_err(|_| Error::RegistryUnavailable)?;
+                if reputation < minimum {
+                    return Err(Error::ReputationTooLow);
+                }
+            }
+            self.add_participant(participant, tickets)
+        }
+
+        // Join like `join`, recording that `referrer`, a member, brought the caller in. With
+        // a `referral_bonus_bps`, the referrer is credited that share of the caller's first
+        // contribution as a dividend. The bonus comes out of the commission; one earned
+        // before there is commission to cover it is credited once the next draw earns some.
+        #[ink(message, payable)]
+        pub fn join_with_referral(&mut self, referrer: AccountId) -> Result<(), Error> {
+            let referee = self.env().caller();
+            if referrer == referee || !self.is_member(referrer) {
+                return Err(Error::InvalidReferrer);
+            }
+            self.join_with_tickets(1)?;
+            self.referrers.insert(referee, &referrer);
+            let referred = self.referral_counts.get(referrer).unwrap_or(0).saturating_add(1);
+            self.referral_counts.insert(referrer, &referred);
+            if self.referral_bonus_bps.get().unwrap_or_default() > 0 {
+                self.unrewarded_referrals.insert(referee, &referrer);
+            }
+            self.env().emit_event(ReferralRecorded {
+                referrer: Some(referrer),
+                referee: Some(referee),
+            });
+            Ok(())
+        }
+
+        // Who referred the account, if it joined with `join_with_referral`.
+        #[ink(message)]
+        pub fn get_referrer(&self, account: AccountId) -> Option<AccountId> {
+            self.referrers.get(account)
+        }
+
+        // Members the account has referred.
+        #[ink(message)]
+        pub fn get_referral_count(&self, account: AccountId) -> u32 {
+            self.referral_counts.get(account).unwrap_or(0)
+        }
+
+        // Lets the admin register a member who does not interact with the chain directly.
+        // Registering counts as an invitation, so the allowlist does not apply, and the admin
+        // vouches for the member having agreed to the terms. The admin pays the security
+        // deposit.
+        #[ink(message, payable)]
+        pub fn join_on_behalf(&mut self, account: AccountId) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Organizer) {
+                return Err(Error::OnlyAdminCanRegister);
+            }
+            self.add_participant(account, 1)
+        }
+
+        // Registers a whole membership at once, for an organizer who collected the members
+        // off-chain. Each account is checked as if it joined on its own, so a duplicate or one
+        // account too many fails the whole batch. The admin pays every member's security
+        // deposit in the one call.
+        #[ink(message, payable)]
+        pub fn admin_register_members(&mut self, accounts: Vec<AccountId>) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Organizer) {
+                return Err(Error::OnlyAdminCanRegister);
+            }
+            let transferred_balance = self.env().transferred_value();
+            if self.contribution_token.is_some() && transferred_balance != 0 {
+                return Err(Error::NativeValueNotAccepted);
+            }
+            let collateral = self
+                .collateral_required
+                .checked_mul(accounts.len() as Balance)
+                .ok_or(Error::IncorrectCollateralAmount)?;
+            if self.contribution_token.is_none() && transferred_balance != collateral {
+                return Err(Error::IncorrectCollateralAmount);
+            }
+            if self.ticket_count as usize + accounts.len() > self.max_participants as usize {
+                return Err(Error::ParticipantsAlreadyFull);
+            }
+            for (position, account) in accounts.iter().enumerate() {
+                self.ensure_can_register(*account)?;
+                if accounts[..position].contains(account) {
+                    return Err(Error::AlreadyJoined);
+                }
+            }
+            for account in accounts {
+                self.enroll(account, 1)?;
+            }
+            Ok(())
+        }
+
+        fn add_participant(&mut self, participant: AccountId, tickets: u32) -> Result<(), Error> {
+            self.ensure_can_register(participant)?;
+            if tickets == 0 || tickets > self.max_tickets.max(1) {
+                return Err(Error::InvalidTicketCount);
+            }
+            if self.ticket_count.saturating_add(tickets) > self.max_participants {
+                return Err(Error::ParticipantsAlreadyFull);
+            }
+            let transferred_balance = self.env().transferred_value();
+            if self.contribution_token.is_some() && transferred_balance != 0 {
+                return Err(Error::NativeValueNotAccepted);
+            }
+            let collateral = self
+                .collateral_required
+                .checked_mul(tickets as Balance)
+                .ok_or(Error::IncorrectCollateralAmount)?;
+            if self.contribution_token.is_none() && transferred_balance != collateral {
+                return Err(Error::IncorrectCollateralAmount);
+            }
+            self.enroll(participant, tickets)
+        }
+
+        fn ensure_can_register(&self, participant: AccountId) -> Result<(), Error> {
+            self.ensure_active()?;
+            if participant == self.admin && !self.admin_participates {
+                return Err(Error::AdminCannotParticipate);
+            }
+            self.ensure_not_paused()?;
+            if self.ticket_count >= self.max_participants {
+            return Err(Error::ParticipantsAlreadyFull);
+            }
+            if self.phase != FundPhase::Recruiting {
+                return Err(Error::MembershipLocked);
+            }
+            if self.is_member(participant) { 
+                return Err(Error::AlreadyJoined);
+            }
+            if self.transferred_seats.contains(participant) {
+                return Err(Error::SeatTransferred);
+            }
+            Ok(())
+        }
+
+        fn enroll(&mut self, participant: AccountId, tickets: u32) -> Result<(), Error> {
+            self.member_index.insert(self.member_count, &participant);
+            self.members.insert(
+                participant,
+                &MemberInfo { index: self.member_count, tickets, wins: 0, has_won: false },
+            );
+            self.set_eligibility_bit(self.member_count, true);
+            self.member_count += 1;
+            self.ticket_count += tickets;
+            let collateral = self.collateral_required * tickets as Balance;
+            if collateral > 0 {
+                self.collateral.insert(participant, &collateral);
+                self.collect(self.env().caller(), collateral)?;
+            }
+            self.env().emit_event(JoinedChitFund {
+                account: Some(participant),
+            });
+            Ok(())
+        }
+
+        // Open the fund for contributions once at least `min_participants` have joined.
+        // The first round's deadline runs from here.
+        #[ink(message)]
+        pub fn start_fund(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanStart);
+            }
+            self.ensure_no_multisig()?;
+            self.run_start_fund(sender)
+        }
+
+        fn run_start_fund(&mut self, sender: AccountId) -> Result<(), Error> {
+            self.ensure_active()?;
+            if self.phase != FundPhase::Recruiting {
+                return Err(Error::FundAlreadyStarted);
+            }
+            if self.member_count < self.min_participants {
+                return Err(Error::NotEnoughParticipants);
+            }
+            // Without an order from the admin, members win in the order they joined.
+            if self.mode == FundMode::Rotation {
+                if self.rotation.is_empty() {
+                    self.rotation = self.participant_list();
+                } else if !self.is_rotation_order(&self.rotation) {
+                    return Err(Error::InvalidRotation);
+                }
+            }
+            self.set_phase(FundPhase::ContributionOpen);
+            self.round_start = self.schedule_now();
+            self.env().emit_event(FundStarted {
+                admin: Some(sender),
+                participant_count: self.member_count,
+            });
+            Ok(())
+        }
+
+        #[ink(message)] 
+        pub fn begin_cycle(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+            return Err(Error::OnlyOwnerCanBeginCycle);
+            }     
+            self.ensure_no_multisig()?;
+            self.run_begin_cycle(sender)
+        }
+
+        fn run_begin_cycle(&mut self, sender: AccountId) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            self.ensure_phase(FundPhase::DrawPending, Error::ChitFundNotFinished)?;
+            if self.pending_payout.is_some() {
+                return Err(Error::PayoutPending);
+            }
+            // Moving on must not take an expired round's contributions from those who have
+            // not reclaimed them yet.
+            let round = self.current_round - 1;
+            if self.escrow_expired() && !self.round_history.contains(round) && !self.voided_rounds.contains(round) {
+                self.void_pending_round(None, round)?;
+            }
+            self.set_phase(FundPhase::ContributionOpen);
+            self.round_start = self.schedule_now();
+            self.env().emit_event(NewCycleBegan {
+                admin: Some(sender),
+                round: self.current_round,
+                deadline: self.round_deadline(),
+            });
+            Ok(())
+        }
+
+        // The deposit function allows participants to deposit their 
+        // monthly contribution into the chit fund's pot. With the native currency it can
+        // be paid in instalments: the member counts as paid once they add up to the amount
+        // due, and any overshoot is sent back. Token funds pull whatever is still due. It
+        // pays whichever round is open when the call lands; see `deposit_for_round`.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.credit_deposit(sender, sender)
+        }
+
+        // Pay the caller's contribution for `round`, which has to be the open round. Unlike
+        // `deposit`, a payment signed for a round that `end_cycle` closes before it lands is
+        // rejected rather than booked to the next one.
+        #[ink(message, payable)]
+        pub fn deposit_for_round(&mut self, round: u32) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            self.ensure_started()?;
+            if round != self.current_round || self.phase != FundPhase::ContributionOpen {
+                return Err(Error::WrongRound);
+            }
+            self.credit_deposit(sender, sender)
+        }
+
+        // Whether `deposit` is accepting contributions: the fund is running, not paused and
+        // the round is open. Between `end_cycle` and `begin_cycle` it is not.
+        #[ink(message)]
+        pub fn deposits_open(&self) -> bool {
+            self.phase == FundPhase::ContributionOpen
+                && self.ensure_active().is_ok()
+                && self.ensure_started().is_ok()
+                && !self.paused
+        }
+
+        // Pay a member's contribution for them, e.g. as a relative or employer. It is
+        // credited to the beneficiary exactly as if they had paid it themselves.
+        #[ink(message, payable)]
+        pub fn deposit_for(&mut self, beneficiary: AccountId) -> Result<(), Error> {
+            let payer = self.env().caller();
+            self.credit_deposit(payer, beneficiary)
+        }
+
+        // Pay the open round for several members at once, e.g. as an employer remitting a
+        // payroll. Each entry is credited to its beneficiary as if paid with `deposit_for`.
+        // Native funds send the sum of the amounts with the call; token funds have each
+        // amount pulled from the caller. Nothing is booked unless every entry is.
+        #[ink(message, payable)]
+        pub fn deposit_batch(&mut self, payments: Vec<(AccountId, Balance)>) -> Result<(), Error> {
+            let payer = self.env().caller();
+            let transferred = self.env().transferred_value();
+            if self.contribution_token.is_some() && transferred != 0 {
+                return Err(Error::NativeValueNotAccepted);
+            }
+            let total = payments
+                .iter()
+                .try_fold(0 as Balance, |total, (_, amount)| total.checked_add(*amount))
+                .ok_or(Error::ArithmeticOverflow)?;
+            if payments.is_empty() || (self.contribution_token.is_none() && total != transferred) {
+                return Err(Error::InvalidBatch);
+            }
+            for (beneficiary, amount) in payments {
+                self.ensure_can_deposit(beneficiary)?;
+                self.book_payment(payer, beneficiary, Some(amount))?;
+            }
+            Ok(())
+        }
+
+        // Takes a payment from `payer` and books it to `sender`'s contribution ledger.
+        fn credit_deposit(&mut self, payer: AccountId, sender: AccountId) -> Result<(), Error> {
+            self.ensure_can_deposit(sender)?;
+            let transferred_balance = self.env().transferred_value();
+            if self.contribution_token.is_some() && transferred_balance != 0 {
+                return Err(Error::NativeValueNotAccepted);
+            }
+            let offered = self.contribution_token.is_none().then_some(transferred_balance);
+            self.book_payment(payer, sender, offered)
+        }
+
+        // Whether the open round can take a payment towards `sender`'s contribution.
+        fn ensure_can_deposit(&self, sender: AccountId) -> Result<(), Error> {
+            self.ensure_active()?;
+            self.ensure_not_paused()?;
+            self.ensure_started()?;
+            if !self.is_member(sender) { 
+            return Err(Error::NotParticipant);
+            }
+            if self.phase != FundPhase::ContributionOpen { 
+            return Err(Error::DepositsClosed);
+            }
+            if self.deposits.contains((sender, self.current_round)) {
+                return Err(Error::AlreadyDeposited);
+            }
+            Ok(())
+        }
+
+        // Books `offered` towards `sender`'s contribution for the open round, or with None
+        // pulls whatever is still due from a token fund's payer. Anything beyond what is due
+        // goes back to the payer.
+        fn book_payment(&mut self, payer: AccountId, sender: AccountId, offered: Option<Balance>) -> Result<(), Error> {
+            // Past the deadline a deposit is only accepted together with the late fee.
+            let late = self.is_round_expired();
+            if late && self.late_fee_bps == 0 {
+                return Err(Error::RoundExpired);
+            }
+            let penalty = if late { self.late_fee() } else { 0 };
+            let contribution = self.contribution_of(sender)?;
+            let amount_due = contribution.checked_add(penalty).ok_or(Error::ArithmeticOverflow)?;
+            let key = (sender, self.current_round);
+            let already_paid = self.partial_deposits.get(key).unwrap_or(0);
+            let payment = match offered {
+                Some(0) => return Err(Error::IncorrectContributionAmount),
+                Some(amount) => amount,
+                None => amount_due.saturating_sub(already_paid),
+            };
+            let paid = already_paid.checked_add(payment).ok_or(Error::ArithmeticOverflow)?;
+            if paid < amount_due {
+                self.collect(payer, payment)?;
+                self.partial_deposits.insert(key, &paid);
+                self.env().emit_event(FundDeposited {
+                    account: Some(sender),
+                    amount: payment,
+                    payer: Some(payer),
+                    round: self.current_round,
+                    paid_towards_round: paid,
+                    total_contributed: self.total_contributed.get(sender).unwrap_or(0),
+                });
+                return Ok(());
+            }
+            let overshoot = paid - amount_due;
+            let pot = self.pot.checked_add(contribution).ok_or(Error::ArithmeticOverflow)?;
+            let penalty_pool = self.penalty_pool.checked_add(penalty).ok_or(Error::ArithmeticOverflow)?;
+            let contributed = self
+                .total_contributed
+                .get(sender)
+                .unwrap_or(0)
+                .checked_add(contribution)
+                .ok_or(Error::ArithmeticOverflow)?;
+            let penalties_paid = self
+                .penalties_paid
+                .get(sender)
+                .unwrap_or(0)
+                .checked_add(penalty)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.pot = pot;
+            self.penalty_pool = penalty_pool;
+            self.update_round_tally(|tally| tally.penalties = tally.penalties.saturating_add(penalty));
+
+            // A late payment keeps the member in good standing but breaks the streak.
+            let streak = if late { 0 } else { self.deposit_streaks.get(sender).unwrap_or(0) + 1 };
+            self.deposit_streaks.insert(sender, &streak);
+            self.deposits.insert((sender, self.current_round), &contribution);
+            self.total_contributed.insert(sender, &contributed);
+            if penalty > 0 {
+                self.penalties_paid.insert(sender, &penalties_paid);
+            }
+            self.partial_deposits.remove(key);
+            self.collect(payer, payment - overshoot)?;
+            // A token payer is only ever charged what is due.
+            if overshoot > 0 && self.contribution_token.is_none() {
+                self.env().transfer(payer, overshoot).map_err(|_| Error::TransferFailed)?;
+            }
+
+            self.env().emit_event(FundDeposited {
+                account: Some(sender),
+                amount: payment - overshoot,
+                payer: Some(payer),
+                round: self.current_round,
+                paid_towards_round: amount_due,
+                total_contributed: contributed,
+            });
+            self.issue_receipt(sender, self.current_round, contribution);
+            if let Some(referrer) = self.unrewarded_referrals.take(sender) {
+                let bonus_bps = self.referral_bonus_bps.get().unwrap_or_default();
+                let amount = contribution.saturating_mul(bonus_bps as Balance) / MAX_BPS as Balance;
+                if amount > 0 {
+                    let mut due = self.referral_bonuses_due.get().unwrap_or_default();
+                    due.push(ReferralBonus {
+                        referrer,
+                        referee: sender,
+                        amount,
+                    });
+                    self.referral_bonuses_due.set(&due);
+                    self.pay_referral_bonuses(self.current_round)?;
+                }
+            }
+            Ok(())
+        }
+
+        // Mints the member a receipt for paying the round, if the fund issues receipts.
+        fn issue_receipt(&self, account: AccountId, round: u32, amount: Balance) {
+            let Some(collection) = self.receipt_collection else {
+                return;
+            };
+            let id = psp34::receipt_id(self.env().account_id(), account, round, amount);
+            let minted = psp34::mint(collection, account, id).is_ok();
+            self.env().emit_event(ReceiptIssued {
+                account: Some(account),
+                round,
+                amount,
+                minted,
+            });
+        }
+        // The draw function allows the admin to get a winner after the cycle is ended.
+        #[ink(message, payable)]
+            pub fn draw(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanDraw);
+            }
+            self.ensure_no_multisig()?;
+            self.run_draw()
+        }
+
+        fn run_draw(&mut self) -> Result<(), Error> {
+            self.non_reentrant(Self::draw_round)
+        }
+
+        fn draw_round(&mut self) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            if self.mode == FundMode::Auction {
+                return Err(Error::WrongFundMode);
+            }
+            let round = self.ensure_round_settleable()?;
+            let winner = match self.mode {
+                FundMode::Rotation => {
+                    let (position, winner) = self.next_rotation_slot().ok_or(Error::FailedToGetWinner)?;
+                    self.rotation_position = position + 1;
+                    winner
+                }
+                _ => self.draw_random_winner(round)?,
+            };
+            let prize = self.round_prize()?;
+            if self.dispute_period > 0 && !self.is_sealed() {
+                return self.hold_payout(round, winner, prize);
+            }
+            self.pay_winner(round, winner, prize, prize)
+        }
+
+        // Draws the round's winner from the configured randomness source and records how.
+        fn draw_random_winner(&mut self, round: u32) -> Result<AccountId, Error> {
+            let all_revealed = self.reveal_count.get(round) == self.commit_count.get(round);
+            if self.randomness_source == RandomnessSource::CommitReveal
+                && !all_revealed
+                && self.env().block_number() <= self.reveal_deadline
+            {
+                return Err(Error::RevealPeriodOpen);
+            }
+            let mut record = self.draw_seed(round)?;
+            let (winner_index, winner, probes) = self.select_ticket(record.seed)?;
+            record.probes = probes;
+            record.member_count = self.member_count;
+            record.ticket_count = self.ticket_count;
+            record.start_index = (record.seed % self.ticket_count as u64) as u32;
+            record.winner_index = winner_index;
+            record.winner = winner;
+            self.draw_records.insert(round, &record);
+            Ok(winner)
+        }
+
+        // Who settling the round awaiting its draw would pay right now and how the prize
+        // would be split, without drawing. In the random modes the winner is worked out from
+        // the current randomness inputs, so a draw in a later block can pick someone else.
+        #[ink(message)]
+        pub fn preview_draw(&self) -> Result<DrawPreview, Error> {
+            let round = self.ensure_round_settleable()?;
+            let gross = self.round_prize()?;
+            let tickets = self.round_snapshot(round)?.tickets as Balance;
+            let mut dividend_per_ticket = 0;
+            let (winner, prize, settled) = match self.mode {
+                FundMode::Rotation => {
+                    let (_, winner) = self.next_rotation_slot().ok_or(Error::FailedToGetWinner)?;
+                    (winner, gross, gross)
+                }
+                FundMode::Auction => {
+                    // Mirrors `award_lowest_bid`.
+                    let (winner, winning_bid) = self.lowest_bids.get(round).ok_or(Error::NoBids)?;
+                    let winning_bid = winning_bid.min(gross);
+                    let discount = gross - winning_bid;
+                    dividend_per_ticket = discount.checked_div(tickets).unwrap_or(0);
+                    let remainder = discount - dividend_per_ticket * tickets;
+                    match self.remainder_policy {
+                        RemainderPolicy::Winner => (winner, winning_bid + remainder, gross),
+                        RemainderPolicy::Pot => (winner, winning_bid, gross - remainder),
+                        RemainderPolicy::Organizer => (winner, winning_bid, gross),
+                    }
+                }
+                _ => {
+                    let seed = self.draw_seed(round)?.seed;
+                    let (_, winner, _) = self.select_ticket(seed)?;
+                    (winner, gross, gross)
+                }
+            };
+            let commission = self.commission_on(prize)?;
+            let bonus = self.round_bonus();
+            let payout = (prize - commission).checked_add(bonus).ok_or(Error::ArithmeticOverflow)?;
+            // The last win completes the fund and shares out what the rounds left behind.
+            if self.winner_count + 1 == self.ticket_count {
+                let leftover = self.total_amount.checked_sub(settled).ok_or(Error::ArithmeticOverflow)?;
+                dividend_per_ticket += leftover.checked_div(tickets).unwrap_or(0);
+            }
+            Ok(DrawPreview {
+                round,
+                // A sealed round's winner is not shown before the draw either.
+                winner: if self.is_sealed() { Self::unrevealed_winner() } else { winner },
+                prize,
+                commission,
+                bonus,
+                payout,
+                dividend_per_ticket,
+                dividends: dividend_per_ticket * tickets,
+            })
+        }
+
+        // Position in the rotation of the next member who can win, walking on from
+        // `rotation_position` so members skipped while ineligible come round again.
+        fn next_rotation_slot(&self) -> Option<(u32, AccountId)> {
+            let len = self.rotation.len() as u32;
+            (0..len)
+                .map(|offset| (self.rotation_position + offset) % len)
+                .map(|position| (position, self.rotation[position as usize]))
+                .find(|(_, account)| self.is_eligible(*account))
+        }
+
+        // In rotation mode, the member the next draw pays.
+        #[ink(message)]
+        pub fn next_in_rotation(&self) -> Option<AccountId> {
+            if self.mode != FundMode::Rotation {
+                return None;
+            }
+            self.next_rotation_slot().map(|(_, account)| account)
+        }
+
+        #[ink(message)]
+        pub fn get_rotation(&self) -> Vec<AccountId> {
+            self.rotation.clone()
+        }
+
+        // Set the order members win in, before the fund starts. It has to list every member
+        // exactly once, also when `start_fund` runs, so members joining afterwards mean it
+        // has to be set again.
+        #[ink(message)]
+        pub fn set_rotation(&mut self, order: Vec<AccountId>) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanSetRotation);
+            }
+            if self.mode != FundMode::Rotation {
+                return Err(Error::WrongFundMode);
+            }
+            if self.phase != FundPhase::Recruiting {
+                return Err(Error::FundAlreadyStarted);
+            }
+            if !self.is_rotation_order(&order) {
+                return Err(Error::InvalidRotation);
+            }
+            self.rotation = order.clone();
+            self.env().emit_event(RotationSet {
+                admin: Some(sender),
+                order,
+            });
+            Ok(())
+        }
+
+        // Whether the order lists every member exactly once.
+        fn is_rotation_order(&self, order: &[AccountId]) -> bool {
+            order.len() == self.member_count as usize
+                && order.iter().enumerate().all(|(position, account)| {
+                    self.is_member(*account) && !order[..position].contains(account)
+                })
+        }
+
+        // Announces the winner and holds their payout until the dispute window closes.
+        fn hold_payout(&mut self, round: u32, winner: AccountId, prize: Balance) -> Result<(), Error> {
+            let dispute_deadline = self.env().block_number().saturating_add(self.dispute_period);
+            self.pending_payout = Some(PendingPayout {
+                round,
+                winner,
+                prize,
+                dispute_deadline,
+            });
+            self.env().emit_event(WinnerAnnounced {
+                winner: Some(winner),
+                round,
+                prize,
+                dispute_deadline,
+            });
+            Ok(())
+        }
+
+        // The winner awaiting payout keeps their seat until they are paid: `finalize_payout`
+        // settles the round with them as a member.
+        fn ensure_not_pending_winner(&self, account: AccountId) -> Result<(), Error> {
+            if self.pending_payout.as_ref().is_some_and(|pending| pending.winner == account) {
+                return Err(Error::WinnerPayoutPending);
+            }
+            Ok(())
+        }
+
+        // Challenge the winner awaiting payout. The caller backs the dispute; other members
+        // join with `support_dispute`.
+        #[ink(message)]
+        pub fn raise_dispute(&mut self, reason_hash: Hash) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let pending = self.open_dispute_window(sender)?;
+            // A winner drawn again after an upheld dispute can be disputed afresh.
+            if self.disputes.get((pending.round, pending.winner)).is_some_and(|dispute| !dispute.upheld) {
+                return Err(Error::DisputeAlreadyRaised);
+            }
+            let dispute = Dispute {
+                raised_by: sender,
+                reason_hash,
+                votes: 0,
+                electorate: self.member_count.saturating_sub(1),
+                upheld: false,
+            };
+            self.env().emit_event(DisputeRaised {
+                round: pending.round,
+                winner: Some(pending.winner),
+                raised_by: Some(sender),
+                reason_hash,
+            });
+            self.back_dispute(sender, pending, dispute)
+        }
+
+        #[ink(message)]
+        pub fn support_dispute(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let pending = self.open_dispute_window(sender)?;
+            let dispute = self
+                .disputes
+                .get((pending.round, pending.winner))
+                .filter(|dispute| !dispute.upheld)
+                .ok_or(Error::NoDispute)?;
+            if self.dispute_votes.get((pending.winner, sender)) == Some(pending.dispute_deadline) {
+                return Err(Error::AlreadyVoted);
+            }
+            self.back_dispute(sender, pending, dispute)
+        }
+
+        // The pending payout, if the caller is a member other than its winner and the
+        // window is still open.
+        fn open_dispute_window(&self, sender: AccountId) -> Result<PendingPayout, Error> {
+            self.ensure_active()?;
+            let pending = self.pending_payout.clone().ok_or(Error::NoPendingPayout)?;
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            if sender == pending.winner {
+                return Err(Error::CannotDisputeOwnWin);
+            }
+            if self.env().block_number() > pending.dispute_deadline {
+                return Err(Error::DisputeWindowClosed);
+            }
+            Ok(pending)
+        }
+
+        // Counts the sender's vote and, once a strict majority backs the dispute, drops the
+        // payout so the round can be drawn again.
+        fn back_dispute(&mut self, sender: AccountId, pending: PendingPayout, mut dispute: Dispute) -> Result<(), Error> {
+            let key = (pending.round, pending.winner);
+            dispute.votes += 1;
+            self.dispute_votes.insert((pending.winner, sender), &pending.dispute_deadline);
+            self.env().emit_event(DisputeBacked {
+                round: pending.round,
+                voter: Some(sender),
+                votes: dispute.votes,
+            });
+            if dispute.votes.saturating_mul(2) > dispute.electorate {
+                dispute.upheld = true;
+                self.pending_payout = None;
+                self.draw_records.remove(pending.round);
+                self.env().emit_event(DisputeUpheld {
+                    round: pending.round,
+                    winner: Some(pending.winner),
+                    votes: dispute.votes,
+                });
+            }
+            self.disputes.insert(key, &dispute);
+            Ok(())
+        }
+
+        // Pay the announced winner once the dispute window has closed without a dispute
+        // being upheld. Anyone can call this, the winner included.
+        #[ink(message)]
+        pub fn finalize_payout(&mut self) -> Result<(), Error> {
+            self.non_reentrant(Self::settle_pending_payout)
+        }
+
+        fn settle_pending_payout(&mut self) -> Result<(), Error> {
+            self.ensure_active()?;
+            self.ensure_not_paused()?;
+            let pending = self.pending_payout.clone().ok_or(Error::NoPendingPayout)?;
+            if self.env().block_number() <= pending.dispute_deadline {
+                return Err(Error::DisputeWindowOpen);
+            }
+            self.pending_payout = None;
+            self.pay_winner(pending.round, pending.winner, pending.prize, pending.prize)
+        }
+
+        #[ink(message)]
+        pub fn get_pending_payout(&self) -> Option<PendingPayout> {
+            self.pending_payout.clone()
+        }
+
+        #[ink(message)]
+        pub fn get_dispute(&self, round: u32, winner: AccountId) -> Option<Dispute> {
+            self.disputes.get((round, winner))
+        }
+
+        // Pulls `amount` of a token fund's currency from `from` into the contract and books
+        // it as held. Native payments arrive with the call, so they are only booked.
+        fn collect(&mut self, from: AccountId, amount: Balance) -> Result<(), Error> {
+            if let Some(token) = self.contribution_token {
+                let contract = self.env().account_id();
+                psp22::transfer_from(token, from, contract, amount).map_err(|error| match error {
+                    TokenError::Token(PSP22Error::InsufficientAllowance) => Error::InsufficientAllowance,
+                    _ => Error::TokenTransferFailed,
+                })?;
+            }
+            self.held = self.held.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+            Ok(())
+        }
+
+        // Sends `amount` of the fund's currency, native or PSP22, out of the contract.
+        fn pay_out(&mut self, to: AccountId, amount: Balance) -> Result<(), Error> {
+            let held = self.held;
+            self.held = held.saturating_sub(amount);
+            if let Err(error) = self.send(to, amount) {
+                self.held = held;
+                return Err(error);
+            }
+            Ok(())
+        }
+
+        fn send(&self, to: AccountId, amount: Balance) -> Result<(), Error> {
+            match self.contribution_token {
+                Some(token) => psp22::transfer(token, to, amount).map_err(|_| Error::TokenTransferFailed),
+                None => self.env().transfer(to, amount).map_err(|_| Error::TransferFailed),
+            }
+        }
+
+        // Balance of the fund's currency the contract holds.
+        fn balance(&self) -> Result<Balance, Error> {
+            match self.contribution_token {
+                Some(token) => psp22::balance_of(token, self.env().account_id()).map_err(|_| Error::TokenTransferFailed),
+                None => Ok(self.env().balance()),
+            }
+        }
+
+        // What the contract holds beyond what it owes: tokens sent to it directly and
+        // other stray transfers.
+        #[ink(message)]
+        pub fn surplus(&self) -> Balance {
+            self.balance().unwrap_or(0).saturating_sub(self.held)
+        }
+
+        // Send the surplus to `to`. Only the difference between the balance and what the
+        // fund holds for its members can move, so contributions, collateral, prizes and
+        // dividends are never touched.
+        #[ink(message)]
+        pub fn sweep_surplus(&mut self, to: AccountId) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Treasurer) {
+                return Err(Error::OnlyAdminCanWithdraw);
+            }
+            let amount = self.balance()?.saturating_sub(self.held);
+            if amount == 0 {
+                return Err(Error::NoSurplus);
+            }
+            self.send(to, amount)?;
+            self.env().emit_event(SurplusSwept {
+                to: Some(to),
+                amount,
+            });
+            Ok(())
+        }
+
+        // Runs for every call that matches no other message, so value sent with a mistyped
+        // or unknown selector is refused rather than left in the contract beside the pot.
+        // With `accept_donations` the value is kept and recorded as a donation instead.
+        // Donations are not added to `held`, so they are part of the `surplus` and leave
+        // through `sweep_surplus`. A plain balance transfer does not call the contract at
+        // all and cannot be refused; it too ends up in the surplus.
+        #[ink(message, payable, selector = _)]
+        pub fn fallback(&mut self) -> Result<(), Error> {
+            let amount = self.env().transferred_value();
+            if self.contribution_token.is_some() && amount != 0 {
+                return Err(Error::NativeValueNotAccepted);
+            }
+            if !self.accept_donations.get().unwrap_or_default() || amount == 0 {
+                return Err(Error::UnknownMessage);
+            }
+            let donations = self.donations.get().unwrap_or_default().checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+            self.donations.set(&donations);
+            self.env().emit_event(DonationReceived {
+                donor: Some(self.env().caller()),
+                amount,
+                donations,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_donations(&self) -> Balance {
+            self.donations.get().unwrap_or_default()
+        }
+
+        // Runs `f` with the reentrancy lock held, so a payout recipient cannot call back
+        // into another payout path while the first one is still running.
+        fn non_reentrant<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+            if self.reentrancy_lock {
+                return Err(Error::ReentrantCall);
+            }
+            self.reentrancy_lock = true;
+            let result = f(self);
+            self.reentrancy_lock = false;
+            result
+        }
+
+        fn ensure_started(&self) -> Result<(), Error> {
+            if self.phase == FundPhase::Recruiting {
+                return Err(Error::FundNotStarted);
+            }
+            Ok(())
+        }
+
+        // Checks the fund is active and in `phase`, failing with `otherwise` if it is in
+        // another phase after the start.
+        fn ensure_phase(&self, phase: FundPhase, otherwise: Error) -> Result<(), Error> {
+            self.ensure_active()?;
+            if self.phase == phase {
+                return Ok(());
+            }
+            self.ensure_started()?;
+            Err(otherwise)
+        }
+
+        fn set_phase(&mut self, phase: FundPhase) {
+            let from = self.phase;
+            self.phase = phase;
+            self.env().emit_event(PhaseChanged { from, to: phase });
+        }
+
+        fn ensure_not_paused(&self) -> Result<(), Error> {
+            if self.paused {
+                return Err(Error::ContractPaused);
+            }
+            self.ensure_migrated()
+        }
+
+        // Storage left by older code is only read once `migrate` has converted it. Paths
+        // that stay open while the fund is paused still have to check this.
+        fn ensure_migrated(&self) -> Result<(), Error> {
+            if self.storage_version < STORAGE_VERSION {
+                return Err(Error::MigrationPending);
+            }
+            Ok(())
+        }
+
+        // Rejects activity once the fund has completed or been cancelled.
+        fn ensure_active(&self) -> Result<(), Error> {
+            match self.phase {
+                FundPhase::Completed => Err(Error::ChitFundTerminated),
+                FundPhase::Cancelled => Err(Error::FundCancelled),
+                _ => Ok(()),
+            }
+        }
+
+        // With co-admins configured, lifecycle actions have to go through `propose_action`.
+        fn ensure_no_multisig(&self) -> Result<(), Error> {
+            if self.approval_threshold > 0 {
+                return Err(Error::RequiresMultisig);
+            }
+            Ok(())
+        }
+
+        // Checks shared by `draw` and `close_bidding`. Returns the round awaiting settlement.
+        fn ensure_round_settleable(&self) -> Result<u32, Error> {
+            self.ensure_phase(FundPhase::DrawPending, Error::ChitFundNotFinished)?;
+            // `end_cycle` has already moved `current_round` on to the next round.
+            let round = self.current_round.saturating_sub(1);
+            if self.round_history.contains(round) {
+                return Err(Error::RoundAlreadyDrawn);
+            }
+            if self.voided_rounds.contains(round) {
+                return Err(Error::RoundVoided);
+            }
+            if self.escrow_expired() {
+                return Err(Error::EscrowExpired);
+            }
+            if self.pending_payout.is_some() {
+                return Err(Error::PayoutPending);
+            }
+            if !self.paid_up_winners_only && !self.defaulters(round).is_empty() {
+                return Err(Error::ContributionsIncomplete);
+            }
+            if self.is_sealed() && !self.round_secrets.contains(round) {
+                return Err(Error::RoundSecretMissing);
+            }
+            Ok(round)
+        }
+
+        // Prize a round pays out with the current membership: `monthly_contribution` for
+        // every ticket, the winner's included.
+        #[ink(message)]
+        pub fn scheduled_prize(&self) -> Balance {
+            self.monthly_contribution.saturating_mul(self.ticket_count as Balance)
+        }
+
+        // The scheduled prize for the round awaiting its draw, capped at what was actually
+        // collected when defaulters were skipped.
+        fn round_prize(&self) -> Result<Balance, Error> {
+            let snapshot = self.round_snapshot(self.current_round.saturating_sub(1))?;
+            Ok(snapshot.expected.min(self.total_amount))
+        }
+
+        // Share of the bonus pool the round being settled pays its winner.
+        fn round_bonus(&self) -> Balance {
+            match self.bonus_split {
+                BonusSplit::FirstRound => self.bonus_pool,
+                BonusSplit::EvenlyAcrossRounds => {
+                    let rounds_left = self.ticket_count.saturating_sub(self.winner_count).max(1);
+                    self.bonus_pool / rounds_left as Balance
+                }
+            }
+        }
+
+        // Membership of a closed round. Rounds closed before snapshots were kept fall back
+        // to the current membership.
+        fn round_snapshot(&self, round: u32) -> Result<RoundSnapshot, Error> {
+            if let Some(snapshot) = self.round_snapshots.get(round) {
+                return Ok(snapshot);
+            }
+            self.current_snapshot()
+        }
+
+        // The membership as it stands, as a round snapshot.
+        fn current_snapshot(&self) -> Result<RoundSnapshot, Error> {
+            let expected = self
+                .monthly_contribution
+                .checked_mul(self.ticket_count as Balance)
+                .ok_or(Error::ArithmeticOverflow)?;
+            Ok(RoundSnapshot { members: self.member_count, tickets: self.ticket_count, expected })
+        }
+
+        fn tickets_of(&self, account: AccountId) -> u32 {
+            self.members.get(account).map_or(0, |member| member.tickets)
+        }
+
+        // What the account owes each round: `monthly_contribution` per ticket.
+        fn contribution_of(&self, account: AccountId) -> Result<Balance, Error> {
+            self.monthly_contribution
+                .checked_mul(self.tickets_of(account) as Balance)
+                .ok_or(Error::ArithmeticOverflow)
+        }
+
+        // Organizer's commission on a prize, rounded as configured.
+        fn commission_on(&self, prize: Balance) -> Result<Balance, Error> {
+            let scaled = prize.checked_mul(self.commission_bps as Balance).ok_or(Error::ArithmeticOverflow)?;
+            let max = MAX_BPS as Balance;
+            let (quotient, remainder) = (scaled / max, scaled % max);
+            let round_up = match self.commission_rounding {
+                Rounding::Down => false,
+                Rounding::Up => remainder > 0,
+                Rounding::Nearest => remainder * 2 >= max,
+            };
+            Ok(quotient + round_up as Balance)
+        }
+
+        // Adds a dividend split's remainder to the commission. Returns whether the remainder
+        // policy sends it there.
+        fn remainder_to_organizer(&mut self, remainder: Balance) -> Result<bool, Error> {
+            if self.remainder_policy != RemainderPolicy::Organizer {
+                return Ok(false);
+            }
+            self.commission_balance = self
+                .commission_balance
+                .checked_add(remainder)
+                .ok_or(Error::ArithmeticOverflow)?;
+            Ok(true)
+        }
+
+        // Credits an equal share of `amount` per ticket in the round's snapshot to every
+        // participant's dividends and returns what was not shared out.
+        fn share_as_dividends(&mut self, round: u32, amount: Balance) -> Result<Balance, Error> {
+            let tickets = self.round_snapshot(round)?.tickets as Balance;
+            if tickets == 0 {
+                return Ok(amount);
+            }
+            let dividend = amount / tickets;
+            let mut shared: Balance = 0;
+            if dividend > 0 {
+                for participant in self.participant_list() {
+                    let share = dividend * self.tickets_of(participant) as Balance;
+                    self.credit_dividend(participant, round, share)?;
+                    shared = shared.checked_add(share).ok_or(Error::ArithmeticOverflow)?;
+                }
+            }
+            amount.checked_sub(shared).ok_or(Error::ArithmeticOverflow)
+        }
+
+        fn update_round_tally(&mut self, update: impl FnOnce(&mut RoundTally)) {
+            let mut tally = self.round_tally.get().unwrap_or_default();
+            update(&mut tally);
+            self.round_tally.set(&tally);
+        }
+
+        fn credit_dividend(&mut self, account: AccountId, round: u32, amount: Balance) -> Result<(), Error> {
+            let balance = self
+                .dividends
+                .get(account)
+                .unwrap_or(0)
+                .checked_add(amount)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.dividends.insert(account, &balance);
+            let earned = self.dividends_earned.get(account).unwrap_or(0).saturating_add(amount);
+            self.dividends_earned.insert(account, &earned);
+            match self.round_history.get(round) {
+                Some(mut record) => {
+                    record.dividends = record.dividends.saturating_add(amount);
+                    self.round_history.insert(round, &record);
+                }
+                None => self.update_round_tally(|tally| tally.dividends = tally.dividends.saturating_add(amount)),
+            }
+            self.env().emit_event(DividendCredited {
+                account: Some(account),
+                round,
+                amount,
+            });
+            Ok(())
+        }
+
+        // Credits the referral bonuses due, oldest first, for as long as the commission
+        // covers them.
+        fn pay_referral_bonuses(&mut self, round: u32) -> Result<(), Error> {
+            let mut due = self.referral_bonuses_due.get().unwrap_or_default();
+            let mut paid = 0;
+            for bonus in &due {
+                if bonus.amount > self.commission_balance {
+                    break;
+                }
+                self.commission_balance -= bonus.amount;
+                self.credit_dividend(bonus.referrer, round, bonus.amount)?;
+                self.env().emit_event(ReferralBonusCredited {
+                    referrer: Some(bonus.referrer),
+                    referee: Some(bonus.referee),
+                    amount: bonus.amount,
+                });
+                paid += 1;
+            }
+            due.drain(..paid);
+            self.referral_bonuses_due.set(&due);
+            Ok(())
+        }
+
+        // Pays the round's prize, less the admin's commission, to the winner and settles the
+        // round, completing the fund once everyone has won. `settled` is what the round takes
+        // out of `total_amount`: the prize plus anything the caller has already handed out.
+        // The rest carries over, and whatever is left when the fund completes is shared
+        // between the members as dividends.
+        fn pay_winner(&mut self, round: u32, winner: AccountId, prize: Balance, settled: Balance) -> Result<(), Error> {
+            let remaining = self.total_amount.checked_sub(settled).ok_or(Error::ArithmeticOverflow)?;
+            // A sealed winner is still marked as having won, so they cannot win again, and
+            // membership records show it; it is the round's records and events that leave
+            // them out until they claim.
+            let sealed = self.is_sealed();
+            let commitment = if sealed {
+                Some(self.round_secrets.take(round).ok_or(Error::RoundSecretMissing)?)
+            } else {
+                None
+            };
+            let eligible = self.eligible_count();
+            let commission = self.commission_on(prize)?;
+            let bonus = self.round_bonus();
+            let amount = (prize - commission).checked_add(bonus).ok_or(Error::ArithmeticOverflow)?;
+            let amount = amount - self.repay_advance(winner, round, amount)?;
+            self.bonus_pool -= bonus;
+            self.commission_balance = self
+                .commission_balance
+                .checked_add(commission)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.pay_referral_bonuses(round)?;
+            let nominee = self.payout_address_of(winner);
+            let recorded_winner = if sealed { Self::unrevealed_winner() } else { winner };
+            if let Some(mut member) = self.members.get(winner) {
+                member.wins += 1;
+                member.has_won = member.wins >= member.tickets;
+                self.members.insert(winner, &member);
+            }
+            self.refresh_eligibility(winner);
+            self.winner_count += 1;
+            let won = self.prizes_won.get(winner).unwrap_or(0).checked_add(prize).ok_or(Error::ArithmeticOverflow)?;
+            self.prizes_won.insert(winner, &won);
+            let tally = self.round_tally.get().unwrap_or_default();
+            self.round_tally.set(&RoundTally::default());
+            self.round_history.insert(
+                round,
+                &RoundRecord {
+                    round,
+                    winner: recorded_winner,
+                    prize,
+                    commission,
+                    total_collected: self.total_amount,
+                    timestamp: self.env().block_timestamp(),
+                    bonus,
+                    snapshot: self.round_snapshot(round)?,
+                    collected: tally.collected,
+                    penalties: tally.penalties,
+                    dividends: tally.dividends,
+                    defaults: tally.defaults,
+                },
+            );
+            if let Some(mut record) = self.draw_records.get(round) {
+                record.winner = recorded_winner;
+                // The ticket positions would name the winner just as well.
+                if sealed {
+                    record.start_index = 0;
+                    record.winner_index = 0;
+                    record.probes = 0;
+                }
+                self.draw_records.insert(round, &record);
+            }
+            self.total_amount = remaining;
+            self.env().emit_event(DrawWinner {
+                victor: (!sealed).then_some(winner),
+                amount_won: amount,
+                nominee: (!sealed).then_some(nominee),
+                round,
+                eligible,
+                remaining: self.ticket_count - self.winner_count,
+            });
+            self.rounds_completed += 1;
+            if self.winner_count == self.ticket_count {
+                if self.total_amount > 0 {
+                    let remainder = self.share_as_dividends(round, self.total_amount)?;
+                    if remainder > 0 && !self.remainder_to_organizer(remainder)? {
+                        self.credit_dividend(winner, round, remainder)?;
+                    }
+                    self.total_amount = 0;
+                }
+                self.set_phase(FundPhase::Completed);
+                self.env().emit_event(ChitFundCompleted {
+                    rounds_completed: self.rounds_completed,
+                });
+            }
+            // The round is fully booked before any value leaves the contract.
+            let tranches = match self.payout_schedule {
+                PayoutSchedule::Installments(tranches) => tranches,
+                PayoutSchedule::LumpSum => 1,
+            };
+            // A second ticket's win while the first prize is still being paid out is paid
+            // in one go rather than replace the running plan, and so is a sealed prize.
+            if sealed {
+                let sealed_prize = SealedPrize {
+                    commitment: commitment.unwrap_or_default(),
+                    amount,
+                    expires_at: self.env().block_number().saturating_add(self.sealed_claim_period.get().unwrap_or_default()),
+                };
+                self.env().emit_event(WinnerSealed {
+                    round,
+                    commitment: sealed_prize.commitment,
+                    amount,
+                    expires_at: sealed_prize.expires_at,
+                });
+                self.sealed_prizes.insert(round, &sealed_prize);
+            } else if tranches > 1 && !self.payout_plans.contains(winner) {
+                self.payout_plans.insert(
+                    winner,
+                    &PayoutPlan {
+                        round,
+                        amount,
+                        tranches,
+                        claimed: 0,
+                        slashed: 0,
+                        won_at: self.rounds_completed,
+                    },
+                );
+                self.env().emit_event(PayoutPlanned {
+                    winner: Some(winner),
+                    round,
+                    amount,
+                    tranches,
+                });
+            } else if self.pay_prize(winner, nominee, amount).is_err() {
+                // Keep the prize in the contract for the winner to pull with `claim_prize`.
+                let unclaimed = self
+                    .unclaimed_prizes
+                    .get(winner)
+                    .unwrap_or(0)
+                    .checked_add(amount)
+                    .ok_or(Error::ArithmeticOverflow)?;
+                self.unclaimed_prizes.insert(winner, &unclaimed);
+                self.env().emit_event(PrizeUnclaimed {
+                    winner: Some(winner),
+                    amount,
+                });
+            }
+            if !sealed {
+                self.notify_hook(Notification::RoundSettled, |hook| {
+                    hooks::on_round_settled(hook, round, winner, prize)
+                });
+            }
+            if self.phase == FundPhase::Completed {
+                let rounds_completed = self.rounds_completed;
+                self.notify_hook(Notification::FundCompleted, |hook| {
+                    hooks::on_fund_completed(hook, rounds_completed)
+                });
+            }
+            Ok(())
+        }
+
+        // Register the contract notified of the fund's lifecycle, or None to stop notifying.
+        #[ink(message)]
+        pub fn set_hook(&mut self, hook: Option<AccountId>) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanSetHook);
+            }
+            self.hook.set(&hook);
+            self.env().emit_event(HookSet {
+                admin: Some(sender),
+                hook,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_hook(&self) -> Option<AccountId> {
+            self.hook.get().flatten()
+        }
+
+        // Calls the hook, if one is registered. A failing hook never holds up the fund; the
+        // failure is only logged.
+        fn notify_hook(&self, notification: Notification, call: impl FnOnce(AccountId) -> Result<(), HookError>) {
+            let Some(hook) = self.hook.get().flatten() else {
+                return;
+            };
+            if call(hook).is_err() {
+                self.env().emit_event(HookFailed {
+                    hook: Some(hook),
+                    notification,
+                });
+            }
+        }
+
+        // In auction mode, bid the smallest prize you are willing to take for the round
+        // that has just ended. The lowest bid wins when the admin closes the bidding.
+        #[ink(message)]
+        pub fn bid(&mut self, amount: Balance) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if self.mode != FundMode::Auction {
+                return Err(Error::WrongFundMode);
+            }
+            let member = self.members.get(sender).ok_or(Error::NotParticipant)?;
+            if member.has_won {
+                return Err(Error::AlreadyWon);
+            }
+            self.ensure_phase(FundPhase::DrawPending, Error::ChitFundNotFinished)?;
+            if amount == 0 || amount > self.round_prize()? {
+                return Err(Error::InvalidBid);
+            }
+            let round = self.current_round.saturating_sub(1);
+            if let Some((_, lowest)) = self.lowest_bids.get(round) {
+                if amount >= lowest {
+                    return Err(Error::BidTooHigh);
+                }
+            }
+            self.bids.insert((sender, round), &amount);
+            self.lowest_bids.insert(round, &(sender, amount));
+            self.env().emit_event(BidPlaced {
+                bidder: Some(sender),
+                round,
+                amount,
+            });
+            Ok(())
+        }
+
+        // In auction mode, award the round to the lowest bidder. The discount between the
+        // scheduled prize and the winning bid is shared equally between all participants
+        // as dividends; the indivisible remainder goes to the winner.
+        #[ink(message)]
+        pub fn close_bidding(&mut self) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Organizer) {
+                return Err(Error::OnlyAdminCanDraw);
+            }
+            if self.mode != FundMode::Auction {
+                return Err(Error::WrongFundMode);
+            }
+            self.non_reentrant(Self::award_lowest_bid)
+        }
+
+        fn award_lowest_bid(&mut self) -> Result<(), Error> {
+            let round = self.ensure_round_settleable()?;
+            let (winner, winning_bid) = self.lowest_bids.get(round).ok_or(Error::NoBids)?;
+
+            // The membership may have shrunk since the bid was placed.
+            let gross = self.round_prize()?;
+            let winning_bid = winning_bid.min(gross);
+            let discount = gross - winning_bid;
+            let tickets = self.round_snapshot(round)?.tickets as Balance;
+            let dividend = discount.checked_div(tickets).unwrap_or(0);
+            let remainder = self.share_as_dividends(round, discount)?;
+            let (prize, settled) = match self.remainder_policy {
+                RemainderPolicy::Winner => (winning_bid + remainder, gross),
+                // Left out of what the round settles, so it stays in `total_amount`.
+                RemainderPolicy::Pot => (winning_bid, gross - remainder),
+                RemainderPolicy::Organizer => {
+                    self.remainder_to_organizer(remainder)?;
+                    (winning_bid, gross)
+                }
+            };
+            self.env().emit_event(BiddingClosed {
+                round,
+                winner: Some(winner),
+                winning_bid,
+                dividend_per_member: dividend,
+            });
+            self.pay_winner(round, winner, prize, settled)
+        }
+        
+        // Picks the winner for the given seed, starting from ticket `seed % ticket_count`
+        // and following the redraw policy past tickets that cannot win. Returns None once
+        // every ticket has won, or when the draw has to be deferred.
+        pub fn select_winner(&self, seed: u64) -> Option<AccountId> {
+            self.select_ticket(seed).ok().map(|(_, winner, _)| winner)
+        }
+
+        // The winning ticket's position and holder, and the probes it took, see `DrawRecord`.
+        fn select_ticket(&self, seed: u64) -> Result<(u32, AccountId, u32), Error> {
+            // With one ticket each, ticket positions are member indices and the bitmap alone
+            // says which can win.
+            let single = self.ticket_count == self.member_count;
+            let tickets = if single { Vec::new() } else { self.ticket_table() };
+            let count = if single { self.member_count } else { tickets.len() as u32 };
+            if count == 0 {
+                return Err(Error::FailedToGetWinner);
+            }
+            let can_win = |position: u32| {
+                if single {
+                    self.eligibility_bit(position)
+                } else {
+                    tickets[position as usize].1
+                }
+            };
+            // Walks forward from `from`, returning the position and the steps taken.
+            let walk = |from: u32| {
+                let position = if single {
+                    self.next_eligible_index(from)
+                } else {
+                    (0..count).map(|offset| (from + offset) % count).find(|position| can_win(*position))
+                };
+                position.map(|position| (position, (position + count - from) % count))
+            };
+            let start = (seed % count as u64) as u32;
+            let (position, probes) = match self.redraw_policy.get().unwrap_or_default() {
+                _ if can_win(start) => (start, 0),
+                RedrawPolicy::LinearProbe => walk(start).ok_or(Error::FailedToGetWinner)?,
+                RedrawPolicy::Rehash => {
+                    let rehashed = (1..=MAX_REHASHES)
+                        .map(|nonce| (nonce, (Self::rehash(seed, nonce) % count as u64) as u32))
+                        .find(|(_, position)| can_win(*position));
+                    match rehashed {
+                        Some((nonce, position)) => (position, nonce),
+                        None => {
+                            let (position, steps) = walk(start).ok_or(Error::FailedToGetWinner)?;
+                            (position, MAX_REHASHES + steps)
+                        }
+                    }
+                }
+                RedrawPolicy::DeferToNextBlock if self.eligible_count() > 0 => return Err(Error::DrawDeferred),
+                RedrawPolicy::DeferToNextBlock => return Err(Error::FailedToGetWinner),
+            };
+            let winner = if single {
+                self.member_index.get(position).ok_or(Error::FailedToGetWinner)?
+            } else {
+                tickets[position as usize].0
+            };
+            Ok((position, winner, probes))
+        }
+
+        // Every ticket in draw order with its holder and whether it can win.
+        fn ticket_table(&self) -> Vec<(AccountId, bool)> {
+            let mut tickets = Vec::new();
+            for index in 0..self.member_count {
+                let Some(account) = self.member_index.get(index) else {
+                    continue;
+                };
+                let Some(member) = self.members.get(account) else {
+                    continue;
+                };
+                let eligible = self.eligibility_bit(index);
+                tickets.extend((0..member.tickets).map(|ticket| (account, eligible && ticket >= member.wins)));
+            }
+            tickets
+        }
+
+        fn rehash(seed: u64, nonce: u32) -> u64 {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(seed, nonce), &mut output);
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&output[..8]);
+            u64::from_le_bytes(bytes)
+        }
+
+        // Whether the account can win the next draw: it has not won yet and, when only
+        // paid-up members may win, has never missed a round.
+        pub fn is_eligible(&self, account: AccountId) -> bool {
+            self.is_member(account)
+                && !self.has_won(account)
+                && (!self.paid_up_winners_only || self.missed_rounds.get(account).unwrap_or(0) == 0)
+        }
+
+        // Number of members who can win the next draw.
+        #[ink(message)]
+        pub fn eligible_count(&self) -> u32 {
+            (0..self.eligibility_words()).map(|word| self.eligibility_word(word).count_ones()).sum()
+        }
+
+        // Words of the bitmap that can hold a member's bit.
+        fn eligibility_words(&self) -> u32 {
+            self.member_count.div_ceil(128)
+        }
+
+        fn eligibility_word(&self, word: u32) -> u128 {
+            self.eligibility.get(word).unwrap_or(0)
+        }
+
+        fn eligibility_bit(&self, index: u32) -> bool {
+            self.eligibility_word(index / 128) & (1 << (index % 128)) != 0
+        }
+
+        fn set_eligibility_bit(&mut self, index: u32, eligible: bool) {
+            let word = index / 128;
+            let bit = 1u128 << (index % 128);
+            let bits = self.eligibility_word(word);
+            let updated = if eligible { bits | bit } else { bits & !bit };
+            if updated == 0 {
+                self.eligibility.remove(word);
+            } else if updated != bits {
+                self.eligibility.insert(word, &updated);
+            }
+        }
+
+        // Re-reads the account's eligibility into the bitmap after something it depends on
+        // has changed.
+        fn refresh_eligibility(&mut self, account: AccountId) {
+            if let Some(member) = self.members.get(account) {
+                let eligible = self.is_eligible(account);
+                self.set_eligibility_bit(member.index, eligible);
+            }
+        }
+
+        // Index of the first eligible member at or after `start`, wrapping around, found a
+        // word of the bitmap at a time.
+        fn next_eligible_index(&self, start: u32) -> Option<u32> {
+            let words = self.eligibility_words();
+            if words == 0 {
+                return None;
+            }
+            let first = start / 128;
+            // Bits at or after `start` in its word, then every later word, then the
+            // earlier words and the bits before `start` once the walk wraps around.
+            let high = self.eligibility_word(first) & (u128::MAX << (start % 128));
+            if high != 0 {
+                return Some(first * 128 + high.trailing_zeros());
+            }
+            (first + 1..words)
+                .chain(0..=first.min(words - 1))
+                .find_map(|word| {
+                    let bits = self.eligibility_word(word);
+                    (bits != 0).then(|| word * 128 + bits.trailing_zeros())
+                })
+                .filter(|index| *index < self.member_count)
+        }
+
+        // Seed for the draw, taken from the configured randomness source. In commit-reveal
+        // mode it is hashed from the secrets revealed for the round and the current block,
+        // so neither the admin nor any single participant can pick the winner. Returns the
+        // draw's record with everything but the winner filled in.
+        fn draw_seed(&self, round: u32) -> Result<DrawRecord, Error> {
+            let entropy = self.revealed_entropy.get(round).unwrap_or_default();
+            let block_number = self.env().block_number();
+            let timestamp = self.env().block_timestamp();
+            let mut input_hash = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(entropy, block_number, timestamp), &mut input_hash);
+            let randomness = if self.randomness_source == RandomnessSource::ChainExtension {
+                self.env()
+                    .extension()
+                    .fetch_random(input_hash)
+                    .map_err(|_| Error::RandomnessUnavailable)?
+            } else {
+                input_hash
+            };
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&randomness[..8]);
+            Ok(DrawRecord {
+                round,
+                source: self.randomness_source,
+                block_number,
+                timestamp,
+                entropy,
+                revealed_by: self
+                    .participant_list()
+                    .into_iter()
+                    .filter(|participant| self.revealed.contains((*participant, round)))
+                    .collect(),
+                commitments: self.commit_count.get(round).unwrap_or(0),
+                input_hash,
+                randomness,
+                seed: u64::from_le_bytes(bytes),
+                member_count: 0,
+                ticket_count: 0,
+                start_index: 0,
+                winner_index: 0,
+                winner: AccountId::from([0; 32]),
+                redraw_policy: self.redraw_policy.get().unwrap_or_default(),
+                probes: 0,
+            })
+        }
+
+        #[ink(message)]
+        pub fn get_draw_record(&self, round: u32) -> Option<DrawRecord> {
+            self.draw_records.get(round)
+        }
+
+        // Pull a prize whose transfer failed during `draw`.
+        #[ink(message)]
+        pub fn claim_prize(&mut self) -> Result<(), Error> {
+            self.non_reentrant(Self::pay_unclaimed_prize)
+        }
+
+        fn pay_unclaimed_prize(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let amount = self.unclaimed_prizes.take(sender).ok_or(Error::NoPrizeToClaim)?;
+            if self.pay_out(self.payout_address_of(sender), amount).is_err() {
+                self.unclaimed_prizes.insert(sender, &amount);
+                return Err(Error::PayoutTransferFailed);
+            }
+            self.env().emit_event(PrizeClaimed {
+                winner: Some(sender),
+                amount,
+            });
+            Ok(())
+        }
+
+        // Whether winners are kept sealed until they claim their prize.
+        fn is_sealed(&self) -> bool {
+            self.sealed_claim_period.get().unwrap_or_default() > 0
+        }
+
+        // Stands in for the winner in the records of a sealed round until it is claimed.
+        fn unrevealed_winner() -> AccountId {
+            AccountId::from([0; 32])
+        }
+
+        // Commitment to a round's secret: the Blake2x256 hash of the SCALE-encoded round and
+        // secret.
+        fn secret_commitment(round: u32, secret: &[u8; 32]) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(round, secret), &mut output);
+            Hash::from(output)
+        }
+
+        // Commit to the secret of a sealed round before it is drawn. The organizer picks a
+        // fresh random secret for every round, keeps it off-chain and, once the draw is in,
+        // hands it only to the winner, who needs it for `claim_sealed_prize`. Committing
+        // again before the draw replaces the commitment.
+        #[ink(message)]
+        pub fn commit_round_secret(&mut self, round: u32, commitment: Hash) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Organizer) {
+                return Err(Error::OnlyAdminCanDraw);
+            }
+            if !self.is_sealed() {
+                return Err(Error::SealingDisabled);
+            }
+            if self.round_history.contains(round) {
+                return Err(Error::RoundAlreadyDrawn);
+            }
+            self.round_secrets.insert(round, &commitment);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_sealed_prize(&self, round: u32) -> Option<SealedPrize> {
+            self.sealed_prizes.get(round)
+        }
+
+        // Claim the prize of a sealed round with the secret the organizer handed the winner.
+        // Only a member who has won can claim. Claiming is what names the winner: the
+        // round's records are filled in and the winner is announced.
+        #[ink(message)]
+        pub fn claim_sealed_prize(&mut self, round: u32, preimage: [u8; 32]) -> Result<(), Error> {
+            self.non_reentrant(|fund| fund.pay_sealed_prize(round, preimage))
+        }
+
+        fn pay_sealed_prize(&mut self, round: u32, preimage: [u8; 32]) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let sealed = self.sealed_prizes.get(round).ok_or(Error::NoPrizeToClaim)?;
+            if sealed.commitment != Self::secret_commitment(round, &preimage) {
+                return Err(Error::WrongClaimSecret);
+            }
+            if self.members.get(sender).is_none_or(|member| member.wins == 0) {
+                return Err(Error::NoPrizeToClaim);
+            }
+            if self.env().block_number() > sealed.expires_at {
+                return Err(Error::SealedPrizeExpired);
+            }
+            self.sealed_prizes.remove(round);
+            let mut prize = 0;
+            if let Some(mut record) = self.round_history.get(round) {
+                record.winner = sender;
+                prize = record.prize;
+                self.round_history.insert(round, &record);
+            }
+            if let Some(mut record) = self.draw_records.get(round) {
+                record.winner = sender;
+                self.draw_records.insert(round, &record);
+            }
+            self.env().emit_event(SealedPrizeClaimed {
+                winner: Some(sender),
+                round,
+                amount: sealed.amount,
+            });
+            if self.pay_out(self.payout_address_of(sender), sealed.amount).is_err() {
+                let unclaimed = self
+                    .unclaimed_prizes
+                    .get(sender)
+                    .unwrap_or(0)
+                    .checked_add(sealed.amount)
+                    .ok_or(Error::ArithmeticOverflow)?;
+                self.unclaimed_prizes.insert(sender, &unclaimed);
+                self.env().emit_event(PrizeUnclaimed {
+                    winner: Some(sender),
+                    amount: sealed.amount,
+                });
+            }
+            self.notify_hook(Notification::RoundSettled, |hook| {
+                hooks::on_round_settled(hook, round, sender, prize)
+            });
+            Ok(())
+        }
+
+        // Once a sealed prize's claim period has passed, anyone can have it shared between
+        // the members as dividends. What does not split evenly goes to the commission.
+        #[ink(message)]
+        pub fn forfeit_sealed_prize(&mut self, round: u32) -> Result<(), Error> {
+            let sealed = self.sealed_prizes.get(round).ok_or(Error::NoPrizeToClaim)?;
+            if self.env().block_number() <= sealed.expires_at {
+                return Err(Error::SealedPrizeNotExpired);
+            }
+            self.sealed_prizes.remove(round);
+            let remainder = self.share_as_dividends(round, sealed.amount)?;
+            self.commission_balance = self
+                .commission_balance
+                .checked_add(remainder)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.env().emit_event(SealedPrizeForfeited {
+                round,
+                amount: sealed.amount,
+            });
+            Ok(())
+        }
+
+        // Send the commission collected so far to the treasury, or to the admin if there is
+        // none. The admin or a treasurer can trigger it, and anyone once the admin role has
+        // been renounced.
+        #[ink(message)]
+        pub fn withdraw_commission(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Treasurer) && !self.admin_renounced.get().unwrap_or_default() {
+                return Err(Error::OnlyAdminCanWithdraw);
+            }
+            let amount = self.commission_balance;
+            if amount == 0 {
+                return Err(Error::NoCommissionToWithdraw);
+            }
+            let recipient = self.treasury.unwrap_or(self.admin);
+            self.commission_balance = 0;
+            if let Err(error) = self.pay_out(recipient, amount) {
+                self.commission_balance = amount;
+                return Err(error);
+            }
+            self.env().emit_event(CommissionWithdrawn {
+                admin: Some(recipient),
+                amount,
+            });
+            Ok(())
+        }
+
+        // End a particular round after its completion
+        #[ink(message)] 
+        pub fn end_cycle(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+            return Err(Error::OnlyOwnerCanEndCycle);
+            }     
+            self.ensure_no_multisig()?;
+            self.run_end_cycle(sender)
+        }
+
+        fn run_end_cycle(&mut self, sender: AccountId) -> Result<(), Error> {
+            self.ensure_not_paused()?;
+            self.ensure_phase(FundPhase::ContributionOpen, Error::ChitFundAlreadyFinished)?;
+            if self.schedule.has_deadline() && !self.is_round_expired() {
+                return Err(Error::RoundNotExpired);
+            }
+            self.close_round(sender)
+        }
+
+        // Moves the open round's deposits into `total_amount`, books the members who missed
+        // it and waits for the draw.
+        fn close_round(&mut self, sender: AccountId) -> Result<(), Error> {
+            self.settle_yield()?;
+            let mut total_amount = self.total_amount.checked_add(self.pot).ok_or(Error::ArithmeticOverflow)?;
+            let mut depositors = 0;
+            let mut on_time = Vec::new();
+            let mut defaulted = Vec::new();
+            for participant in self.participant_list() {
+                if self.deposits.contains((participant, self.current_round)) {
+                    depositors += 1;
+                    // A late payment resets the streak.
+                    if self.deposit_streaks.get(participant).unwrap_or(0) > 0 {
+                        on_time.push(participant);
+                    }
+                } else {
+                    defaulted.push(participant);
+                    self.deposit_streaks.remove(participant);
+                    let missed = self.missed_rounds.get(participant).unwrap_or(0) + 1;
+                    self.missed_rounds.insert(participant, &missed);
+                    self.refresh_eligibility(participant);
+                    let defaulted = self.rounds_defaulted.get(participant).unwrap_or(0) + 1;
+                    self.rounds_defaulted.insert(participant, &defaulted);
+                    if self.max_missed_rounds > 0 && missed == self.max_missed_rounds {
+                        self.defaulted_at.insert(participant, &self.schedule_now());
+                    }
+                    // Instalments that fell short are carried over towards the next round.
+                    if let Some(partial) = self.partial_deposits.take((participant, self.current_round)) {
+                        self.partial_deposits.insert((participant, self.current_round + 1), &partial);
+                    }
+                    // The defaulter's collateral stands in for the missing contribution.
+                    let contribution = self.contribution_of(participant)?;
+                    let held = self.collateral.get(participant).unwrap_or(0);
+                    let slashed = held.min(contribution);
+                    if slashed > 0 {
+                        total_amount = total_amount.checked_add(slashed).ok_or(Error::ArithmeticOverflow)?;
+                        self.collateral.insert(participant, &(held - slashed));
+                        self.env().emit_event(CollateralSlashed {
+                            account: Some(participant),
+                            round: self.current_round,
+                            amount: slashed,
+                        });
+                    }
+                    // A winner still being paid in installments covers the rest from them.
+                    let mut shortfall = contribution - slashed;
+                    if let Some(mut plan) = self.payout_plans.get(participant).filter(|_| shortfall > 0) {
+                        let taken = shortfall.min(plan.amount - plan.claimed - plan.slashed);
+                        shortfall -= taken;
+                        if taken > 0 {
+                            total_amount = total_amount.checked_add(taken).ok_or(Error::ArithmeticOverflow)?;
+                            plan.slashed += taken;
+                            self.payout_plans.insert(participant, &plan);
+                            self.env().emit_event(InstallmentSlashed {
+                                account: Some(participant),
+                                round: self.current_round,
+                                amount: taken,
+                            });
+                        }
+                    }
+                    // Whatever is still missing is owed if the member wants to cure the default.
+                    if shortfall > 0 {
+                        let owed = self.arrears.get(participant).unwrap_or(0).saturating_add(shortfall);
+                        self.arrears.insert(participant, &owed);
+                    }
+                }
+            }
+            if let Some(registry) = self.blacklist_registry {
+                // Reputation is a courtesy to later funds; this round closes regardless.
+                let _ = registry::report_round(registry, on_time, defaulted.clone());
+            }
+            self.round_snapshots.insert(self.current_round, &self.current_snapshot()?);
+            let collected = self.pot;
+            self.update_round_tally(|tally| {
+                tally.collected = collected;
+                tally.defaults = defaulted.len() as u32;
+            });
+            self.env().emit_event(RoundSummary {
+                round: self.current_round,
+                total_collected: self.pot,
+                participant_count: self.member_count,
+                depositors,
+            });
+            self.total_amount = total_amount;
+            self.pot = 0;
+            self.catch_up_since = None;
+            self.reveal_deadline = self.env().block_number() + REVEAL_PERIOD;
+            self.draw_pending_since.set(&self.env().block_number());
+            self.current_round += 1;
+                self.set_phase(FundPhase::DrawPending);
+                self.env().emit_event(CycleEnded {
+                    admin: Some(sender),
+                    round: self.current_round - 1,
+                    reveal_deadline: self.reveal_deadline,
+                });
+                let round = self.current_round - 1;
+                for account in defaulted {
+                    self.notify_hook(Notification::MemberDefault, |hook| {
+                        hooks::on_member_default(hook, account, round)
+                    });
+                }
+                Ok(())
+        }
+
+        // Change the monthly contribution for later rounds. Only allowed between cycles of a
+        // fund that has not completed or been cancelled, or while recruiting as long as
+        // `set_parameters` could still change it, since members' collateral was posted
+        // against the old amount.
+        #[ink(message)]
+        pub fn set_monthly_contribution(&mut self, amount: Balance) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanChangeContribution);
+            }
+            self.ensure_no_multisig()?;
+            self.run_set_monthly_contribution(sender, amount)
+        }
+
+        fn run_set_monthly_contribution(&mut self, sender: AccountId, amount: Balance) -> Result<(), Error> {
+            self.ensure_active()?;
+            match self.phase {
+                FundPhase::ContributionOpen => return Err(Error::CannotChangeMidCycle),
+                FundPhase::Recruiting if self.held > self.bonus_pool => return Err(Error::ParametersLocked),
+                _ => {}
+            }
+            if amount == 0 {
+                return Err(Error::InvalidContributionAmount);
+            }
+            self.monthly_contribution = amount;
+            self.env().emit_event(ContributionChanged {
+                admin: Some(sender),
+                amount,
+            });
+            Ok(())
+        }
+
+        // Change the fund's terms after deploying it. Only possible while recruiting and
+        // before the fund holds anyone's money, so nobody has committed to the old terms.
+        #[ink(message)]
+        pub fn set_parameters(
+            &mut self,
+            max_participants: u32,
+            monthly_contribution: Balance,
+            schedule: ScheduleSource,
+            commission_bps: u16,
+        ) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanSetParameters);
+            }
+            // A bonus does not lock the terms, member money does.
+            if self.phase != FundPhase::Recruiting || self.held > self.bonus_pool {
+                return Err(Error::ParametersLocked);
+            }
+            if max_participants == 0 || max_participants < self.ticket_count {
+                return Err(Error::InvalidParticipantLimit);
+            }
+            if monthly_contribution == 0 {
+                return Err(Error::InvalidContributionAmount);
+            }
+            let commission_bps = commission_bps.min(MAX_BPS);
+            self.max_participants = max_participants;
+            self.monthly_contribution = monthly_contribution;
+            self.schedule = schedule;
+            self.commission_bps = commission_bps;
+            self.env().emit_event(ParametersUpdated {
+                admin: Some(sender),
+                max_participants,
+                monthly_contribution,
+                schedule,
+                commission_bps,
+            });
+            Ok(())
+        }
+
+        // Add a promotional bonus for the winners, paid out as `bonus_split` says. Only the
+        // organizer can, and only while the fund is recruiting. Native funds send `amount`
+        // with the call; token funds have it pulled from the caller.
+        #[ink(message, payable)]
+        pub fn fund_bonus(&mut self, amount: Balance) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanFundBonus);
+            }
+            if self.phase != FundPhase::Recruiting {
+                return Err(Error::BonusFundingClosed);
+            }
+            let transferred = self.env().transferred_value();
+            if self.contribution_token.is_some() && transferred != 0 {
+                return Err(Error::NativeValueNotAccepted);
+            }
+            if amount == 0 || (self.contribution_token.is_none() && transferred != amount) {
+                return Err(Error::IncorrectBonusAmount);
+            }
+            let bonus_pool = self.bonus_pool.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+            self.collect(sender, amount)?;
+            self.bonus_pool = bonus_pool;
+            self.env().emit_event(BonusFunded {
+                organizer: Some(sender),
+                amount,
+                bonus_pool,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_bonus_pool(&self) -> Balance {
+            self.bonus_pool
+        }
+
+        // Set the contribution in whole units of the fund's currency, e.g. 50 for 50 tokens
+        // of a currency with `decimals` decimals.
+        #[ink(message)]
+        pub fn set_monthly_contribution_units(&mut self, units: Balance) -> Result<(), Error> {
+            let amount = self.to_base_units(units).ok_or(Error::ArithmeticOverflow)?;
+            self.set_monthly_contribution(amount)
+        }
+
+        // Whole units of the fund's currency in base units. None if it does not fit.
+        #[ink(message)]
+        pub fn to_base_units(&self, units: Balance) -> Option<Balance> {
+            units.checked_mul((10 as Balance).pow(self.decimals as u32))
+        }
+
+        #[ink(message)]
+        pub fn decimals(&self) -> u8 {
+            self.decimals
+        }
+
+        // Whether the account has paid its contribution for the given round.
+        #[ink(message)]
+        pub fn has_paid(&self, account: AccountId, round: u32) -> bool {
+            self.deposits.contains((account, round))
+        }
+
+        // Commit to a secret for this round's draw. The commitment is the Blake2x256 hash
+        // of the SCALE encoded `(caller, secret)` pair.
+        #[ink(message)]
+        pub fn commit(&mut self, commitment: Hash) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if self.randomness_source != RandomnessSource::CommitReveal {
+                return Err(Error::CommitRevealDisabled);
+            }
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            if self.phase != FundPhase::ContributionOpen {
+                return Err(Error::ChitFundHasFinished);
+            }
+            let round = self.current_round;
+            if self.commitments.contains((sender, round)) {
+                return Err(Error::AlreadyCommitted);
+            }
+            self.commitments.insert((sender, round), &commitment);
+            self.commit_count.insert(round, &(self.commit_count.get(round).unwrap_or(0) + 1));
+            self.env().emit_event(SecretCommitted {
+                account: Some(sender),
+                round,
+            });
+            Ok(())
+        }
+
+        // Reveal the secret committed during the round that has just ended.
+        #[ink(message)]
+        pub fn reveal(&mut self, secret: Hash) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if self.phase != FundPhase::DrawPending {
+                return Err(Error::ChitFundNotFinished);
+            }
+            if self.env().block_number() > self.reveal_deadline {
+                return Err(Error::RevealPeriodOver);
+            }
+            let round = self.current_round.saturating_sub(1);
+            let commitment = self.commitments.get((sender, round)).ok_or(Error::NoCommitment)?;
+            if self.revealed.contains((sender, round)) {
+                return Err(Error::AlreadyRevealed);
+            }
+            if Self::commitment_of(sender, secret) != commitment {
+                return Err(Error::InvalidReveal);
+            }
+
+            let mut entropy = self.revealed_entropy.get(round).unwrap_or_default();
+            for (byte, secret_byte) in entropy.iter_mut().zip(secret.as_ref()) {
+                *byte ^= secret_byte;
+            }
+            self.revealed_entropy.insert(round, &entropy);
+            self.revealed.insert((sender, round), &true);
+            self.reveal_count.insert(round, &(self.reveal_count.get(round).unwrap_or(0) + 1));
+            self.env().emit_event(SecretRevealed {
+                account: Some(sender),
+                round,
+            });
+            Ok(())
+        }
+
+        // The commitment a participant has to submit for the given secret.
+        pub fn commitment_of(account: AccountId, secret: Hash) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(account, secret), &mut output);
+            Hash::from(output)
+        }
+
+        // End the open round, draw and pay its winner and open the next round, all in one
+        // call. Anyone may settle once every member has paid or the deadline has passed; a
+        // round that still has defaulters at its deadline closes without a draw and its
+        // funds carry over, to be shared as dividends once the fund completes.
+        #[ink(message)]
+        pub fn settle_round(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if self.mode == FundMode::Auction {
+                return Err(Error::WrongFundMode);
+            }
+            self.ensure_not_paused()?;
+            self.ensure_phase(FundPhase::ContributionOpen, Error::ChitFundAlreadyFinished)?;
+            let round = self.current_round;
+            let complete = self.defaulters(round).is_empty();
+            if !complete && !self.is_round_expired() {
+                return Err(Error::ContributionsIncomplete);
+            }
+            // Committed secrets can only be revealed once the round has ended, which rules
+            // out settling it in the same call.
+            if self.randomness_source == RandomnessSource::CommitReveal
+                && self.commit_count.get(round).unwrap_or(0) > 0
+            {
+                return Err(Error::RevealPeriodOpen);
+            }
+            self.close_round(sender)?;
+            if complete || (self.paid_up_winners_only && self.eligible_count() > 0) {
+                self.run_draw()?;
+            }
+            if self.phase == FundPhase::DrawPending {
+                self.run_begin_cycle(sender)?;
+            }
+            Ok(())
+        }
+
+        // Instalments the account has paid towards the open round without covering it yet.
+        #[ink(message)]
+        pub fn paid_towards_round(&self, account: AccountId) -> Balance {
+            self.partial_deposits.get((account, self.current_round)).unwrap_or(0)
+        }
+
+        // Tick of the schedule at which contributions for the open round are due, if rounds
+        // have a deadline.
+        #[ink(message)]
+        pub fn round_deadline(&self) -> Option<u64> {
+            self.schedule.deadline(self.round_start)
+        }
+
+        // The open round's deadline as both a block number and a timestamp.
+        #[ink(message)]
+        pub fn round_deadlines(&self) -> Option<Deadline> {
+            self.round_deadline().map(|deadline| self.to_deadline(deadline))
+        }
+
+        #[ink(message)]
+        pub fn get_schedule(&self) -> ScheduleSource {
+            self.schedule
+        }
+
+        // Whether the open round has run past its deadline. Always false when rounds
+        // have no fixed duration.
+        #[ink(message)]
+        pub fn is_round_expired(&self) -> bool {
+            self.round_deadline().is_some_and(|deadline| self.schedule_now() >= deadline)
+        }
+
+        fn schedule_now(&self) -> u64 {
+            self.schedule.now(self.env().block_number(), self.env().block_timestamp())
+        }
+
+        fn to_deadline(&self, tick: u64) -> Deadline {
+            self.schedule.to_deadline(tick, self.env().block_number(), self.env().block_timestamp())
+        }
+
+        // Leave the fund before the first draw, getting back every contribution made so far,
+        // any instalments towards the open round and the security deposit.
+        #[ink(message)]
+        pub fn leave(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            self.ensure_active()?;
+            self.ensure_migrated()?;
+            self.ensure_not_pending_winner(sender)?;
+            if self.rounds_completed > 0 {
+                return Err(Error::CannotLeaveMidCycle);
+            }
+            if self.has_outstanding_advance(sender) {
+                return Err(Error::AdvanceOutstanding);
+            }
+
+            // Nothing has been paid out yet, so the open round's deposit is still in `pot`
+            // and those of ended rounds are in `total_amount`.
+            let refund = self.total_contributed.get(sender).unwrap_or(0);
+            let open_round = if self.phase == FundPhase::ContributionOpen {
+                self.deposits.get((sender, self.current_round)).unwrap_or(0)
+            } else {
+                0
+            };
+            self.pot = self.pot.checked_sub(open_round).ok_or(Error::ArithmeticOverflow)?;
+            self.total_amount = self
+                .total_amount
+                .checked_sub(refund - open_round)
+                .ok_or(Error::ArithmeticOverflow)?;
+            let collateral = self.collateral.get(sender).unwrap_or(0);
+            let partial = self.partial_deposits.get((sender, self.current_round)).unwrap_or(0);
+            let payout = refund
+                .checked_add(collateral)
+                .and_then(|payout| payout.checked_add(partial))
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.collateral.remove(sender);
+            self.partial_deposits.remove((sender, self.current_round));
+            for round in 1..=self.current_round {
+                self.deposits.remove((sender, round));
+            }
+            self.total_contributed.remove(sender);
+            self.deposit_streaks.remove(sender);
+            self.remove_member(sender);
+            // Paid last, once the seat is gone, so the refund cannot be claimed twice.
+            if payout > 0 {
+                self.pay_out(sender, payout)?;
+            }
+            self.env().emit_event(LeftChitFund {
+                account: Some(sender),
+                refund: payout,
+            });
+            Ok(())
+        }
+
+        // Fee due on top of the contribution for a deposit made after the deadline.
+        #[ink(message)]
+        pub fn late_fee(&self) -> Balance {
+            self.monthly_contribution.saturating_mul(self.late_fee_bps as Balance) / MAX_BPS as Balance
+        }
+
+        // Remove a member who has missed at least `max_missed_rounds` rounds, refunding what
+        // is left of their collateral, their unfinished instalments, and what they paid in,
+        // less any prize they were awarded, as far as it is still booked for upcoming prizes.
+        // What went into prizes already paid out stays with their winners.
+        #[ink(message)]
+        pub fn expel(&mut self, account: AccountId) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Organizer) {
+                return Err(Error::OnlyAdminCanExpel);
+            }
+            if !self.is_member(account) {
+                return Err(Error::NotParticipant);
+            }
+            self.ensure_active()?;
+            self.ensure_migrated()?;
+            self.ensure_not_pending_winner(account)?;
+            let missed = self.missed_rounds.get(account).unwrap_or(0);
+            if self.max_missed_rounds == 0 || missed < self.max_missed_rounds {
+                return Err(Error::NotEnoughMissedRounds);
+            }
+            self.ensure_grace_period_over(account)?;
+            let round = self.current_round;
+            let partial = self.partial_deposits.get((account, round)).unwrap_or(0);
+            let (open_round, carried) = self.booked_contribution(account);
+            let refund = open_round
+                .checked_add(carried)
+                .and_then(|refund| refund.checked_add(partial))
+                .and_then(|refund| refund.checked_add(self.collateral.get(account).unwrap_or(0)))
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.release_booked_contribution(open_round, carried)?;
+            self.collateral.remove(account);
+            self.partial_deposits.remove((account, round));
+            self.remove_member(account);
+            self.report_expulsion(account);
+            if refund > 0 {
+                self.pay_out(account, refund)?;
+            }
+            self.env().emit_event(MemberExpelled {
+                account: Some(account),
+                missed_rounds: missed,
+                refund,
+            });
+            Ok(())
+        }
+
+        // Participants who have not paid their contribution for the given round.
+        #[ink(message)]
+        pub fn defaulters(&self, round: u32) -> Vec<AccountId> {
+            self.participant_list()
+                .into_iter()
+                .filter(|participant| !self.deposits.contains((*participant, round)))
+                .collect()
+        }
+
+        // Number of consecutive rounds the account has deposited in.
+        #[ink(message)]
+        pub fn deposit_streak(&self, account: AccountId) -> u32 {
+            self.deposit_streaks.get(account).unwrap_or(0)
+        }
+
+        // Returns (is_participant, has_deposited_this_round, total_contributed) for the caller.
+        #[ink(message)]
+        pub fn my_status(&self) -> (bool, bool, Balance) {
+            let caller = self.env().caller();
+            (
+                self.is_member(caller),
+                self.deposits.contains((caller, self.current_round)),
+                self.total_contributed.get(caller).unwrap_or(0),
+            )
+        }
+
+        // Abort the fund. Everything collected and not yet paid out is set aside and shared
+        // between the participants in proportion to their net contributions, which each of
+        // them pulls with `claim_refund`. The guardian can cancel on its own, without the
+        // co-admins' approval.
+        #[ink(message)]
+        pub fn cancel_fund(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if self.is_guardian(sender) {
+                return self.run_cancel_fund(sender);
+            }
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanCancel);
+            }
+            self.ensure_no_multisig()?;
+            self.run_cancel_fund(sender)
+        }
+
+        fn run_cancel_fund(&mut self, sender: AccountId) -> Result<(), Error> {
+            self.ensure_active()?;
+            self.settle_yield()?;
+
+            let mut refund_basis: Balance = 0;
+            for participant in self.participant_list() {
+                refund_basis = refund_basis
+                    .checked_add(self.net_contribution(participant))
+                    .ok_or(Error::ArithmeticOverflow)?;
+            }
+            self.refund_pool = self.pot.checked_add(self.total_amount).ok_or(Error::ArithmeticOverflow)?;
+            self.refund_basis = refund_basis;
+            self.pot = 0;
+            self.total_amount = 0;
+            // The bonus was never the members' money; it goes back to the organizer with the
+            // commission.
+            self.commission_balance = self
+                .commission_balance
+                .checked_add(self.bonus_pool)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.bonus_pool = 0;
+            // A winner still waiting out the dispute window is refunded like everyone else.
+            self.pending_payout = None;
+            self.set_phase(FundPhase::Cancelled);
+            self.env().emit_event(FundCancelled {
+                admin: Some(sender),
+                refund_pool: self.refund_pool,
+            });
+            Ok(())
+        }
+
+        // Give up on the round awaiting its draw when no member is left who could win it.
+        // Every member who contributed to it is credited their contribution back, to pull
+        // with `claim_refund`; collateral slashed from defaulters stays in the fund. The
+        // organizer or the guardian can void.
+        #[ink(message)]
+        pub fn void_round(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) && !self.is_guardian(sender) {
+                return Err(Error::OnlyAdminCanVoid);
+            }
+            self.ensure_phase(FundPhase::DrawPending, Error::ChitFundNotFinished)?;
+            let round = self.current_round.saturating_sub(1);
+            if self.round_history.contains(round) {
+                return Err(Error::RoundAlreadyDrawn);
+            }
+            if self.voided_rounds.contains(round) {
+                return Err(Error::RoundVoided);
+            }
+            if self.eligible_count() > 0 {
+                return Err(Error::RoundStillDrawable);
+            }
+            self.void_pending_round(Some(sender), round)
+        }
+
+        // Credits every member who contributed to `round` their contribution back, to pull
+        // with `claim_refund`, and marks the round void.
+        fn void_pending_round(&mut self, admin: Option<AccountId>, round: u32) -> Result<(), Error> {
+            let mut refunded: Balance = 0;
+            for participant in self.participant_list() {
+                if !self.deposits.contains((participant, round)) {
+                    continue;
+                }
+                let amount = self.contribution_of(participant)?.min(self.total_amount - refunded);
+                if amount == 0 {
+                    continue;
+                }
+                refunded += amount;
+                let credit = self.round_refunds.get(participant).unwrap_or(0).saturating_add(amount);
+                self.round_refunds.insert(participant, &credit);
+                // Refunded contributions no longer count towards a cancellation refund.
+                let contributed = self.total_contributed.get(participant).unwrap_or(0).saturating_sub(amount);
+                self.total_contributed.insert(participant, &contributed);
+                self.env().emit_event(RefundCredited {
+                    account: Some(participant),
+                    round,
+                    amount,
+                });
+            }
+            self.total_amount -= refunded;
+            self.voided_rounds.insert(round, &refunded);
+            // Late fees stay in the penalty pool, so they are reported with the next round.
+            self.round_tally.set(&RoundTally {
+                penalties: self.round_tally.get().unwrap_or_default().penalties,
+                ..Default::default()
+            });
+            self.env().emit_event(RoundVoided {
+                admin,
+                round,
+                refunded,
+            });
+            Ok(())
+        }
+
+        // Block after which the round awaiting its draw can be reclaimed. Funds that predate
+        // `escrow_timeout` have none stored and wait the default.
+        fn escrow_expires_at(&self) -> BlockNumber {
+            let timeout = self.escrow_timeout.get().unwrap_or(ESCROW_TIMEOUT);
+            self.draw_pending_since.get().unwrap_or_default().saturating_add(timeout)
+        }
+
+        // Whether the round awaiting its draw has waited out `escrow_timeout`.
+        fn escrow_expired(&self) -> bool {
+            self.phase == FundPhase::DrawPending
+                && self.env().block_number() > self.escrow_expires_at()
+        }
+
+        // Take back the caller's contribution to the round awaiting its draw once it has
+        // waited `escrow_timeout` blocks without one, with no need for the admin. The first
+        // call voids the round for everyone: no draw can pay it out any more, and every
+        // contributor is credited their contribution as with `void_round`.
+        #[ink(message)]
+        pub fn reclaim_if_expired(&mut self) -> Result<(), Error> {
+            self.non_reentrant(Self::reclaim_escrow)
+        }
+
+        fn reclaim_escrow(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_phase(FundPhase::DrawPending, Error::ChitFundNotFinished)?;
+            let round = self.current_round - 1;
+            if self.round_history.contains(round) {
+                return Err(Error::RoundAlreadyDrawn);
+            }
+            if !self.escrow_expired() {
+                return Err(Error::EscrowNotExpired);
+            }
+            if !self.voided_rounds.contains(round) {
+                self.void_pending_round(None, round)?;
+            }
+            if !self.round_refunds.contains(sender) {
+                return Err(Error::NothingToRefund);
+            }
+            self.pay_refund()
+        }
+
+        // Block after which the round awaiting its draw can be reclaimed, if there is one.
+        #[ink(message)]
+        pub fn escrow_deadline(&self) -> Option<BlockNumber> {
+            (self.phase == FundPhase::DrawPending).then(|| self.escrow_expires_at())
+        }
+
+        #[ink(message)]
+        pub fn is_round_voided(&self, round: u32) -> bool {
+            self.voided_rounds.contains(round)
+        }
+
+        // Pull the caller's contributions to voided rounds or, once the fund has been
+        // cancelled, their share of the refund pool. Contributions to voided rounds are
+        // paid first; a second call collects the cancellation share.
+        #[ink(message)]
+        pub fn claim_refund(&mut self) -> Result<(), Error> {
+            self.non_reentrant(Self::pay_refund)
+        }
+
+        fn pay_refund(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if let Some(credit) = self.round_refunds.take(sender) {
+                if let Err(error) = self.pay_out(sender, credit) {
+                    self.round_refunds.insert(sender, &credit);
+                    return Err(error);
+                }
+                self.env().emit_event(RefundClaimed {
+                    account: Some(sender),
+                    amount: credit,
+                });
+                return Ok(());
+            }
+            if self.phase != FundPhase::Cancelled {
+                return Err(Error::FundNotCancelled);
+            }
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            if self.refunds_claimed.contains(sender) {
+                return Err(Error::RefundAlreadyClaimed);
+            }
+            let amount = self.refund_due(sender);
+            if amount == 0 {
+                return Err(Error::NothingToRefund);
+            }
+            self.refunds_claimed.insert(sender, &amount);
+            if let Err(error) = self.pay_out(sender, amount) {
+                self.refunds_claimed.remove(sender);
+                return Err(error);
+            }
+            self.env().emit_event(RefundClaimed {
+                account: Some(sender),
+                amount,
+            });
+            Ok(())
+        }
+
+        // What the account is owed from voided rounds and the refund pool of a cancelled fund.
+        #[ink(message)]
+        pub fn refund_due(&self, account: AccountId) -> Balance {
+            let credit = self.round_refunds.get(account).unwrap_or(0);
+            if self.phase != FundPhase::Cancelled || self.refunds_claimed.contains(account) {
+                return credit;
+            }
+            // Instalments that never completed a contribution are returned in full.
+            let partial = self.partial_deposits.get((account, self.current_round)).unwrap_or(0) + credit;
+            if self.refund_basis == 0 {
+                return partial;
+            }
+            let share = self.net_contribution(account);
+            // Multiply first for precision; fall back to dividing first on overflow.
+            let refund = match share.checked_mul(self.refund_pool) {
+                Some(product) => product / self.refund_basis,
+                None => share / self.refund_basis * self.refund_pool,
+            };
+            refund.saturating_add(partial)
+        }
+
+        // The part of the account's net contribution the fund still holds for upcoming
+        // prizes: their deposit for the open round, in `pot`, and as much of the rest as
+        // `total_amount` covers.
+        fn booked_contribution(&self, account: AccountId) -> (Balance, Balance) {
+            let net = self.net_contribution(account);
+            let open_round = if self.phase == FundPhase::ContributionOpen {
+                self.deposits.get((account, self.current_round)).unwrap_or(0).min(net).min(self.pot)
+            } else {
+                0
+            };
+            (open_round, (net - open_round).min(self.total_amount))
+        }
+
+        // Takes a departing member's `booked_contribution` off the books before it is
+        // refunded, so no later prize counts on it.
+        fn release_booked_contribution(&mut self, open_round: Balance, carried: Balance) -> Result<(), Error> {
+            self.pot = self.pot.checked_sub(open_round).ok_or(Error::ArithmeticOverflow)?;
+            self.total_amount = self.total_amount.checked_sub(carried).ok_or(Error::ArithmeticOverflow)?;
+            Ok(())
+        }
+
+        // Contributions paid in minus any prize awarded.
+        fn net_contribution(&self, account: AccountId) -> Balance {
+            self.total_contributed
+                .get(account)
+                .unwrap_or(0)
+                .saturating_sub(self.prizes_won.get(account).unwrap_or(0))
+        }
+
+        // Recover the whole contract balance once the fund has been cancelled,
+        // for when refunds to the participants cannot complete.
+        #[ink(message)]
+        pub fn emergency_withdraw(&mut self, to: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanWithdraw);
+            }
+            if self.phase != FundPhase::Cancelled {
+                return Err(Error::FundNotCancelled);
+            }
+            let amount = self.balance()?;
+            self.pay_out(to, amount)?;
+            self.env().emit_event(EmergencyWithdrawal {
+                to: Some(to),
+                amount,
+            });
+            Ok(())
+        }
+
+        // The admin, or the last one if the role has been renounced.
+        #[ink(message)]
+        pub fn get_admin(&self) -> AccountId {
+            self.admin
+        }
+
+        #[ink(message)]
+        pub fn get_participants(&self) -> Vec<AccountId> {
+            self.participant_list()
+        }
+
+        #[ink(message)]
+        pub fn participant_count(&self) -> u32 {
+            self.member_count
+        }
+
+        // Seats the account holds.
+        #[ink(message)]
+        pub fn get_tickets(&self, account: AccountId) -> u32 {
+            self.tickets_of(account)
+        }
+
+        pub fn is_member(&self, account: AccountId) -> bool {
+            self.members.contains(account)
+        }
+
+        pub fn has_won(&self, account: AccountId) -> bool {
+            self.members.get(account).is_some_and(|member| member.has_won)
+        }
+
+        // Every participant in join order, except that removed members' slots are taken by
+        // the last member. Loads the whole index, so only use it where a full walk is needed.
+        fn participant_list(&self) -> Vec<AccountId> {
+            (0..self.member_count).filter_map(|index| self.member_index.get(index)).collect()
+        }
+
+        // Drops the account from the membership, moving the last member into its slot so
+        // the index stays dense.
+        fn remove_member(&mut self, account: AccountId) {
+            let Some(member) = self.members.take(account) else {
+                return;
+            };
+            let last = self.member_count - 1;
+            let moved_eligible = self.eligibility_bit(last);
+            self.set_eligibility_bit(member.index, moved_eligible);
+            self.set_eligibility_bit(last, false);
+            if member.index != last {
+                if let Some(moved) = self.member_index.get(last) {
+                    self.member_index.insert(member.index, &moved);
+                    if let Some(mut info) = self.members.get(moved) {
+                        info.index = member.index;
+                        self.members.insert(moved, &info);
+                    }
+                }
+            }
+            self.member_index.remove(last);
+            self.member_count = last;
+            self.ticket_count -= member.tickets;
+            // A round waiting for its draw is no longer shared with the departing member.
+            if self.phase == FundPhase::DrawPending {
+                let round = self.current_round.saturating_sub(1);
+                if let Some(mut snapshot) = self.round_snapshots.get(round) {
+                    snapshot.members = snapshot.members.saturating_sub(1);
+                    snapshot.tickets = snapshot.tickets.saturating_sub(member.tickets);
+                    snapshot.expected = snapshot
+                        .expected
+                        .saturating_sub(self.monthly_contribution.saturating_mul(member.tickets as Balance));
+                    self.round_snapshots.insert(round, &snapshot);
+                }
+            }
+            self.arrears.remove(account);
+            self.defaulted_at.remove(account);
+            self.unrewarded_referrals.remove(account);
+            self.winner_count -= member.wins;
+        }
+
+        #[ink(message)]
+        pub fn get_current_round(&self) -> u32 {
+            self.current_round
+        }
+
+        #[ink(message)]
+        pub fn get_pot(&self) -> Balance {
+            self.pot
+        }
+
+        #[ink(message)]
+        pub fn get_monthly_contribution(&self) -> Balance {
+            self.monthly_contribution
+        }
+
+        #[ink(message)]
+        pub fn is_finished(&self) -> bool {
+            self.phase == FundPhase::DrawPending
+        }
+
+        #[ink(message)]
+        pub fn get_phase(&self) -> FundPhase {
+            self.phase
+        }
+
+        #[ink(message)]
+        pub fn get_winners(&self) -> Vec<AccountId> {
+            self.get_all_winners().into_iter().map(|(_, winner)| winner).collect()
+        }
+
+        #[ink(message)]
+        pub fn get_round_history(&self, round: u32) -> Option<RoundRecord> {
+            self.round_history.get(round)
+        }
+
+        // Membership a closed round's prize is worked out from.
+        #[ink(message)]
+        pub fn get_round_snapshot(&self, round: u32) -> Option<RoundSnapshot> {
+            self.round_snapshots.get(round)
+        }
+
+        // Every settled round with its winner, oldest first. Sealed winners who have not
+        // claimed are left out.
+        #[ink(message)]
+        pub fn get_all_winners(&self) -> Vec<(u32, AccountId)> {
+            (1..self.current_round)
+                .filter_map(|round| self.round_history.get(round))
+                .filter(|record| record.winner != Self::unrevealed_winner())
+                .map(|record| (record.round, record.winner))
+                .collect()
+        }
+
+        // Whether the account has paid its contribution for the current round.
+        #[ink(message)]
+        pub fn has_paid_this_round(&self, account: AccountId) -> bool {
+            self.has_paid(account, self.current_round)
+        }
+
+        // Everything above in a single call.
+        #[ink(message)]
+        pub fn get_fund_info(&self) -> FundInfo {
+            FundInfo {
+                admin: self.admin,
+                max_participants: self.max_participants,
+                participant_count: self.participant_count(),
+                monthly_contribution: self.monthly_contribution,
+                current_round: self.current_round,
+                pot: self.pot,
+                total_amount: self.total_amount,
+                phase: self.phase,
+                rounds_completed: self.rounds_completed,
+                winners: self.get_winners(),
+            }
+        }
+
+        // Totals for reporting, added up from the settled rounds' records.
+        #[ink(message)]
+        pub fn get_fund_summary(&self) -> FundSummary {
+            let mut summary = FundSummary {
+                total_collected: 0,
+                total_paid_out: 0,
+                commission_earned: 0,
+                penalties_collected: 0,
+                dividends_distributed: 0,
+                defaults: 0,
+                rounds_completed: self.rounds_completed,
+                rounds_remaining: 0,
+                expected_completion: None,
+            };
+            for record in (1..=self.current_round).filter_map(|round| self.round_history.get(round)) {
+                summary.total_collected = summary.total_collected.saturating_add(record.collected);
+                let paid = record.prize.saturating_sub(record.commission).saturating_add(record.bonus);
+                summary.total_paid_out = summary.total_paid_out.saturating_add(paid);
+                summary.commission_earned = summary.commission_earned.saturating_add(record.commission);
+                summary.penalties_collected = summary.penalties_collected.saturating_add(record.penalties);
+                summary.dividends_distributed = summary.dividends_distributed.saturating_add(record.dividends);
+                summary.defaults = summary.defaults.saturating_add(record.defaults);
+            }
+            if matches!(self.phase, FundPhase::ContributionOpen | FundPhase::DrawPending) {
+                summary.rounds_remaining = self.ticket_count.saturating_sub(self.winner_count);
+                // The round under way ends at its deadline, and every round left after it
+                // takes one round length more.
+                let later_rounds = summary.rounds_remaining.saturating_sub(1) as u64;
+                summary.expected_completion = self
+                    .round_deadline()
+                    .map(|deadline| deadline.saturating_add(self.schedule.round_length().saturating_mul(later_rounds)))
+                    .map(|tick| self.to_deadline(tick));
+            }
+            summary
+        }
+
+        #[ink(message)]
+        pub fn get_contract_info(&self) -> ContractInfo {
+            ContractInfo {
+                version: String::from(VERSION),
+                mode: self.mode,
+                schedule: self.schedule,
+                token: self.contribution_token,
+                commission_bps: self.commission_bps,
+                phase: self.phase,
+            }
+        }
+
+        // First step of handing the fund over: nominate the next admin, who then has to
+        // call `accept_admin`. Nominating again replaces the pending admin.
+        #[ink(message)]
+        pub fn transfer_admin(&mut self, new_admin: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanTransferAdmin);
+            }
+            self.pending_admin = Some(new_admin);
+            self.env().emit_event(AdminTransferStarted {
+                admin: Some(sender),
+                pending_admin: Some(new_admin),
+            });
+            Ok(())
+        }
+
+        // Second step of the handover, called by the nominated account.
+        #[ink(message)]
+        pub fn accept_admin(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if self.pending_admin != Some(sender) {
+                return Err(Error::NotPendingAdmin);
+            }
+            if self.is_member(sender) && !self.admin_participates {
+                return Err(Error::AdminCannotParticipate);
+            }
+            let previous_admin = self.admin;
+            self.admin = sender;
+            self.pending_admin = None;
+            self.env().emit_event(AdminTransferred {
+                previous_admin: Some(previous_admin),
+                new_admin: Some(sender),
+            });
+            Ok(())
+        }
+
+        // Give up the admin role for good. Every admin-only message is unusable afterwards.
+        // The commission has to be withdrawn first; what accrues later can still be withdrawn
+        // by anyone, and goes where it would have gone before.
+        #[ink(message)]
+        pub fn renounce_admin(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanTransferAdmin);
+            }
+            if self.commission_balance > 0 {
+                return Err(Error::CommissionNotWithdrawn);
+            }
+            self.admin_renounced.set(&true);
+            self.pending_admin = None;
+            self.env().emit_event(AdminTransferred {
+                previous_admin: Some(sender),
+                new_admin: None,
+            });
+            Ok(())
+        }
+
+        // Propose a lifecycle action for the co-admins to approve. Proposing counts as the
+        // proposer's own approval. Returns the proposal id.
+        #[ink(message)]
+        pub fn propose_action(&mut self, action: AdminAction) -> Result<u32, Error> {
+            let sender = self.env().caller();
+            self.ensure_co_admin(sender)?;
+            let proposal_id = self.next_proposal_id;
+            self.next_proposal_id += 1;
+            self.proposals.insert(
+                proposal_id,
+                &Proposal {
+                    action,
+                    proposer: sender,
+                    approvals: 1,
+                    expires_at: self.env().block_number().saturating_add(PROPOSAL_LIFETIME),
+                    executed: false,
+                },
+            );
+            self.approvals.insert((proposal_id, sender), &true);
+            self.env().emit_event(ActionProposed {
+                proposal_id,
+                proposer: Some(sender),
+                action,
+            });
+            Ok(proposal_id)
+        }
+
+        #[ink(message)]
+        pub fn approve_action(&mut self, proposal_id: u32) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_co_admin(sender)?;
+            let mut proposal = self.open_proposal(proposal_id)?;
+            if self.approvals.contains((proposal_id, sender)) {
+                return Err(Error::AlreadyApproved);
+            }
+            proposal.approvals += 1;
+            self.proposals.insert(proposal_id, &proposal);
+            self.approvals.insert((proposal_id, sender), &true);
+            self.env().emit_event(ActionApproved {
+                proposal_id,
+                approver: Some(sender),
+                approvals: proposal.approvals,
+            });
+            Ok(())
+        }
+
+        // Run a proposal that has gathered enough approvals.
+        #[ink(message)]
+        pub fn execute_action(&mut self, proposal_id: u32) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_co_admin(sender)?;
+            let mut proposal = self.open_proposal(proposal_id)?;
+            if proposal.approvals < self.approval_threshold {
+                return Err(Error::NotEnoughApprovals);
+            }
+            match proposal.action {
+                AdminAction::BeginCycle => self.run_begin_cycle(sender)?,
+                AdminAction::EndCycle => self.run_end_cycle(sender)?,
+                AdminAction::Draw => self.run_draw()?,
+                AdminAction::CancelFund => self.run_cancel_fund(sender)?,
+                AdminAction::Upgrade(code_hash) => self.run_upgrade(sender, code_hash)?,
+                AdminAction::StartFund => self.run_start_fund(sender)?,
+                AdminAction::SetMonthlyContribution(amount) => self.run_set_monthly_contribution(sender, amount)?,
+            }
+            proposal.executed = true;
+            self.proposals.insert(proposal_id, &proposal);
+            self.env().emit_event(ActionExecuted {
+                proposal_id,
+                executor: Some(sender),
+                action: proposal.action,
+            });
+            Ok(())
+        }
+
+        fn ensure_co_admin(&self, account: AccountId) -> Result<(), Error> {
+            if self.approval_threshold == 0 {
+                return Err(Error::MultisigDisabled);
+            }
+            if !self.co_admins.contains(&account) {
+                return Err(Error::NotCoAdmin);
+            }
+            Ok(())
+        }
+
+        // Loads a proposal that can still be approved or executed.
+        fn open_proposal(&self, proposal_id: u32) -> Result<Proposal, Error> {
+            let proposal = self.proposals.get(proposal_id).ok_or(Error::ProposalNotFound)?;
+            if proposal.executed {
+                return Err(Error::ProposalAlreadyExecuted);
+            }
+            if self.env().block_number() > proposal.expires_at {
+                return Err(Error::ProposalExpired);
+            }
+            Ok(proposal)
+        }
+
+        #[ink(message)]
+        pub fn add_to_allowlist(&mut self, account: AccountId) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Organizer) {
+                return Err(Error::OnlyAdminCanManageAllowlist);
+            }
+            self.allowlist.insert(account, &true);
+            self.env().emit_event(AllowlistUpdated {
+                account: Some(account),
+                allowed: true,
+            });
+            Ok(())
+        }
+
+        // Removing an account stops it from joining; it does not remove an existing member.
+        #[ink(message)]
+        pub fn remove_from_allowlist(&mut self, account: AccountId) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Organizer) {
+                return Err(Error::OnlyAdminCanManageAllowlist);
+            }
+            self.allowlist.remove(account);
+            self.env().emit_event(AllowlistUpdated {
+                account: Some(account),
+                allowed: false,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_allowlisted(&self, account: AccountId) -> bool {
+            self.allowlist.contains(account)
+        }
+
+        // Take back what is left of the caller's security deposit once the fund has
+        // completed or been cancelled.
+        #[ink(message)]
+        pub fn reclaim_collateral(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !matches!(self.phase, FundPhase::Completed | FundPhase::Cancelled) {
+                return Err(Error::CollateralLocked);
+            }
+            let amount = self.collateral.get(sender).unwrap_or(0);
+            if amount == 0 {
+                return Err(Error::NoCollateral);
+            }
+            self.collateral.remove(sender);
+            self.pay_out(sender, amount)?;
+            self.env().emit_event(CollateralReturned {
+                account: Some(sender),
+                amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn collateral_of(&self, account: AccountId) -> Balance {
+            self.collateral.get(account).unwrap_or(0)
+        }
+
+        // Circuit breaker: stop joining, deposits and the cycle lifecycle. Refunds, prize
+        // and collateral claims keep working so nobody is locked out of their funds. The
+        // guardian can pause too, but only the admin can unpause.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) && !self.is_guardian(sender) {
+                return Err(Error::OnlyAdminCanPause);
+            }
+            if self.paused {
+                return Err(Error::AlreadyPaused);
+            }
+            self.paused = true;
+            self.env().emit_event(Paused {
+                admin: Some(sender),
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanPause);
+            }
+            if !self.paused {
+                return Err(Error::NotPaused);
+            }
+            self.paused = false;
+            self.env().emit_event(Unpaused {
+                admin: Some(sender),
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        // Replace the contract code while keeping its storage and balance, e.g. to patch a
+        // bug without moving the participants' funds. Call `migrate` on the new code next.
+        #[ink(message)]
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanUpgrade);
+            }
+            self.ensure_no_multisig()?;
+            self.run_upgrade(sender, code_hash)
+        }
+
+        fn run_upgrade(&mut self, sender: AccountId, code_hash: Hash) -> Result<(), Error> {
+            self.env().set_code_hash(&code_hash).map_err(|_| Error::UpgradeFailed)?;
+            self.env().emit_event(CodeUpgraded {
+                admin: Some(sender),
+                code_hash,
+            });
+            Ok(())
+        }
+
+        // Bring storage written by older code up to `STORAGE_VERSION`, one version at a
+        // time, converting at most `limit` members per call (zero for the default batch).
+        // Returns whether storage is up to date; until it is, the fund is as good as paused.
+        // Converting a member twice does no harm, so a repeated call is safe.
+        #[ink(message)]
+        pub fn migrate(&mut self, limit: u32) -> Result<bool, Error> {
+            if !self.is_admin(self.env().caller()) {
+                return Err(Error::OnlyAdminCanUpgrade);
+            }
+            if self.storage_version >= STORAGE_VERSION {
+                return Err(Error::AlreadyMigrated);
+            }
+            let from_version = self.storage_version;
+            let step = migrations::step_from(from_version).ok_or(Error::UnsupportedStorageVersion)?;
+            let chunk = migrations::chunk(self.migration_cursor.get().unwrap_or_default(), limit, self.member_count);
+            for index in chunk.clone() {
+                let Some(account) = self.member_index.get(index) else {
+                    continue;
+                };
+                match step {
+                    Step::IndexEligibility => self.refresh_eligibility(account),
+                }
+            }
+            if chunk.end < self.member_count {
+                self.migration_cursor.set(&chunk.end);
+                return Ok(false);
+            }
+            self.migration_cursor.set(&0);
+            self.storage_version = from_version + 1;
+            self.env().emit_event(StorageMigrated {
+                from_version,
+                to_version: self.storage_version,
+            });
+            Ok(self.storage_version == STORAGE_VERSION)
+        }
+
+        // Pull every dividend credited to the caller so far.
+        #[ink(message)]
+        pub fn claim_dividends(&mut self) -> Result<(), Error> {
+            self.non_reentrant(Self::pay_dividends)
+        }
+
+        fn pay_dividends(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let amount = self.dividends.get(sender).unwrap_or(0);
+            if amount == 0 {
+                return Err(Error::NoDividends);
+            }
+            self.dividends.remove(sender);
+            if let Err(error) = self.pay_out(sender, amount) {
+                self.dividends.insert(sender, &amount);
+                return Err(error);
+            }
+            self.env().emit_event(DividendsClaimed {
+                account: Some(sender),
+                amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_pending_dividends(&self, account: AccountId) -> Balance {
+            self.dividends.get(account).unwrap_or(0)
+        }
+
+        // Have prizes sent to `payout_address` instead of the caller's own account, e.g. a
+        // cold wallet. Setting it back to the caller's account clears it.
+        #[ink(message)]
+        pub fn set_payout_address(&mut self, payout_address: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            if payout_address == sender {
+                self.payout_addresses.remove(sender);
+            } else {
+                self.payout_addresses.insert(sender, &payout_address);
+            }
+            self.env().emit_event(PayoutAddressSet {
+                account: Some(sender),
+                payout_address: Some(payout_address),
+            });
+            Ok(())
+        }
+
+        // Account prizes won by `account` are sent to.
+        #[ink(message)]
+        pub fn payout_address_of(&self, account: AccountId) -> AccountId {
+            self.payout_addresses.get(account).unwrap_or(account)
+        }
+
+        // Have prizes converted into another PSP22 token through the fund's DEX router, or
+        // with None paid in the fund's currency again. Prizes paid in one go at the draw
+        // are converted; instalments and prizes claimed later are not. When the router
+        // cannot meet `min_rate` the prize is paid in the fund's currency instead.
+        #[ink(message)]
+        pub fn set_payout_preference(&mut self, preference: Option<PayoutPreference>) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            if let Some(preference) = &preference {
+                if self.dex_router.get().flatten().is_none() {
+                    return Err(Error::NoDexRouter);
+                }
+                if Some(preference.token) == self.contribution_token {
+                    return Err(Error::InvalidPayoutPreference);
+                }
+                self.payout_preferences.insert(sender, preference);
+            } else {
+                self.payout_preferences.remove(sender);
+            }
+            self.env().emit_event(PayoutPreferenceSet {
+                account: Some(sender),
+                preference,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_payout_preference(&self, account: AccountId) -> Option<PayoutPreference> {
+            self.payout_preferences.get(account)
+        }
+
+        // Pays `winner`'s prize to `to`, converted as the winner prefers if they have asked
+        // for another token.
+        fn pay_prize(&mut self, winner: AccountId, to: AccountId, amount: Balance) -> Result<(), Error> {
+            let (Some(router), Some(preference)) = (self.dex_router.get().flatten(), self.payout_preferences.get(winner)) else {
+                return self.pay_out(to, amount);
+            };
+            match self.swap_out(router, &preference, to, amount) {
+                Ok(received) => {
+                    self.held = self.held.saturating_sub(amount);
+                    self.env().emit_event(PrizeSwapped {
+                        winner: Some(winner),
+                        amount,
+                        token: preference.token,
+                        received,
+                    });
+                    Ok(())
+                }
+                Err(_) => {
+                    self.env().emit_event(PrizeSwapFailed {
+                        winner: Some(winner),
+                        amount,
+                        token: preference.token,
+                    });
+                    self.pay_out(to, amount)
+                }
+            }
+        }
+
+        fn swap_out(&self, router: AccountId, preference: &PayoutPreference, to: AccountId, amount: Balance) -> Result<Balance, Error> {
+            let min_amount_out = amount
+                .checked_mul(preference.min_rate)
+                .ok_or(Error::ArithmeticOverflow)?
+                / RATE_SCALE;
+            // Token funds let the router pull the prize, so a failed swap leaves it here.
+            if let Some(token) = self.contribution_token {
+                psp22::approve(token, router, amount).map_err(|_| Error::TokenTransferFailed)?;
+            }
+            let swapped = dex_router::swap_exact_in(router, self.contribution_token, preference.token, amount, min_amount_out, to);
+            if let (Err(_), Some(token)) = (&swapped, self.contribution_token) {
+                let _ = psp22::approve(token, router, 0);
+            }
+            swapped.map_err(|_| Error::SwapFailed)
+        }
+
+        // Open a vote to expel a member who has missed at least `max_missed_rounds` rounds.
+        // Only paid-up members may propose, and proposing counts as the proposer's vote.
+        #[ink(message)]
+        pub fn propose_expulsion(&mut self, account: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            self.ensure_started()?;
+            self.ensure_can_vote_on(sender, account)?;
+            if !self.is_member(account) {
+                return Err(Error::NotParticipant);
+            }
+            let missed = self.missed_rounds.get(account).unwrap_or(0);
+            if self.max_missed_rounds == 0 || missed < self.max_missed_rounds {
+                return Err(Error::NotEnoughMissedRounds);
+            }
+            let round = self.current_round;
+            if self.expulsion_votes.contains((round, account)) {
+                return Err(Error::ExpulsionAlreadyProposed);
+            }
+            let electorate = self
+                .participant_list()
+                .into_iter()
+                .filter(|member| *member != account && self.is_paid_up(*member))
+                .count() as u32;
+            self.expulsion_votes.insert(
+                (round, account),
+                &ExpulsionVote {
+                    proposer: sender,
+                    votes: 1,
+                    electorate,
+                    executed: false,
+                },
+            );
+            self.expulsion_ballots.insert((account, sender), &round);
+            self.env().emit_event(ExpulsionProposed {
+                round,
+                account: Some(account),
+                proposer: Some(sender),
+                electorate,
+            });
+            Ok(())
+        }
+
+        // Back the current round's vote to expel the account.
+        #[ink(message)]
+        pub fn vote_expulsion(&mut self, account: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            self.ensure_can_vote_on(sender, account)?;
+            let round = self.current_round;
+            let mut vote = self.open_expulsion_vote(round, account)?;
+            if self.expulsion_ballots.get((account, sender)) == Some(round) {
+                return Err(Error::AlreadyVoted);
+            }
+            vote.votes += 1;
+            self.expulsion_votes.insert((round, account), &vote);
+            self.expulsion_ballots.insert((account, sender), &round);
+            self.env().emit_event(ExpulsionVoted {
+                round,
+                account: Some(account),
+                voter: Some(sender),
+                votes: vote.votes,
+            });
+            Ok(())
+        }
+
+        // Expel the account once a majority of the electorate has voted for it. Anyone can
+        // call this. The member's remaining collateral goes into the penalty pool. Unfinished
+        // instalments are refunded, and so is what they paid in, less any prize, as far as it
+        // is still booked for upcoming prizes, as with `expel`. Later rounds shrink to the
+        // remaining membership.
+        #[ink(message)]
+        pub fn execute_expulsion(&mut self, account: AccountId) -> Result<(), Error> {
+            self.ensure_active()?;
+            self.ensure_migrated()?;
+            self.ensure_not_pending_winner(account)?;
+            let round = self.current_round;
+            let mut vote = self.open_expulsion_vote(round, account)?;
+            if vote.votes.saturating_mul(2) <= vote.electorate {
+                return Err(Error::QuorumNotReached);
+            }
+            if !self.is_member(account) {
+                return Err(Error::NotParticipant);
+            }
+            // A member who cured their default in the meantime keeps their seat.
+            if self.missed_rounds.get(account).unwrap_or(0) < self.max_missed_rounds {
+                return Err(Error::NotEnoughMissedRounds);
+            }
+            self.ensure_grace_period_over(account)?;
+            // Forfeited collateral joins the penalties shared out when the fund completes.
+            let forfeited = self.collateral.get(account).unwrap_or(0);
+            let penalty_pool = self.penalty_pool.checked_add(forfeited).ok_or(Error::ArithmeticOverflow)?;
+            let partial = self.partial_deposits.get((account, round)).unwrap_or(0);
+            let (open_round, carried) = self.booked_contribution(account);
+            let refund = open_round
+                .checked_add(carried)
+                .and_then(|refund| refund.checked_add(partial))
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.release_booked_contribution(open_round, carried)?;
+            self.penalty_pool = penalty_pool;
+            self.collateral.remove(account);
+            self.partial_deposits.remove((account, round));
+            self.remove_member(account);
+            self.report_expulsion(account);
+            vote.executed = true;
+            self.expulsion_votes.insert((round, account), &vote);
+            if refund > 0 {
+                self.pay_out(account, refund)?;
+            }
+            self.env().emit_event(MemberVotedOut {
+                account: Some(account),
+                round,
+                votes: vote.votes,
+                forfeited,
+                refund,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_expulsion_vote(&self, round: u32, account: AccountId) -> Option<ExpulsionVote> {
+            self.expulsion_votes.get((round, account))
+        }
+
+        // Whether the account is a member who paid for the last round to have closed.
+        #[ink(message)]
+        pub fn is_paid_up(&self, account: AccountId) -> bool {
+            self.is_member(account) && self.deposits.contains((account, self.current_round.saturating_sub(1)))
+        }
+
+        fn ensure_can_vote_on(&self, voter: AccountId, account: AccountId) -> Result<(), Error> {
+            if voter == account {
+                return Err(Error::CannotVoteOnOwnExpulsion);
+            }
+            if !self.is_paid_up(voter) {
+                return Err(Error::NotPaidUp);
+            }
+            Ok(())
+        }
+
+        // Loads the round's vote against the account while it can still be voted on.
+        fn open_expulsion_vote(&self, round: u32, account: AccountId) -> Result<ExpulsionVote, Error> {
+            let vote = self.expulsion_votes.get((round, account)).ok_or(Error::ProposalNotFound)?;
+            if vote.executed {
+                return Err(Error::ProposalAlreadyExecuted);
+            }
+            Ok(vote)
+        }
+
+        // Pull the prize tranches unlocked so far, in a fund paying prizes in installments.
+        #[ink(message)]
+        pub fn claim_installment(&mut self) -> Result<(), Error> {
+            self.non_reentrant(Self::pay_installment)
+        }
+
+        fn pay_installment(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let mut plan = self.payout_plans.get(sender).ok_or(Error::NoPrizeToClaim)?;
+            let amount = self.claimable_installment(sender);
+            if amount == 0 {
+                return Err(Error::NoPrizeToClaim);
+            }
+            plan.claimed += amount;
+            self.payout_plans.insert(sender, &plan);
+            if self.pay_out(self.payout_address_of(sender), amount).is_err() {
+                plan.claimed -= amount;
+                self.payout_plans.insert(sender, &plan);
+                return Err(Error::PayoutTransferFailed);
+            }
+            self.env().emit_event(InstallmentClaimed {
+                winner: Some(sender),
+                amount,
+                remaining: plan.amount - plan.claimed - plan.slashed,
+            });
+            Ok(())
+        }
+
+        // Unlocked prize tranches the account has not claimed yet. Everything left unlocks
+        // once the fund has completed or been cancelled.
+        #[ink(message)]
+        pub fn claimable_installment(&self, account: AccountId) -> Balance {
+            let Some(plan) = self.payout_plans.get(account) else {
+                return 0;
+            };
+            let unlocked = match self.phase {
+                FundPhase::Completed | FundPhase::Cancelled => plan.tranches,
+                _ => (self.rounds_completed - plan.won_at + 1).min(plan.tranches),
+            };
+            let unlocked = plan.amount.saturating_mul(unlocked as Balance) / plan.tranches as Balance;
+            unlocked.min(plan.amount - plan.slashed).saturating_sub(plan.claimed)
+        }
+
+        #[ink(message)]
+        pub fn get_payout_plan(&self, account: AccountId) -> Option<PayoutPlan> {
+            self.payout_plans.get(account)
+        }
+
+        // Choose the contract the pot can be invested in, or None to stop investing. Only
+        // possible while nothing is invested or stranded. Setting a strategy lifts the kill
+        // switch.
+        #[ink(message)]
+        pub fn set_yield_strategy(&mut self, strategy: Option<AccountId>) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_admin(sender) {
+                return Err(Error::OnlyAdminCanManageYield);
+            }
+            if self.invested > 0 || self.stranded.get().unwrap_or_default() > 0 {
+                return Err(Error::YieldStrategyInUse);
+            }
+            self.yield_strategy = strategy;
+            self.yield_killed = false;
+            self.env().emit_event(YieldStrategyChanged {
+                admin: Some(sender),
+                strategy,
+            });
+            Ok(())
+        }
+
+        // Kill switch: stop investing. The strategy is not called, so this works even when it
+        // is broken; what is already invested comes back when the round closes or through
+        // `recover_invested`.
+        #[ink(message)]
+        pub fn kill_yield_strategy(&mut self) -> Result<(), Error> {
+            if !self.is_admin(self.env().caller()) {
+                return Err(Error::OnlyAdminCanManageYield);
+            }
+            self.yield_killed = true;
+            Ok(())
+        }
+
+        // Pull invested funds back from the strategy. Brings the open round's investment
+        // back into the pot, or, once it has been stranded, shares whatever the strategy
+        // returns between the members as dividends. Can be retried until the strategy pays.
+        #[ink(message)]
+        pub fn recover_invested(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) && !self.has_role(sender, Role::Treasurer) {
+                return Err(Error::OnlyAdminCanManageYield);
+            }
+            self.non_reentrant(Self::run_recover_invested)
+        }
+
+        fn run_recover_invested(&mut self) -> Result<(), Error> {
+            let strategy = self.yield_strategy.ok_or(Error::NoYieldStrategy)?;
+            if self.invested > 0 {
+                return self.harvest_yield();
+            }
+            let stranded = self.stranded.get().unwrap_or_default();
+            if stranded == 0 {
+                return Err(Error::NothingToRecover);
+            }
+            self.stranded.set(&0);
+            let returned = match yield_strategy::withdraw_all(strategy) {
+                Ok(returned) => returned,
+                Err(_) => {
+                    self.stranded.set(&stranded);
+                    return Err(Error::YieldStrategyFailed);
+                }
+            };
+            self.held = self.held.checked_add(returned).ok_or(Error::ArithmeticOverflow)?;
+            let remainder = self.share_as_dividends(self.current_round, returned)?;
+            if !self.remainder_to_organizer(remainder)? {
+                self.pot = self.pot.checked_add(remainder).ok_or(Error::ArithmeticOverflow)?;
+            }
+            self.env().emit_event(YieldHarvested {
+                strategy: Some(strategy),
+                round: self.current_round,
+                principal: stranded,
+                returned,
+            });
+            Ok(())
+        }
+
+        // Park the part of the open round's pot that is not invested yet in the yield
+        // strategy. The funds come back when the round ends.
+        #[ink(message)]
+        pub fn invest_pot(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) && !self.has_role(sender, Role::Treasurer) {
+                return Err(Error::OnlyAdminCanManageYield);
+            }
+            self.ensure_not_paused()?;
+            self.ensure_phase(FundPhase::ContributionOpen, Error::ChitFundHasFinished)?;
+            let strategy = self.yield_strategy.ok_or(Error::NoYieldStrategy)?;
+            if self.yield_killed {
+                return Err(Error::YieldStrategyKilled);
+            }
+            // Whatever the strategy holds for the fund comes back in one withdrawal, so stranded
+            // funds have to be recovered before anything new goes in.
+            if self.stranded.get().unwrap_or_default() > 0 {
+                return Err(Error::YieldStrategyInUse);
+            }
+            let amount = self.pot.saturating_sub(self.invested);
+            if amount == 0 {
+                return Err(Error::NothingToInvest);
+            }
+            self.held = self.held.checked_sub(amount).ok_or(Error::ArithmeticOverflow)?;
+            self.invested = self.invested.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+            let value = match self.contribution_token {
+                Some(token) => {
+                    psp22::transfer(token, strategy, amount).map_err(|_| Error::TokenTransferFailed)?;
+                    0
+                }
+                None => amount,
+            };
+            yield_strategy::deposit(strategy, amount, value).map_err(|_| Error::YieldStrategyFailed)?;
+            self.env().emit_event(PotInvested {
+                strategy: Some(strategy),
+                round: self.current_round,
+                amount,
+            });
+            Ok(())
+        }
+
+        // Brings invested funds back into the pot. Yield is shared between the members as
+        // dividends; a loss comes out of the pot.
+        fn harvest_yield(&mut self) -> Result<(), Error> {
+            let (Some(strategy), true) = (self.yield_strategy, self.invested > 0) else {
+                return Ok(());
+            };
+            let principal = self.invested;
+            self.invested = 0;
+            let returned = match yield_strategy::withdraw_all(strategy) {
+                Ok(returned) => returned,
+                Err(_) => {
+                    self.invested = principal;
+                    return Err(Error::YieldStrategyFailed);
+                }
+            };
+            self.held = self.held.checked_add(returned).ok_or(Error::ArithmeticOverflow)?;
+            if returned >= principal {
+                let remainder = self.share_as_dividends(self.current_round, returned - principal)?;
+                if !self.remainder_to_organizer(remainder)? {
+                    self.pot = self.pot.checked_add(remainder).ok_or(Error::ArithmeticOverflow)?;
+                }
+            } else {
+                self.pot = self.pot.saturating_sub(principal - returned);
+            }
+            self.env().emit_event(YieldHarvested {
+                strategy: Some(strategy),
+                round: self.current_round,
+                principal,
+                returned,
+            });
+            Ok(())
+        }
+
+        // Harvests before the pot is settled. If the strategy fails, its funds are written off
+        // the pot and left for `recover_invested`, so closing or cancelling never waits on it.
+        fn settle_yield(&mut self) -> Result<(), Error> {
+            match self.harvest_yield() {
+                Err(Error::YieldStrategyFailed) => {}
+                result => return result,
+            }
+            let principal = self.invested;
+            self.invested = 0;
+            let stranded = self
+                .stranded
+                .get()
+                .unwrap_or_default()
+                .checked_add(principal)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.stranded.set(&stranded);
+            self.pot = self.pot.saturating_sub(principal);
+            self.env().emit_event(YieldStranded {
+                strategy: self.yield_strategy,
+                round: self.current_round,
+                amount: principal,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn grant_role(&mut self, account: AccountId, role: Role) -> Result<(), Error> {
+            if !self.is_admin(self.env().caller()) {
+                return Err(Error::OnlyAdminCanManageRoles);
+            }
+            if self.roles.contains((account, role)) {
+                return Err(Error::RoleAlreadyGranted);
+            }
+            self.roles.insert((account, role), &true);
+            self.env().emit_event(RoleGranted {
+                account: Some(account),
+                role,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn revoke_role(&mut self, account: AccountId, role: Role) -> Result<(), Error> {
+            if !self.is_admin(self.env().caller()) {
+                return Err(Error::OnlyAdminCanManageRoles);
+            }
+            if self.roles.take((account, role)).is_none() {
+                return Err(Error::RoleNotGranted);
+            }
+            self.env().emit_event(RoleRevoked {
+                account: Some(account),
+                role,
+            });
+            Ok(())
+        }
+
+        // Whether the account may act in the role, either by grant or by being the admin.
+        #[ink(message)]
+        pub fn has_role(&self, account: AccountId, role: Role) -> bool {
+            self.is_admin(account) || self.roles.contains((account, role))
+        }
+
+        fn is_admin(&self, account: AccountId) -> bool {
+            account == self.admin && !self.admin_renounced.get().unwrap_or_default()
+        }
+
+        fn is_guardian(&self, account: AccountId) -> bool {
+            self.guardian.get().flatten() == Some(account)
+        }
+
+        #[ink(message)]
+        pub fn get_guardian(&self) -> Option<AccountId> {
+            self.guardian.get().flatten()
+        }
+
+        // Propose a new guardian, or none, to the other members. Proposing counts as the
+        // proposer's vote. Returns the election id.
+        #[ink(message)]
+        pub fn propose_guardian(&mut self, candidate: Option<AccountId>) -> Result<u32, Error> {
+            let sender = self.env().caller();
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            let election_id = self.next_guardian_election_id.get().unwrap_or_default();
+            self.next_guardian_election_id.set(&(election_id + 1));
+            let election = GuardianElection {
+                candidate,
+                proposer: sender,
+                votes: 1,
+                expires_at: self.env().block_number().saturating_add(PROPOSAL_LIFETIME),
+                executed: false,
+            };
+            self.guardian_votes.insert((election_id, sender), &true);
+            self.env().emit_event(GuardianProposed {
+                election_id,
+                proposer: Some(sender),
+                candidate,
+            });
+            self.record_guardian_election(election_id, election);
+            Ok(election_id)
+        }
+
+        #[ink(message)]
+        pub fn vote_guardian(&mut self, election_id: u32) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            let mut election = self.guardian_elections.get(election_id).ok_or(Error::ProposalNotFound)?;
+            if election.executed {
+                return Err(Error::ProposalAlreadyExecuted);
+            }
+            if self.env().block_number() > election.expires_at {
+                return Err(Error::ProposalExpired);
+            }
+            if self.guardian_votes.contains((election_id, sender)) {
+                return Err(Error::AlreadyVoted);
+            }
+            election.votes += 1;
+            self.guardian_votes.insert((election_id, sender), &true);
+            self.env().emit_event(GuardianVoted {
+                election_id,
+                voter: Some(sender),
+                votes: election.votes,
+            });
+            self.record_guardian_election(election_id, election);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_guardian_election(&self, election_id: u32) -> Option<GuardianElection> {
+            self.guardian_elections.get(election_id)
+        }
+
+        // Stores the election, appointing its candidate once a majority of the members back it.
+        fn record_guardian_election(&mut self, election_id: u32, mut election: GuardianElection) {
+            if election.votes.saturating_mul(2) > self.member_count {
+                election.executed = true;
+                self.guardian.set(&election.candidate);
+                self.env().emit_event(GuardianChanged {
+                    election_id,
+                    guardian: election.candidate,
+                });
+            }
+            self.guardian_elections.insert(election_id, &election);
+        }
+
+        // Once half of a timed round has passed, anyone can issue on-chain reminders to the
+        // members who have not paid yet, once per round, and collect `poke_bounty` from the
+        // penalty pool for doing so.
+        #[ink(message)]
+        pub fn poke_defaulters(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_not_paused()?;
+            self.ensure_phase(FundPhase::ContributionOpen, Error::ChitFundHasFinished)?;
+            let round = self.current_round;
+            let midpoint = self.round_start.saturating_add(self.schedule.round_length() / 2);
+            if !self.schedule.has_deadline() || self.schedule_now() < midpoint {
+                return Err(Error::TooEarlyToPoke);
+            }
+            if self.last_poked_round == round {
+                return Err(Error::AlreadyPoked);
+            }
+            let defaulters = self.defaulters(round);
+            if defaulters.is_empty() {
+                return Err(Error::NoDefaulters);
+            }
+            let bounty = self.poke_bounty.min(self.penalty_pool);
+            self.last_poked_round = round;
+            self.penalty_pool -= bounty;
+            let deadline = self.round_deadline();
+            for account in &defaulters {
+                self.env().emit_event(ReminderIssued {
+                    account: Some(*account),
+                    round,
+                    deadline,
+                });
+            }
+            if bounty > 0 {
+                self.pay_out(sender, bounty)?;
+            }
+            self.env().emit_event(DefaultersPoked {
+                caller: Some(sender),
+                round,
+                reminders: defaulters.len() as u32,
+                bounty,
+            });
+            Ok(())
+        }
+
+        // Accept the fund's terms. The caller passes the hash of the document they agreed
+        // to, so acceptance fails if the terms have changed in the meantime.
+        #[ink(message)]
+        pub fn accept_terms(&mut self, terms_hash: Hash) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let current = self.terms_hash.ok_or(Error::NoTerms)?;
+            if terms_hash != current {
+                return Err(Error::TermsMismatch);
+            }
+            self.terms_accepted.insert(sender, &terms_hash);
+            self.env().emit_event(TermsAccepted {
+                account: Some(sender),
+                terms_hash,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_terms(&self) -> (Option<Hash>, Vec<u8>) {
+            (self.terms_hash, self.terms_uri.clone())
+        }
+
+        #[ink(message)]
+        pub fn has_accepted_terms(&self, account: AccountId) -> bool {
+            self.terms_hash.is_some() && self.terms_accepted.get(account) == self.terms_hash
+        }
+
+        // Propose new terms to the other members. Proposing counts as the proposer's vote.
+        // Returns the amendment id.
+        #[ink(message)]
+        pub fn propose_terms_amendment(&mut self, terms_hash: Hash, terms_uri: Vec<u8>) -> Result<u32, Error> {
+            let sender = self.env().caller();
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            let amendment_id = self.next_amendment_id;
+            self.next_amendment_id += 1;
+            let amendment = TermsAmendment {
+                terms_hash,
+                terms_uri,
+                proposer: sender,
+                votes: 1,
+                expires_at: self.env().block_number().saturating_add(PROPOSAL_LIFETIME),
+                executed: false,
+            };
+            self.amendment_votes.insert((amendment_id, sender), &true);
+            self.env().emit_event(TermsAmendmentProposed {
+                amendment_id,
+                proposer: Some(sender),
+                terms_hash,
+            });
+            self.record_amendment(amendment_id, amendment);
+            Ok(amendment_id)
+        }
+
+        #[ink(message)]
+        pub fn vote_terms_amendment(&mut self, amendment_id: u32) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            let mut amendment = self.terms_amendments.get(amendment_id).ok_or(Error::ProposalNotFound)?;
+            if amendment.executed {
+                return Err(Error::ProposalAlreadyExecuted);
+            }
+            if self.env().block_number() > amendment.expires_at {
+                return Err(Error::ProposalExpired);
+            }
+            if self.amendment_votes.contains((amendment_id, sender)) {
+                return Err(Error::AlreadyVoted);
+            }
+            amendment.votes += 1;
+            self.amendment_votes.insert((amendment_id, sender), &true);
+            self.env().emit_event(TermsAmendmentVoted {
+                amendment_id,
+                voter: Some(sender),
+                votes: amendment.votes,
+            });
+            self.record_amendment(amendment_id, amendment);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_terms_amendment(&self, amendment_id: u32) -> Option<TermsAmendment> {
+            self.terms_amendments.get(amendment_id)
+        }
+
+        // Stores the amendment, adopting its terms once a majority of the members back it.
+        fn record_amendment(&mut self, amendment_id: u32, mut amendment: TermsAmendment) {
+            if amendment.votes.saturating_mul(2) > self.member_count {
+                amendment.executed = true;
+                self.terms_hash = Some(amendment.terms_hash);
+                self.terms_uri = amendment.terms_uri.clone();
+                self.env().emit_event(TermsAmended {
+                    amendment_id,
+                    terms_hash: amendment.terms_hash,
+                });
+            }
+            self.terms_amendments.insert(amendment_id, &amendment);
+        }
+
+        // Bring a member who has missed rounds back into good standing by paying their
+        // arrears plus the late fee for every round missed. The arrears go into the pot and
+        // the member is eligible to win again. Only the member can cure their own default.
+        #[ink(message, payable)]
+        pub fn cure_default(&mut self) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            self.ensure_not_paused()?;
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            let missed = self.missed_rounds.get(sender).unwrap_or(0);
+            if missed == 0 {
+                return Err(Error::NotInDefault);
+            }
+            let arrears = self.arrears.get(sender).unwrap_or(0);
+            let penalty = self.late_fee().checked_mul(missed as Balance).ok_or(Error::ArithmeticOverflow)?;
+            let amount_due = arrears.checked_add(penalty).ok_or(Error::ArithmeticOverflow)?;
+            let transferred = self.env().transferred_value();
+            let overshoot = match self.contribution_token {
+                Some(_) if transferred != 0 => return Err(Error::NativeValueNotAccepted),
+                Some(_) => 0,
+                None => transferred.checked_sub(amount_due).ok_or(Error::IncorrectContributionAmount)?,
+            };
+            let pot = self.pot.checked_add(arrears).ok_or(Error::ArithmeticOverflow)?;
+            let penalty_pool = self.penalty_pool.checked_add(penalty).ok_or(Error::ArithmeticOverflow)?;
+            let contributed = self
+                .total_contributed
+                .get(sender)
+                .unwrap_or(0)
+                .checked_add(arrears)
+                .ok_or(Error::ArithmeticOverflow)?;
+            let penalties_paid = self
+                .penalties_paid
+                .get(sender)
+                .unwrap_or(0)
+                .checked_add(penalty)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.pot = pot;
+            self.penalty_pool = penalty_pool;
+            self.total_contributed.insert(sender, &contributed);
+            self.penalties_paid.insert(sender, &penalties_paid);
+            self.missed_rounds.remove(sender);
+            self.refresh_eligibility(sender);
+            self.arrears.remove(sender);
+            self.defaulted_at.remove(sender);
+            self.collect(sender, amount_due)?;
+            if overshoot > 0 {
+                self.env().transfer(sender, overshoot).map_err(|_| Error::TransferFailed)?;
+            }
+            self.env().emit_event(DefaultCured {
+                account: Some(sender),
+                missed_rounds: missed,
+                arrears,
+                penalty,
+            });
+            Ok(())
+        }
+
+        // Contributions the account has missed that nothing else has covered.
+        #[ink(message)]
+        pub fn arrears_of(&self, account: AccountId) -> Balance {
+            self.arrears.get(account).unwrap_or(0)
+        }
+
+        // What `cure_default` costs the account right now: arrears plus late fees.
+        #[ink(message)]
+        pub fn cure_cost(&self, account: AccountId) -> Balance {
+            let missed = self.missed_rounds.get(account).unwrap_or(0) as Balance;
+            self.arrears_of(account).saturating_add(self.late_fee().saturating_mul(missed))
+        }
+
+        // Tick of the schedule at which the grace period of a member liable to expulsion runs
+        // out, if they are.
+        #[ink(message)]
+        pub fn grace_period_ends(&self, account: AccountId) -> Option<u64> {
+            self.defaulted_at.get(account).map(|since| since.saturating_add(self.grace_period))
+        }
+
+        fn ensure_grace_period_over(&self, account: AccountId) -> Result<(), Error> {
+            match self.grace_period_ends(account) {
+                Some(ends) if self.schedule_now() < ends => Err(Error::GracePeriodActive),
+                _ => Ok(()),
+            }
+        }
+
+        // Ask to hand the caller's seat, with its contribution history, collateral and
+        // standing, to `new_account`. Only seats that have not won can be transferred. A new
+        // request replaces any earlier one and its votes.
+        #[ink(message)]
+        pub fn transfer_membership(&mut self, new_account: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            self.ensure_not_paused()?;
+            self.ensure_transferable(sender, new_account)?;
+            let id = self.last_seat_transfer_id.get().unwrap_or_default() + 1;
+            self.last_seat_transfer_id.set(&id);
+            self.seat_transfer_ids.insert(sender, &id);
+            self.seat_transfers.insert(
+                sender,
+                &SeatTransfer {
+                    new_account,
+                    votes: 0,
+                    electorate: self.member_count - 1,
+                },
+            );
+            self.env().emit_event(SeatTransferRequested {
+                account: Some(sender),
+                new_account: Some(new_account),
+            });
+            Ok(())
+        }
+
+        // Let the requested transfer of the account's seat go through.
+        #[ink(message)]
+        pub fn approve_membership_transfer(&mut self, account: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanApproveTransfer);
+            }
+            self.ensure_active()?;
+            let transfer = self.seat_transfers.get(account).ok_or(Error::NoSeatTransfer)?;
+            self.move_seat(account, transfer.new_account, Some(sender))
+        }
+
+        // Vote for the requested transfer of the account's seat. It goes through as soon as a
+        // strict majority of the other members have voted for it.
+        #[ink(message)]
+        pub fn vote_membership_transfer(&mut self, account: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            if sender == account {
+                return Err(Error::CannotVoteOnOwnTransfer);
+            }
+            let mut transfer = self.seat_transfers.get(account).ok_or(Error::NoSeatTransfer)?;
+            let id = self.seat_transfer_ids.get(account).unwrap_or(0);
+            if self.seat_transfer_votes.get((account, sender)) == Some(id) {
+                return Err(Error::AlreadyVoted);
+            }
+            transfer.votes += 1;
+            self.seat_transfer_votes.insert((account, sender), &id);
+            self.env().emit_event(SeatTransferVoted {
+                account: Some(account),
+                voter: Some(sender),
+                votes: transfer.votes,
+            });
+            if transfer.votes.saturating_mul(2) > transfer.electorate {
+                return self.move_seat(account, transfer.new_account, None);
+            }
+            self.seat_transfers.insert(account, &transfer);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_seat_transfer(&self, account: AccountId) -> Option<SeatTransfer> {
+            self.seat_transfers.get(account)
+        }
+
+        // Ask the other members for an advance of `amount` against the caller's prize, at
+        // most `max_advance_bps` of `scheduled_prize`. Only members who have not won can
+        // ask, one advance at a time. A new request replaces an unpaid one and its votes.
+        #[ink(message)]
+        pub fn request_advance(&mut self, amount: Balance) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            self.ensure_not_paused()?;
+            let max_advance_bps = self.max_advance_bps.get().unwrap_or_default();
+            if max_advance_bps == 0 {
+                return Err(Error::AdvancesDisabled);
+            }
+            let member = self.members.get(sender).ok_or(Error::NotParticipant)?;
+            if member.wins > 0 {
+                return Err(Error::AlreadyWon);
+            }
+            if self.has_outstanding_advance(sender) {
+                return Err(Error::AdvanceOutstanding);
+            }
+            let limit = self.scheduled_prize().saturating_mul(max_advance_bps as Balance) / MAX_BPS as Balance;
+            if amount == 0 || amount > limit {
+                return Err(Error::InvalidAdvanceAmount);
+            }
+            let id = self.next_advance_id.get().unwrap_or_default();
+            self.next_advance_id.set(&(id + 1));
+            self.advances.insert(
+                sender,
+                &Advance {
+                    id,
+                    amount,
+                    votes: 0,
+                    electorate: self.member_count - 1,
+                    paid: false,
+                },
+            );
+            self.env().emit_event(AdvanceRequested {
+                account: Some(sender),
+                amount,
+            });
+            Ok(())
+        }
+
+        // Vote for the account's requested advance. It is paid as soon as a strict majority
+        // of the other members have voted for it, provided the commission can cover it.
+        #[ink(message)]
+        pub fn vote_advance(&mut self, account: AccountId) -> Result<(), Error> {
+            self.non_reentrant(|fund| fund.record_advance_vote(account))
+        }
+
+        fn record_advance_vote(&mut self, account: AccountId) -> Result<(), Error> {
+            let sender = self.env().caller();
+            self.ensure_active()?;
+            self.ensure_not_paused()?;
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            if sender == account {
+                return Err(Error::CannotVoteOnOwnAdvance);
+            }
+            let mut advance = self.advances.get(account).filter(|advance| !advance.paid).ok_or(Error::NoAdvanceRequest)?;
+            if self.advance_ballots.get((account, sender)) == Some(advance.id) {
+                return Err(Error::AlreadyVoted);
+            }
+            advance.votes += 1;
+            let approved = advance.votes.saturating_mul(2) > advance.electorate;
+            if approved && self.commission_balance < advance.amount {
+                return Err(Error::InsufficientAdvanceFunds);
+            }
+            self.advance_ballots.insert((account, sender), &advance.id);
+            self.env().emit_event(AdvanceVoted {
+                account: Some(account),
+                voter: Some(sender),
+                votes: advance.votes,
+            });
+            if approved {
+                self.commission_balance -= advance.amount;
+                advance.paid = true;
+                self.pay_out(account, advance.amount)?;
+                self.env().emit_event(AdvancePaid {
+                    account: Some(account),
+                    amount: advance.amount,
+                });
+            }
+            self.advances.insert(account, &advance);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_advance(&self, account: AccountId) -> Option<Advance> {
+            self.advances.get(account)
+        }
+
+        fn has_outstanding_advance(&self, account: AccountId) -> bool {
+            self.advances.get(account).is_some_and(|advance| advance.paid)
+        }
+
+        // Deducts what the winner owes on an advance from a prize of `amount`, returning it
+        // to the commission. Returns the deduction.
+        fn repay_advance(&mut self, winner: AccountId, round: u32, amount: Balance) -> Result<Balance, Error> {
+            let Some(mut advance) = self.advances.get(winner).filter(|advance| advance.paid) else {
+                return Ok(0);
+            };
+            let repaid = advance.amount.min(amount);
+            advance.amount -= repaid;
+            if advance.amount == 0 {
+                self.advances.remove(winner);
+            } else {
+                self.advances.insert(winner, &advance);
+            }
+            self.commission_balance = self
+                .commission_balance
+                .checked_add(repaid)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.env().emit_event(AdvanceRepaid {
+                account: (!self.is_sealed()).then_some(winner),
+                round,
+                amount: repaid,
+                outstanding: advance.amount,
+            });
+            Ok(repaid)
+        }
+
+        fn ensure_transferable(&self, account: AccountId, new_account: AccountId) -> Result<(), Error> {
+            if !self.is_member(account) {
+                return Err(Error::NotParticipant);
+            }
+            self.ensure_migrated()?;
+            self.ensure_not_pending_winner(account)?;
+            // Prizes stay booked to the account that won them, so any win ties the seat.
+            if self.members.get(account).is_some_and(|member| member.wins > 0) {
+                return Err(Error::SeatAlreadyWon);
+            }
+            if self.has_outstanding_advance(account) {
+                return Err(Error::AdvanceOutstanding);
+            }
+            if self.is_member(new_account) {
+                return Err(Error::AlreadyJoined);
+            }
+            if new_account == self.admin && !self.admin_participates {
+                return Err(Error::AdminCannotParticipate);
+            }
+            if self.transferred_seats.contains(new_account) {
+                return Err(Error::SeatTransferred);
+            }
+            if self.terms_hash.is_some() && self.terms_accepted.get(new_account) != self.terms_hash {
+                return Err(Error::TermsNotAccepted);
+            }
+            Ok(())
+        }
+
+        // Hands the seat and everything booked to it from `from` to `to`. The payout address
+        // and accepted terms stay personal and are not carried over.
+        fn move_seat(&mut self, from: AccountId, to: AccountId, approved_by: Option<AccountId>) -> Result<(), Error> {
+            self.ensure_transferable(from, to)?;
+            let member = self.members.take(from).ok_or(Error::NotParticipant)?;
+            self.member_index.insert(member.index, &to);
+            self.members.insert(to, &member);
+            for round in 0..=self.current_round {
+                move_entry(&mut self.deposits, (from, round), (to, round));
+                move_entry(&mut self.partial_deposits, (from, round), (to, round));
+                move_entry(&mut self.commitments, (from, round), (to, round));
+                move_entry(&mut self.revealed, (from, round), (to, round));
+                move_entry(&mut self.bids, (from, round), (to, round));
+                // Bids are kept under the round that has just ended, not the open one.
+                if let Some((bidder, amount)) = self.lowest_bids.get(round) {
+                    if bidder == from {
+                        self.lowest_bids.insert(round, &(to, amount));
+                    }
+                }
+            }
+            if let Some(slot) = self.rotation.iter_mut().find(|account| **account == from) {
+                *slot = to;
+            }
+            move_entry(&mut self.deposit_streaks, from, to);
+            move_entry(&mut self.total_contributed, from, to);
+            move_entry(&mut self.dividends, from, to);
+            move_entry(&mut self.dividends_earned, from, to);
+            move_entry(&mut self.rounds_defaulted, from, to);
+            move_entry(&mut self.penalties_paid, from, to);
+            move_entry(&mut self.missed_rounds, from, to);
+            move_entry(&mut self.collateral, from, to);
+            move_entry(&mut self.arrears, from, to);
+            move_entry(&mut self.defaulted_at, from, to);
+            self.payout_addresses.remove(from);
+            self.seat_transfers.remove(from);
+            self.seat_transfer_ids.remove(from);
+            self.transferred_seats.insert(from, &to);
+            self.env().emit_event(MembershipTransferred {
+                from: Some(from),
+                to: Some(to),
+                approved_by,
+            });
+            Ok(())
+        }
+
+        // Human-readable explanation of an error code, for frontends that only have the
+        // code, e.g. from a failed dry run. None for codes that are not in use.
+        #[ink(message)]
+        pub fn describe_error(&self, code: u8) -> Option<String> {
+            Error::from_code(code).map(|error| String::from(error.description()))
+        }
+
+        // After a pause or a stalled round, give the open round a fresh deadline from now and
+        // let members pay for every round from `since_round` on with `deposit_many`, without
+        // late fees, until the round closes.
+        #[ink(message)]
+        pub fn catch_up_round(&mut self, since_round: u32) -> Result<(), Error> {
+            let sender = self.env().caller();
+            if !self.has_role(sender, Role::Organizer) {
+                return Err(Error::OnlyAdminCanCatchUp);
+            }
+            self.ensure_active()?;
+            self.ensure_started()?;
+            self.ensure_phase(FundPhase::ContributionOpen, Error::ChitFundHasFinished)?;
+            if since_round == 0 || since_round > self.current_round {
+                return Err(Error::RoundNotPayable);
+            }
+            self.round_start = self.schedule_now();
+            self.catch_up_since = Some(since_round);
+            self.env().emit_event(CatchUpScheduled {
+                admin: Some(sender),
+                since_round,
+                round: self.current_round,
+                deadline: self.round_deadline(),
+            });
+            Ok(())
+        }
+
+        // Pay several outstanding rounds at once: the open round and, while a catch-up is
+        // scheduled, missed rounds back to its first round. Each is booked to its own round.
+        // A missed round paid this way clears the arrears it left, tops the collateral it
+        // used back up and no longer counts against the member.
+        #[ink(message, payable)]
+        pub fn deposit_many(&mut self, rounds: Vec<u32>) -> Result<(), Error> {
+            let sender = self.env().caller();
+            let mut rounds = rounds;
+            self.ensure_active()?;
+            self.ensure_not_paused()?;
+            self.ensure_started()?;
+            if !self.is_member(sender) {
+                return Err(Error::NotParticipant);
+            }
+            self.ensure_phase(FundPhase::ContributionOpen, Error::DepositsClosed)?;
+            rounds.sort_unstable();
+            rounds.dedup();
+            let current = self.current_round;
+            let earliest = self.catch_up_since.unwrap_or(current);
+            if rounds.is_empty() || rounds.iter().any(|round| *round < earliest || *round > current) {
+                return Err(Error::RoundNotPayable);
+            }
+            if rounds.iter().any(|round| self.deposits.contains((sender, *round))) {
+                return Err(Error::AlreadyDeposited);
+            }
+            // The open round is due as with `deposit`, less any instalments already made.
+            let contribution = self.contribution_of(sender)?;
+            let collateral_required = self.collateral_required.saturating_mul(self.tickets_of(sender) as Balance);
+            let late = self.is_round_expired();
+            let pays_current = rounds.last() == Some(&current);
+            if pays_current && late && self.late_fee_bps == 0 {
+                return Err(Error::RoundExpired);
+            }
+            let penalty = if pays_current && late { self.late_fee() } else { 0 };
+            let already_paid = if pays_current { self.partial_deposits.get((sender, current)).unwrap_or(0) } else { 0 };
+            let amount_due = contribution
+                .checked_mul(rounds.len() as Balance)
+                .and_then(|amount| amount.checked_add(penalty))
+                .ok_or(Error::ArithmeticOverflow)?
+                .saturating_sub(already_paid);
+            let transferred = self.env().transferred_value();
+            let overshoot = match self.contribution_token {
+                Some(_) if transferred != 0 => return Err(Error::NativeValueNotAccepted),
+                Some(_) => 0,
+                None => transferred.checked_sub(amount_due).ok_or(Error::IncorrectContributionAmount)?,
+            };
+
+            let mut pot = self.pot;
+            let mut collateral = self.collateral.get(sender).unwrap_or(0);
+            let mut arrears = self.arrears.get(sender).unwrap_or(0);
+            let mut missed = self.missed_rounds.get(sender).unwrap_or(0);
+            for round in &rounds {
+                let mut to_pot = contribution;
+                if *round < current {
+                    // Whatever of the missed round the arrears do not account for was covered
+                    // by collateral or prize installments; the collateral is restored first.
+                    let cleared = arrears.min(contribution);
+                    arrears -= cleared;
+                    let restored = (contribution - cleared).min(collateral_required.saturating_sub(collateral));
+                    collateral += restored;
+                    to_pot -= restored;
+                    missed = missed.saturating_sub(1);
+                }
+                pot = pot.checked_add(to_pot).ok_or(Error::ArithmeticOverflow)?;
+            }
+            let contributed = self
+                .total_contributed
+                .get(sender)
+                .unwrap_or(0)
+                .checked_add(contribution * rounds.len() as Balance)
+                .ok_or(Error::ArithmeticOverflow)?;
+            self.pot = pot;
+            self.penalty_pool = self.penalty_pool.checked_add(penalty).ok_or(Error::ArithmeticOverflow)?;
+            self.update_round_tally(|tally| tally.penalties = tally.penalties.saturating_add(penalty));
+            if penalty > 0 {
+                let penalties_paid = self.penalties_paid.get(sender).unwrap_or(0).saturating_add(penalty);
+                self.penalties_paid.insert(sender, &penalties_paid);
+            }
+            if collateral > 0 {
+                self.collateral.insert(sender, &collateral);
+            }
+            self.arrears.insert(sender, &arrears);
+            self.missed_rounds.insert(sender, &missed);
+            self.refresh_eligibility(sender);
+            if self.max_missed_rounds == 0 || missed < self.max_missed_rounds {
+                self.defaulted_at.remove(sender);
+            }
+            if pays_current {
+                let streak = if late { 0 } else { self.deposit_streaks.get(sender).unwrap_or(0) + 1 };
+                self.deposit_streaks.insert(sender, &streak);
+                self.partial_deposits.remove((sender, current));
+            }
+            for round in &rounds {
+                self.deposits.insert((sender, *round), &contribution);
+            }
+            self.total_contributed.insert(sender, &contributed);
+            self.collect(sender, amount_due)?;
+            if overshoot > 0 {
+                self.env().transfer(sender, overshoot).map_err(|_| Error::TransferFailed)?;
+            }
+            for round in &rounds {
+                self.issue_receipt(sender, *round, contribution);
+            }
+            self.env().emit_event(RoundsPaid {
+                account: Some(sender),
+                rounds,
+                amount: amount_due,
+            });
+            Ok(())
+        }
+
+        // Everything the account has paid into and received from the fund, for organizers
+        // settling up with members at the end of a cycle.
+        #[ink(message)]
+        pub fn get_member_statement(&self, account: AccountId) -> MemberStatement {
+            let won = self
+                .get_all_winners()
+                .into_iter()
+                .find(|(_, winner)| *winner == account)
+                .and_then(|(round, _)| self.round_history.get(round));
+            MemberStatement {
+                account,
+                is_member: self.is_member(account),
+                total_contributed: self.total_contributed.get(account).unwrap_or(0),
+                penalties_paid: self.penalties_paid.get(account).unwrap_or(0),
+                dividends_earned: self.dividends_earned.get(account).unwrap_or(0),
+                dividends_pending: self.dividends.get(account).unwrap_or(0),
+                won_round: won.as_ref().map(|record| record.round),
+                prize: won.as_ref().map_or(0, |record| record.prize),
+                prize_after_commission: won.as_ref().map_or(0, |record| record.prize - record.commission),
+                collateral_held: self.collateral.get(account).unwrap_or(0),
+                rounds_defaulted: self.rounds_defaulted.get(account).unwrap_or(0),
+                rounds_outstanding: self.missed_rounds.get(account).unwrap_or(0),
+                arrears: self.arrears.get(account).unwrap_or(0),
+                refund_received: self.refunds_claimed.get(account).unwrap_or(0),
+            }
+        }
+
+        // Tells the registry, if any, that the account was expelled so the organizer's later
+        // funds turn it away. A registry that cannot be reached does not hold up the
+        // expulsion; the organizer can still blacklist the account there by hand.
+        fn report_expulsion(&self, account: AccountId) {
+            if let Some(registry) = self.blacklist_registry {
+                let _ = registry::report_default(registry, account);
+            }
+        }
+
+        // Once the fund has completed, share the penalty pool (late fees and forfeited
+        // collateral) between the members who never paid late or missed a round, in
+        // proportion to what they contributed. Each share is credited as a dividend. If no
+        // member qualifies, every member shares in it. Anyone can run it, once.
+        #[ink(message)]
+        pub fn final_distribution(&mut self) -> Result<(), Error> {
+            if self.phase != FundPhase::Completed {
+                return Err(Error::FundNotCompleted);
+            }
+            if self.penalty_pool_distributed {
+                return Err(Error::PenaltyPoolAlreadyDistributed);
+            }
+            let members = self.participant_list();
+            let perfect: Vec<AccountId> = members
+                .iter()
+                .copied()
+                .filter(|account| {
+                    self.rounds_defaulted.get(account).unwrap_or(0) == 0
+                        && self.penalties_paid.get(account).unwrap_or(0) == 0
+                })
+                .collect();
+            let recipients = if perfect.is_empty() { members } else { perfect };
+            let mut basis: Balance = 0;
+            for account in &recipients {
+                basis = basis
+                    .checked_add(self.total_contributed.get(account).unwrap_or(0))
+                    .ok_or(Error::ArithmeticOverflow)?;
+            }
+            let pool = self.penalty_pool;
+            let mut shared: Balance = 0;
+            for account in &recipients {
+                let contributed = self.total_contributed.get(account).unwrap_or(0);
+                // Multiply first for precision; fall back to dividing first on overflow.
+                let share = match contributed.checked_mul(pool) {
+                    Some(product) => product.checked_div(basis),
+                    None => contributed.checked_div(basis).map(|part| part * pool),
+                }
+                .unwrap_or(0);
+                if share > 0 {
+                    self.credit_dividend(*account, self.current_round, share)?;
+                    shared += share;
+                }
+            }
+            let remainder = pool - shared;
+            if remainder > 0 && shared > 0 {
+                if !self.remainder_to_organizer(remainder)? {
+                    self.credit_dividend(recipients[0], self.current_round, remainder)?;
+                }
+                shared += remainder;
+            }
+            self.penalty_pool = pool - shared;
+            self.penalty_pool_distributed = true;
+            self.env().emit_event(PenaltyPoolDistributed {
+                amount: pool,
+                recipients: recipients.len() as u32,
+            });
+            Ok(())
+        }
+
+        // Remove a finished fund once nobody is owed anything, sending the dust left in it to
+        // the treasury. Everything members can still claim has to be claimed first, so
+        // abandoned funds do not keep tiny balances stuck forever.
+        #[ink(message)]
+        pub fn terminate_contract(&mut self) -> Result<(), Error> {
+            if !self.has_role(self.env().caller(), Role::Treasurer) {
+                return Err(Error::OnlyAdminCanTerminate);
+            }
+            if !matches!(self.phase, FundPhase::Completed | FundPhase::Cancelled) {
+                return Err(Error::FundStillRunning);
+            }
+            if self.has_outstanding_claims() {
+                return Err(Error::ClaimsOutstanding);
+            }
+            let treasury = self.treasury.unwrap_or(self.admin);
+            let amount = self.balance()?;
+            if self.contribution_token.is_some() && amount > 0 {
+                self.pay_out(treasury, amount)?;
+            }
+            self.held = 0;
+            self.env().emit_event(ContractTerminated {
+                treasury: Some(treasury),
+                amount,
+            });
+            self.env().terminate_contract(treasury)
+        }
+
+        // Whether any member, or the organizer, can still claim something from the fund. A
+        // sealed prize counts until it is claimed or forfeited, even past its claim period,
+        // as forfeiting it credits the members.
+        fn has_outstanding_claims(&self) -> bool {
+            if self.commission_balance > 0 || (self.penalty_pool > 0 && !self.penalty_pool_distributed) {
+                return true;
+            }
+            if (1..=self.current_round).any(|round| self.sealed_prizes.contains(round)) {
+                return true;
+            }
+            self.participant_list().into_iter().any(|member| {
+                let unpaid_installments = self
+                    .payout_plans
+                    .get(member)
+                    .map_or(0, |plan| plan.amount - plan.claimed - plan.slashed);
+                self.dividends.get(member).unwrap_or(0) > 0
+                    || self.unclaimed_prizes.get(member).unwrap_or(0) > 0
+                    || self.collateral.get(member).unwrap_or(0) > 0
+                    || self.refund_due(member) > 0
+                    || unpaid_installments > 0
+            })
+        }
+    }
+
+    // Moves whatever is stored under `from` to `to`.
+    fn move_entry<K, V, S>(map: &mut Mapping<K, V, S>, from: K, to: K)
+    where
+        K: scale::EncodeLike,
+        V: ink::storage::traits::Packed + scale::EncodeLike,
+        S: ink::storage::traits::StorageKey,
+    {
+        if let Some(value) = map.take(from) {
+            map.insert(to, &value);
+        }
+    }
+}
+    
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::{Deadline, ScheduleSource, EXPECTED_BLOCK_TIME};
+    use crate::my_contract::{AdminAction, BonusSplit, ChitFund, DrawRecord, RedrawPolicy, ContractInfo, DrawPreview, Error, FundSummary, PayoutPreference, PendingPayout, FundConfig, FundInfo, FundMode, FundPhase, MemberInfo, PayoutSchedule, RemainderPolicy, Role, RandomnessSource, Rounding, RoundRecord, RoundSnapshot, RoundSummary, SeatTransfer, MAX_BPS, PROPOSAL_LIFETIME, RATE_SCALE, REVEAL_PERIOD, STORAGE_VERSION, VERSION};
+    use ink::primitives::{AccountId, Hash};
+    use ink::env::hash::{Blake2x256, HashOutput};
+    use ink::env::Environment;
+    use ink::env::{test, DefaultEnvironment};
+    use ink::reflect::ContractEventBase;
+    use ink::prelude::string::String;
+    use scale::{Decode, Encode};
+
+    type Event = <ChitFund as ContractEventBase>::Type;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+
+    // Starts the fund as its admin, leaving the caller as it was.
+    fn start_fund(chit_fund: &mut ChitFund) {
+        let caller = ink::env::caller::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(chit_fund.get_admin());
+        assert_eq!(chit_fund.start_fund(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(caller);
+    }
+
+    // Helper function to create a random account ID for testing purposes.
+    fn random_account_id() -> AccountId {
+        AccountId::from([0x42; 32])
+    }
+
+    // Makes the next calls come from `account` with `value` attached.
+    fn call_as(account: AccountId, value: Balance) {
+        test::set_caller::<DefaultEnvironment>(account);
+        test::set_value_transferred::<DefaultEnvironment>(value);
+    }
+
+    // A fund administered by alice that `members` have joined, with enough balance for
+    // the payouts the off-chain environment does not credit it for. Alice is left as the
+    // caller.
+    fn fund_with_members(max_participants: u32, config: FundConfig, members: &[AccountId]) -> ChitFund {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        call_as(accounts.alice, 0);
+        let mut chit_fund = ChitFund::with_config(accounts.alice, max_participants, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000_000);
+        for member in members {
+            call_as(*member, 0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        call_as(accounts.alice, 0);
+        chit_fund
+    }
+
+    // Every member in `members` pays the open round, leaving the admin as the caller.
+    fn pay_round(chit_fund: &mut ChitFund, members: &[AccountId]) {
+        for member in members {
+            call_as(*member, chit_fund.monthly_contribution);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        call_as(chit_fund.get_admin(), 0);
+    }
+
+    fn advance_blocks(blocks: u32) {
+        for _ in 0..blocks {
+            test::advance_block::<DefaultEnvironment>();
+        }
+    }
+
+    #[ink::test]
+    fn test_new() {
+        let admin = random_account_id();
+        let max_participants = 5;
+        let monthly_contribution = 100;
+        let chit_fund = ChitFund::new(admin, max_participants, monthly_contribution);
+
+        assert_eq!(chit_fund.admin, admin);
+        assert_eq!(chit_fund.max_participants, max_participants);
         assert_eq!(chit_fund.monthly_contribution, monthly_contribution);
         assert_eq!(chit_fund.current_round, 1);
         assert_eq!(chit_fund.pot, 0);
         assert_eq!(chit_fund.total_amount, 0);
-        assert_eq!(chit_fund.participants.len(), 0);
-        assert_eq!(chit_fund.used_indexes.len(), 0);
-        assert_eq!(chit_fund.finished, false);
+        assert_eq!(chit_fund.member_count, 0);
+        assert_eq!(chit_fund.winner_count, 0);
+        assert_eq!(chit_fund.phase, FundPhase::Recruiting);
+        assert_ne!(chit_fund.phase, FundPhase::Cancelled);
+    }
+
+    #[ink::test]
+    fn test_emergency_withdraw_when_cancelled() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+
+        let contract = test::callee::<DefaultEnvironment>();
+        test::set_account_balance::<DefaultEnvironment>(contract, 500);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap();
+
+        chit_fund.phase = FundPhase::Cancelled;
+        assert_eq!(chit_fund.emergency_withdraw(accounts.eve), Ok(()));
+
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap();
+        assert_eq!(after - before, 500);
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(contract).unwrap(), 0);
+        assert_eq!(test::recorded_events().count(), 1);
+    }
+
+    #[ink::test]
+    fn test_emergency_withdraw_rejected_on_active_fund() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+
+        assert_eq!(chit_fund.emergency_withdraw(accounts.eve), Err(Error::FundNotCancelled));
+
+        // Only the admin may withdraw, even from a cancelled fund. A treasurer manages
+        // commission, not the members' unclaimed refunds.
+        assert_eq!(chit_fund.grant_role(accounts.charlie, Role::Treasurer), Ok(()));
+        chit_fund.phase = FundPhase::Cancelled;
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.emergency_withdraw(accounts.bob), Err(Error::OnlyAdminCanWithdraw));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.emergency_withdraw(accounts.charlie), Err(Error::OnlyAdminCanWithdraw));
+    }
+
+    #[ink::test]
+    fn test_deposit_streak() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+
+        // Bob pays three rounds straight.
+        for round in 1..=3 {
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+            assert_eq!(chit_fund.deposit_streak(accounts.bob), round);
+
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        }
+        assert_eq!(chit_fund.deposit_streak(accounts.bob), 3);
+
+        // Then misses one.
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.deposit_streak(accounts.bob), 0);
+    }
+
+    #[ink::test]
+    fn test_set_monthly_contribution_between_cycles() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+
+        assert_eq!(chit_fund.set_monthly_contribution(0), Err(Error::InvalidContributionAmount));
+        assert_eq!(chit_fund.set_monthly_contribution(120), Ok(()));
+        assert_eq!(chit_fund.monthly_contribution, 120);
+    }
+
+    #[ink::test]
+    fn test_set_monthly_contribution_rejected_mid_cycle() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        start_fund(&mut chit_fund);
+
+        assert_eq!(chit_fund.set_monthly_contribution(120), Err(Error::CannotChangeMidCycle));
+        assert_eq!(chit_fund.monthly_contribution, 100);
+    }
+
+    #[ink::test]
+    fn test_set_monthly_contribution_locked_once_members_paid_in() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { collateral: 50, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        assert_eq!(chit_fund.set_monthly_contribution(120), Ok(()));
+
+        // Bob's collateral was posted against 120, so the amount is fixed until the fund runs.
+        call_as(accounts.bob, 50);
+        assert_eq!(chit_fund.join(), Ok(()));
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.set_monthly_contribution(150), Err(Error::ParametersLocked));
+        assert_eq!(chit_fund.monthly_contribution, 120);
+    }
+
+    #[ink::test]
+    fn test_set_monthly_contribution_goes_through_multisig() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig {
+            co_admins: vec![accounts.alice, accounts.bob],
+            approval_threshold: 2,
+            ..Default::default()
+        };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        chit_fund.phase = FundPhase::DrawPending;
+        assert_eq!(chit_fund.set_monthly_contribution(120), Err(Error::RequiresMultisig));
+
+        assert_eq!(chit_fund.propose_action(AdminAction::SetMonthlyContribution(120)), Ok(0));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.approve_action(0), Ok(()));
+        assert_eq!(chit_fund.execute_action(0), Ok(()));
+        assert_eq!(chit_fund.monthly_contribution, 120);
+    }
+
+    #[ink::test]
+    fn test_set_monthly_contribution_rejected_once_cancelled() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+
+        assert_eq!(chit_fund.set_monthly_contribution(120), Err(Error::FundCancelled));
+        assert_eq!(chit_fund.monthly_contribution, 100);
+    }
+
+    #[ink::test]
+    fn test_set_monthly_contribution_rejected_for_non_admin() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.set_monthly_contribution(120), Err(Error::OnlyAdminCanChangeContribution));
+    }
+
+    #[ink::test]
+    fn test_full_cycle_draw_payout() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let contract = test::callee::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 2, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        for _ in 0..2 {
+            for member in [accounts.bob, accounts.charlie] {
+                test::set_caller::<DefaultEnvironment>(member);
+                test::set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_account_balance::<DefaultEnvironment>(contract, 200);
+
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.pot, 0);
+            assert_eq!(chit_fund.total_amount, 200);
+
+            let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap()
+                + test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+            assert_eq!(chit_fund.draw(), Ok(()));
+            let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap()
+                + test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+            assert_eq!(after - before, 200);
+            assert_eq!(chit_fund.total_amount, 0);
+
+            if chit_fund.phase != FundPhase::Completed {
+                assert_eq!(chit_fund.begin_cycle(), Ok(()));
+                assert_eq!(chit_fund.total_amount, 0);
+            }
+            test::advance_block::<DefaultEnvironment>();
+        }
+    }
+
+    #[ink::test]
+    fn test_my_status() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.my_status(), (false, false, 0));
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.my_status(), (true, false, 0));
+
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.my_status(), (true, true, 100));
+    }
+
+    #[ink::test]
+    fn test_round_summary_event() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+
+        let summary = test::recorded_events()
+            .filter_map(|event| match Event::decode(&mut &event.data[..]) {
+                Ok(Event::RoundSummary(summary)) => Some(summary),
+                _ => None,
+            })
+            .last()
+            .unwrap();
+        let RoundSummary { round, total_collected, participant_count, depositors } = summary;
+        assert_eq!(round, 1);
+        assert_eq!(total_collected, 200);
+        assert_eq!(participant_count, 3);
+        assert_eq!(depositors, 2);
+    }
+
+    #[ink::test]
+    fn test_join_on_behalf() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+
+        assert_eq!(chit_fund.join_on_behalf(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.get_participants(), vec![accounts.bob]);
+        assert_eq!(test::recorded_events().count(), 1);
+
+        // Registering the same member twice is rejected like a second join.
+        assert_eq!(chit_fund.join_on_behalf(accounts.bob), Err(Error::AlreadyJoined));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Err(Error::AlreadyJoined));
+    }
+
+    #[ink::test]
+    fn test_join_on_behalf_rejected_for_non_admin() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join_on_behalf(accounts.charlie), Err(Error::OnlyAdminCanRegister));
+        assert!(chit_fund.get_participants().is_empty());
+    }
+
+    #[ink::test]
+    fn test_admin_register_members() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 4, 100);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.admin_register_members(vec![accounts.bob]), Err(Error::OnlyAdminCanRegister));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.admin_register_members(vec![accounts.bob, accounts.charlie]), Ok(()));
+        assert_eq!(chit_fund.get_participants(), vec![accounts.bob, accounts.charlie]);
+        assert_eq!(test::recorded_events().count(), 2);
+
+        assert_eq!(chit_fund.admin_register_members(vec![accounts.django, accounts.django]), Err(Error::AlreadyJoined));
+        assert_eq!(chit_fund.admin_register_members(vec![accounts.bob]), Err(Error::AlreadyJoined));
+        assert_eq!(
+            chit_fund.admin_register_members(vec![accounts.django, accounts.eve, accounts.frank]),
+            Err(Error::ParticipantsAlreadyFull)
+        );
+        // A rejected batch registers none of its accounts.
+        assert_eq!(chit_fund.get_participants(), vec![accounts.bob, accounts.charlie]);
+    }
+
+    #[ink::test]
+    fn test_select_winner_with_fixed_seeds() {
+        let bob = AccountId::from([0x01; 32]);
+        let charlie = AccountId::from([0x02; 32]);
+        let django = AccountId::from([0x03; 32]);
+        let mut chit_fund = ChitFund::new(random_account_id(), 3, 100);
+        assert_eq!(chit_fund.select_winner(7), None);
+        for member in [bob, charlie, django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+
+        assert_eq!(chit_fund.select_winner(0), Some(bob));
+        assert_eq!(chit_fund.select_winner(4), Some(charlie));
+        assert_eq!(chit_fund.select_winner(8), Some(django));
+
+        // Past winners are skipped, wrapping around the participant list. The draw reads
+        // eligibility from the bitmap, so a win is booked in both places.
+        let mark_won = |chit_fund: &mut ChitFund, account: AccountId, index: u32| {
+            chit_fund.members.insert(account, &MemberInfo { index, tickets: 1, wins: 1, has_won: true });
+            let word = chit_fund.eligibility.get(0).unwrap_or(0);
+            chit_fund.eligibility.insert(0, &(word & !(1 << index)));
+        };
+        mark_won(&mut chit_fund, charlie, 1);
+        assert_eq!(chit_fund.select_winner(4), Some(django));
+        mark_won(&mut chit_fund, django, 2);
+        assert_eq!(chit_fund.select_winner(5), Some(bob));
+        mark_won(&mut chit_fund, bob, 0);
+        assert_eq!(chit_fund.select_winner(5), None);
+    }
+
+    #[ink::test]
+    fn test_deposit_accumulates_instalments() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        assert_eq!(chit_fund.deposit(), Err(Error::IncorrectContributionAmount));
+        test::set_value_transferred::<DefaultEnvironment>(40);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.paid_towards_round(accounts.bob), 80);
+        assert!(!chit_fund.has_paid(accounts.bob, 1));
+        assert_eq!(chit_fund.pot, 0);
+
+        // The instalment that completes the contribution has its overshoot sent back.
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(after - before, 30);
+        assert_eq!(chit_fund.deposits.get((accounts.bob, 1)), Some(100));
+        assert_eq!(chit_fund.paid_towards_round(accounts.bob), 0);
+        assert_eq!(chit_fund.deposit(), Err(Error::AlreadyDeposited));
+        assert_eq!(chit_fund.pot, 100);
+    }
+
+    #[ink::test]
+    fn test_has_paid_per_round() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        assert!(chit_fund.has_paid(accounts.bob, 1));
+        assert!(!chit_fund.has_paid(accounts.bob, 2));
+        assert!(!chit_fund.has_paid(accounts.charlie, 1));
+        assert!(chit_fund.has_paid(accounts.charlie, 2));
+    }
+
+    #[ink::test]
+    fn test_draw_blocked_until_all_contributed() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.defaulters(1), vec![accounts.charlie]);
+        assert_eq!(chit_fund.draw(), Err(Error::ContributionsIncomplete));
+        assert_eq!(chit_fund.total_amount, 100);
+    }
+
+    #[ink::test]
+    fn test_commit_reveal_draw() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 200);
+        let members = [(accounts.bob, Hash::from([0x01; 32])), (accounts.charlie, Hash::from([0x02; 32]))];
+        for (member, _) in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for (member, secret) in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+            let commitment = ChitFund::commitment_of(member, secret);
+            assert_eq!(chit_fund.commit(commitment), Ok(()));
+            assert_eq!(chit_fund.commit(commitment), Err(Error::AlreadyCommitted));
+        }
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Err(Error::RevealPeriodOpen));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.reveal(Hash::from([0x02; 32])), Err(Error::InvalidReveal));
+        for (member, secret) in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.reveal(secret), Ok(()));
+        }
+        assert_eq!(chit_fund.reveal(Hash::from([0x02; 32])), Err(Error::AlreadyRevealed));
+        assert_eq!(chit_fund.revealed_entropy.get(1), Some([0x03; 32]));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.get_draw_record(1), None);
+        assert_eq!(chit_fund.draw(), Ok(()));
+
+        // The record lets anyone redo the draw.
+        let record = chit_fund.get_draw_record(1).unwrap();
+        assert_eq!(record.entropy, [0x03; 32]);
+        assert_eq!(record.revealed_by, vec![accounts.bob, accounts.charlie]);
+        assert_eq!(record.commitments, 2);
+        let mut input_hash = [0u8; 32];
+        ink::env::hash_encoded::<ink::env::hash::Blake2x256, _>(
+            &(record.entropy, record.block_number, record.timestamp),
+            &mut input_hash,
+        );
+        assert_eq!(record.input_hash, input_hash);
+        assert_eq!(record.randomness, input_hash);
+        assert_eq!(record.seed, u64::from_le_bytes(input_hash[..8].try_into().unwrap()));
+        assert_eq!(record.start_index, (record.seed % 2) as u32);
+        assert_eq!(record.winner_index, record.start_index);
+        assert_eq!(chit_fund.get_winners(), vec![record.winner]);
+        assert_eq!(chit_fund.get_participants()[record.winner_index as usize], record.winner);
+    }
+
+    #[ink::test]
+    fn test_draw_proceeds_after_reveal_deadline() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        let secret = Hash::from([0x07; 32]);
+        assert_eq!(chit_fund.commit(ChitFund::commitment_of(accounts.bob, secret)), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        for _ in 0..=REVEAL_PERIOD {
+            test::advance_block::<DefaultEnvironment>();
+        }
+
+        // Bob never revealed; the draw goes ahead without his secret.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.reveal(secret), Err(Error::RevealPeriodOver));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.draw(), Ok(()));
+    }
+
+    // Chain extension that always returns the same "random" bytes.
+    struct MockedRandomness([u8; 32]);
+
+    impl test::ChainExtension for MockedRandomness {
+        fn func_id(&self) -> u32 {
+            1101
+        }
+
+        fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+            scale::Encode::encode_to(&self.0, output);
+            0
+        }
+    }
+
+    #[ink::test]
+    fn test_draw_with_chain_extension_randomness() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::register_chain_extension(MockedRandomness([0x01; 32]));
+        let mut chit_fund = ChitFund::with_randomness_source(accounts.alice, 5, 100, RandomnessSource::ChainExtension);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 200);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        assert_eq!(chit_fund.commit(Hash::from([0x01; 32])), Err(Error::CommitRevealDisabled));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        // 0x0101010101010101 is odd, so the second participant wins.
+        assert_eq!(chit_fund.get_winners(), vec![accounts.charlie]);
+    }
+
+    #[ink::test]
+    fn test_each_participant_wins_once() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 3, 100);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        // The block never advances, so every draw starts from the same seed and has
+        // to walk past the earlier winners.
+        for round in 1..=3 {
+            for member in members {
+                test::set_caller::<DefaultEnvironment>(member);
+                test::set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 300);
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.draw(), Ok(()));
+            assert_eq!(chit_fund.winner_count, round);
+            if round < 3 {
+                assert_eq!(chit_fund.begin_cycle(), Ok(()));
+            }
+        }
+        for member in members {
+            assert!(chit_fund.has_won(member));
+        }
+    }
+
+    #[ink::test]
+    fn test_fund_terminates_once_everyone_has_won() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 2, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        for round in 1..=2 {
+            assert_ne!(chit_fund.phase, FundPhase::Completed);
+            for member in [accounts.bob, accounts.charlie] {
+                test::set_caller::<DefaultEnvironment>(member);
+                test::set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 200);
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.draw(), Ok(()));
+            assert_eq!(chit_fund.rounds_completed, round);
+            if round < 2 {
+                assert_eq!(chit_fund.begin_cycle(), Ok(()));
+            }
+        }
+
+        assert_eq!(chit_fund.phase, FundPhase::Completed);
+        let completed = test::recorded_events()
+            .filter(|event| matches!(Event::decode(&mut &event.data[..]), Ok(Event::ChitFundCompleted(_))))
+            .count();
+        assert_eq!(completed, 1);
+        assert_eq!(chit_fund.draw(), Err(Error::ChitFundTerminated));
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::ChitFundTerminated));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit(), Err(Error::ChitFundTerminated));
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.join(), Err(Error::ChitFundTerminated));
+    }
+
+    #[ink::test]
+    fn test_failed_payout_can_be_claimed() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        // A contract account without any balance entry, so transfers out of it fail.
+        let contract = AccountId::from([0xFF; 32]);
+        test::set_callee::<DefaultEnvironment>(contract);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.claim_prize(), Err(Error::NoPrizeToClaim));
+
+        // The payout fails, so the prize is kept for Bob to claim.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.get_winners(), vec![accounts.bob]);
+        assert_eq!(chit_fund.unclaimed_prizes.get(accounts.bob), Some(100));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.claim_prize(), Err(Error::PayoutTransferFailed));
+        assert_eq!(chit_fund.unclaimed_prizes.get(accounts.bob), Some(100));
+        test::set_account_balance::<DefaultEnvironment>(contract, 100);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(chit_fund.claim_prize(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(after - before, 100);
+        assert_eq!(chit_fund.claim_prize(), Err(Error::NoPrizeToClaim));
+    }
+
+    #[ink::test]
+    fn test_deposit_overflow_is_rejected() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+
+        chit_fund.pot = Balance::MAX - 99;
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Err(Error::ArithmeticOverflow));
+        assert_eq!(chit_fund.pot, Balance::MAX - 99);
+        assert!(!chit_fund.has_paid(accounts.bob, 1));
+
+        // Exactly reaching the maximum is still fine.
+        chit_fund.pot = Balance::MAX - 100;
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.pot, Balance::MAX);
+    }
+
+    #[ink::test]
+    fn test_end_cycle_overflow_is_rejected() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        start_fund(&mut chit_fund);
+
+        chit_fund.total_amount = Balance::MAX;
+        chit_fund.pot = 1;
+        assert_eq!(chit_fund.end_cycle(), Err(Error::ArithmeticOverflow));
+        assert_eq!(chit_fund.current_round, 1);
+        assert_eq!(chit_fund.phase, FundPhase::ContributionOpen);
+
+        chit_fund.pot = 0;
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.total_amount, Balance::MAX);
+    }
+
+    #[ink::test]
+    fn test_getters() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        assert_eq!(chit_fund.get_admin(), accounts.alice);
+        assert_eq!(chit_fund.get_participants(), vec![accounts.bob, accounts.charlie]);
+        assert_eq!(chit_fund.participant_count(), 2);
+        assert_eq!(chit_fund.get_current_round(), 1);
+        assert_eq!(chit_fund.get_pot(), 100);
+        assert_eq!(chit_fund.get_monthly_contribution(), 100);
+        assert!(!chit_fund.is_finished());
+        assert!(chit_fund.get_winners().is_empty());
+        assert!(chit_fund.has_paid_this_round(accounts.bob));
+        assert!(!chit_fund.has_paid_this_round(accounts.charlie));
+        assert_eq!(
+            chit_fund.get_fund_info(),
+            FundInfo {
+                admin: accounts.alice,
+                max_participants: 5,
+                participant_count: 2,
+                monthly_contribution: 100,
+                current_round: 1,
+                pot: 100,
+                total_amount: 0,
+                phase: FundPhase::ContributionOpen,
+                rounds_completed: 0,
+                winners: vec![],
+            }
+        );
+    }
+
+    #[ink::test]
+    fn test_auction_lowest_bid_wins() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { mode: FundMode::Auction, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.bid(250), Err(Error::ChitFundNotFinished));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.close_bidding(), Err(Error::NoBids));
+        assert_eq!(chit_fund.draw(), Err(Error::WrongFundMode));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.bid(301), Err(Error::InvalidBid));
+        assert_eq!(chit_fund.bid(270), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.bid(241), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.bid(250), Err(Error::BidTooHigh));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(chit_fund.close_bidding(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+
+        // A discount of 59 is split three ways; the two leftover units go to the winner.
+        assert_eq!(after - before, 243);
+        for member in members {
+            assert_eq!(chit_fund.dividends.get(member), Some(19));
+        }
+        assert_eq!(chit_fund.get_winners(), vec![accounts.charlie]);
+        assert_eq!(chit_fund.total_amount, 0);
+
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.bid(100), Err(Error::AlreadyWon));
+    }
+
+    #[ink::test]
+    fn test_claim_dividends() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { mode: FundMode::Auction, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 2, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 400);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.claim_dividends(), Err(Error::NoDividends));
+        assert_eq!(chit_fund.bid(160), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.close_bidding(), Ok(()));
+        assert_eq!(chit_fund.get_pending_dividends(accounts.charlie), 20);
+
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(chit_fund.claim_dividends(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(after - before, 20);
+        assert_eq!(chit_fund.get_pending_dividends(accounts.charlie), 0);
+        assert_eq!(chit_fund.claim_dividends(), Err(Error::NoDividends));
+        assert_eq!(chit_fund.get_pending_dividends(accounts.bob), 20);
+    }
+
+    #[ink::test]
+    fn test_bid_rejected_in_fixed_mode() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 3, 100);
+        assert_eq!(chit_fund.bid(100), Err(Error::WrongFundMode));
+        assert_eq!(chit_fund.close_bidding(), Err(Error::WrongFundMode));
+    }
+
+    #[ink::test]
+    fn test_commission_is_deducted_and_withdrawn() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { commission_bps: 500, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 2, 100, config);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.withdraw_commission(), Err(Error::NoCommissionToWithdraw));
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        let members_before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap()
+            + test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let members_after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap()
+            + test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(members_after - members_before, 190);
+        assert_eq!(chit_fund.commission_balance, 10);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.withdraw_commission(), Err(Error::OnlyAdminCanWithdraw));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(chit_fund.withdraw_commission(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(after - before, 10);
+        assert_eq!(chit_fund.commission_balance, 0);
+    }
+
+    #[ink::test]
+    fn test_round_deadline() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        let config = FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 500 }, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Err(Error::RoundNotExpired));
+        assert!(!chit_fund.is_round_expired());
+
+        test::set_block_timestamp::<DefaultEnvironment>(1_500);
+        assert!(chit_fund.is_round_expired());
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.deposit(), Err(Error::RoundExpired));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+
+        // The next round gets a fresh deadline.
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        assert_eq!(chit_fund.round_start, 1_500);
+        assert!(!chit_fund.is_round_expired());
+    }
+
+    #[ink::test]
+    fn test_late_deposit_requires_penalty() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 500 }, late_fee_bps: 1_000, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+
+        test::set_block_timestamp::<DefaultEnvironment>(500);
+        assert_eq!(chit_fund.late_fee(), 10);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert!(!chit_fund.has_paid(accounts.bob, 1));
+        test::set_value_transferred::<DefaultEnvironment>(10);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        assert_eq!(chit_fund.pot, 100);
+        assert_eq!(chit_fund.penalty_pool, 10);
+        assert_eq!(chit_fund.penalties_paid.get(accounts.bob), Some(10));
+        assert_eq!(chit_fund.deposit_streak(accounts.bob), 0);
+        assert!(chit_fund.has_paid(accounts.bob, 1));
+    }
+
+    #[ink::test]
+    fn test_final_distribution_rewards_perfect_payers() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 500 }, late_fee_bps: 1_000, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        for round in 1..=3 {
+            for member in [accounts.charlie, accounts.django, accounts.bob] {
+                test::set_caller::<DefaultEnvironment>(member);
+                // Bob pays the first round late, with the late fee.
+                if round == 1 && member == accounts.bob {
+                    test::set_block_timestamp::<DefaultEnvironment>(500);
+                    test::set_value_transferred::<DefaultEnvironment>(110);
+                } else {
+                    test::set_value_transferred::<DefaultEnvironment>(100);
+                }
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            test::set_block_timestamp::<DefaultEnvironment>(round as u64 * 500);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.final_distribution(), Err(Error::FundNotCompleted));
+            assert_eq!(chit_fund.draw(), Ok(()));
+            if round < 3 {
+                assert_eq!(chit_fund.begin_cycle(), Ok(()));
+            }
+        }
+        assert_eq!(chit_fund.penalty_pool, 10);
+
+        // Anyone can run the distribution, but only once.
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.final_distribution(), Ok(()));
+        assert_eq!(chit_fund.final_distribution(), Err(Error::PenaltyPoolAlreadyDistributed));
+        assert_eq!(chit_fund.penalty_pool, 0);
+        assert_eq!(chit_fund.get_pending_dividends(accounts.bob), 0);
+        assert_eq!(chit_fund.get_pending_dividends(accounts.charlie), 5);
+        assert_eq!(chit_fund.get_pending_dividends(accounts.django), 5);
+    }
+
+    #[ink::test]
+    fn test_expel_after_missed_rounds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { max_missed_rounds: 2, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        // Charlie pays the first round and then stops paying.
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        for round in 1..=3 {
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.missed_rounds.get(accounts.charlie).unwrap_or(0), round - 1);
+            if round == 2 {
+                assert_eq!(chit_fund.expel(accounts.charlie), Err(Error::NotEnoughMissedRounds));
+            }
+            assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        }
+
+        // An instalment towards the open round is refunded along with the rest.
+        call_as(accounts.charlie, 40);
+        assert_eq!(chit_fund.deposit_batch(vec![(accounts.charlie, 40)]), Ok(()));
+        assert_eq!(chit_fund.partial_deposits.get((accounts.charlie, 4)), Some(40));
+
+        call_as(accounts.bob, 0);
+        assert_eq!(chit_fund.expel(accounts.charlie), Err(Error::OnlyAdminCanExpel));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(chit_fund.expel(accounts.charlie), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(after - before, 140);
+        assert_eq!(chit_fund.partial_deposits.get((accounts.charlie, 4)), None);
+        // The refund comes off the books, so the fund never counts on more than it holds.
+        assert_eq!(chit_fund.total_amount, 0);
+        assert!(chit_fund.pot + chit_fund.total_amount <= chit_fund.held);
+        assert_eq!(chit_fund.get_participants(), vec![accounts.bob]);
+        assert_eq!(chit_fund.expel(accounts.charlie), Err(Error::NotParticipant));
+    }
+
+    #[ink::test]
+    fn test_leave_refunds_deposits() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(chit_fund.leave(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(after - before, 200);
+        assert_eq!(chit_fund.get_participants(), vec![accounts.charlie]);
+        assert_eq!(chit_fund.pot, 0);
+        assert_eq!(chit_fund.total_amount, 100);
+        assert!(!chit_fund.has_paid(accounts.bob, 1));
+        assert_eq!(chit_fund.leave(), Err(Error::NotParticipant));
+    }
+
+    #[ink::test]
+    fn test_leave_rejected_after_first_draw() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.leave(), Err(Error::CannotLeaveMidCycle));
+    }
+
+    #[ink::test]
+    fn test_cancel_fund_and_claim_refunds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.claim_refund(), Err(Error::FundNotCancelled));
+        assert_eq!(chit_fund.cancel_fund(), Err(Error::OnlyAdminCanCancel));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+        assert_eq!(chit_fund.refund_pool, 300);
+        assert_eq!(chit_fund.cancel_fund(), Err(Error::FundCancelled));
+        assert_eq!(chit_fund.end_cycle(), Err(Error::FundCancelled));
+
+        for (member, refund) in [(accounts.bob, 200), (accounts.charlie, 100)] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.refund_due(member), refund);
+            let before = test::get_account_balance::<DefaultEnvironment>(member).unwrap();
+            assert_eq!(chit_fund.claim_refund(), Ok(()));
+            let after = test::get_account_balance::<DefaultEnvironment>(member).unwrap();
+            assert_eq!(after - before, refund);
+            assert_eq!(chit_fund.claim_refund(), Err(Error::RefundAlreadyClaimed));
+            assert_eq!(chit_fund.deposit(), Err(Error::FundCancelled));
+        }
+    }
+
+    #[ink::test]
+    fn test_void_round_refunds_contributors() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { paid_up_winners_only: true, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        // Charlie misses the first round, which Bob then wins.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.void_round(), Err(Error::RoundStillDrawable));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.void_round(), Err(Error::RoundAlreadyDrawn));
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+
+        // Both pay the second round, but no one is left who may win it.
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Err(Error::FailedToGetWinner));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.void_round(), Err(Error::OnlyAdminCanVoid));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.void_round(), Ok(()));
+        assert_eq!(chit_fund.void_round(), Err(Error::RoundVoided));
+        assert_eq!(chit_fund.draw(), Err(Error::RoundVoided));
+        assert!(chit_fund.is_round_voided(2));
+        assert_eq!(chit_fund.total_amount, 0);
+
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.refund_due(member), 100);
+            let before = test::get_account_balance::<DefaultEnvironment>(member).unwrap();
+            assert_eq!(chit_fund.claim_refund(), Ok(()));
+            let after = test::get_account_balance::<DefaultEnvironment>(member).unwrap();
+            assert_eq!(after - before, 100);
+            assert_eq!(chit_fund.refund_due(member), 0);
+            assert_eq!(chit_fund.claim_refund(), Err(Error::FundNotCancelled));
+        }
+    }
+
+    #[ink::test]
+    fn test_cancelled_refunds_are_pro_rata() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        // Pretend part of the collected money has already gone out, leaving 150 for
+        // net contributions of 200 and 100.
+        chit_fund.total_contributed.insert(accounts.bob, &200);
+        chit_fund.total_contributed.insert(accounts.charlie, &100);
+        chit_fund.total_amount = 150;
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+        assert_eq!(chit_fund.refund_due(accounts.bob), 100);
+        assert_eq!(chit_fund.refund_due(accounts.charlie), 50);
+        assert_eq!(chit_fund.refund_due(accounts.django), 0);
+    }
+
+    #[ink::test]
+    fn test_two_step_admin_transfer() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        start_fund(&mut chit_fund);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.transfer_admin(accounts.bob), Err(Error::OnlyAdminCanTransferAdmin));
+        assert_eq!(chit_fund.accept_admin(), Err(Error::NotPendingAdmin));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.transfer_admin(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.admin, accounts.alice);
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.accept_admin(), Err(Error::NotPendingAdmin));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.accept_admin(), Ok(()));
+        assert_eq!(chit_fund.admin, accounts.bob);
+        assert_eq!(chit_fund.pending_admin, None);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::OnlyOwnerCanBeginCycle));
+    }
+
+    #[ink::test]
+    fn test_renounce_admin() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        assert_eq!(chit_fund.transfer_admin(accounts.bob), Ok(()));
+
+        chit_fund.commission_balance = 10;
+        assert_eq!(chit_fund.renounce_admin(), Err(Error::CommissionNotWithdrawn));
+        chit_fund.commission_balance = 0;
+        assert_eq!(chit_fund.renounce_admin(), Ok(()));
+        assert_eq!(chit_fund.end_cycle(), Err(Error::OnlyOwnerCanEndCycle));
+        assert!(!chit_fund.has_role(accounts.alice, Role::Organizer));
+        // The zero account the draw records sealed winners under gets no powers either.
+        assert!(!chit_fund.has_role(AccountId::from([0; 32]), Role::Organizer));
+        // The pending nomination is dropped too.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.accept_admin(), Err(Error::NotPendingAdmin));
+    }
+
+    #[ink::test]
+    fn test_commission_after_renounce_goes_to_treasury() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let config = FundConfig { treasury: Some(accounts.eve), ..Default::default() };
+        let mut chit_fund = fund_with_members(3, config, &[accounts.bob, accounts.charlie]);
+        assert_eq!(chit_fund.renounce_admin(), Ok(()));
+
+        // Commission keeps accruing from later rounds. Anyone can send it on, and it goes
+        // to the treasury rather than to a burned account.
+        chit_fund.commission_balance = 30;
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap();
+        call_as(accounts.django, 0);
+        assert_eq!(chit_fund.withdraw_commission(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap();
+        assert_eq!(after - before, 30);
+        assert_eq!(chit_fund.commission_balance, 0);
+    }
+
+    #[ink::test]
+    fn test_multisig_lifecycle_actions() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig {
+            co_admins: vec![accounts.alice, accounts.bob, accounts.charlie],
+            approval_threshold: 2,
+            ..Default::default()
+        };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        chit_fund.phase = FundPhase::ContributionOpen;
+        assert_eq!(chit_fund.end_cycle(), Err(Error::RequiresMultisig));
+
+        assert_eq!(chit_fund.propose_action(AdminAction::EndCycle), Ok(0));
+        assert_eq!(chit_fund.approve_action(0), Err(Error::AlreadyApproved));
+        assert_eq!(chit_fund.execute_action(0), Err(Error::NotEnoughApprovals));
+
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.approve_action(0), Err(Error::NotCoAdmin));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.approve_action(0), Ok(()));
+        assert_eq!(chit_fund.execute_action(0), Ok(()));
+        assert_eq!(chit_fund.phase, FundPhase::DrawPending);
+        assert_eq!(chit_fund.current_round, 2);
+        assert_eq!(chit_fund.execute_action(0), Err(Error::ProposalAlreadyExecuted));
+        assert_eq!(chit_fund.approve_action(1), Err(Error::ProposalNotFound));
+    }
+
+    #[ink::test]
+    fn test_multisig_proposal_expires() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig {
+            co_admins: vec![accounts.alice, accounts.bob],
+            approval_threshold: 2,
+            ..Default::default()
+        };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        assert_eq!(chit_fund.propose_action(AdminAction::CancelFund), Ok(0));
+        for _ in 0..=PROPOSAL_LIFETIME {
+            test::advance_block::<DefaultEnvironment>();
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.approve_action(0), Err(Error::ProposalExpired));
+        assert_ne!(chit_fund.phase, FundPhase::Cancelled);
+    }
+
+    #[ink::test]
+    fn test_propose_action_without_multisig() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        assert_eq!(chit_fund.propose_action(AdminAction::Draw), Err(Error::MultisigDisabled));
+    }
+
+    #[ink::test]
+    fn test_token_fund_rejects_native_value() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { contribution_token: Some(accounts.frank), ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+
+        // Contributions are pulled from the token with `transfer_from`, so native value
+        // sent along is refused.
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Err(Error::NativeValueNotAccepted));
+        assert_eq!(chit_fund.pot, 0);
+    }
+
+    #[ink::test]
+    fn test_invite_only_join() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { invite_only: true, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        assert_eq!(chit_fund.add_to_allowlist(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.add_to_allowlist(accounts.charlie), Ok(()));
+        assert_eq!(chit_fund.remove_from_allowlist(accounts.charlie), Ok(()));
+        assert!(chit_fund.is_allowlisted(accounts.bob));
+        assert!(!chit_fund.is_allowlisted(accounts.charlie));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!(chit_fund.add_to_allowlist(accounts.django), Err(Error::OnlyAdminCanManageAllowlist));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.join(), Err(Error::NotAllowlisted));
+        assert_eq!(chit_fund.get_participants(), vec![accounts.bob]);
+    }
+
+    #[ink::test]
+    fn test_collateral_slashed_on_default_and_reclaimed() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { collateral: 100, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 500);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        assert_eq!(chit_fund.join(), Err(Error::IncorrectCollateralAmount));
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.join(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.collateral_of(accounts.charlie), 100);
+
+        // Charlie skips the round, so their collateral covers the contribution.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.collateral_of(accounts.charlie), 0);
+        assert_eq!(chit_fund.collateral_of(accounts.bob), 100);
+        assert_eq!(chit_fund.total_amount, 200);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.reclaim_collateral(), Err(Error::CollateralLocked));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(chit_fund.reclaim_collateral(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(after - before, 100);
+        assert_eq!(chit_fund.reclaim_collateral(), Err(Error::NoCollateral));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.reclaim_collateral(), Err(Error::NoCollateral));
+    }
+
+    #[ink::test]
+    fn test_pause_blocks_activity_but_not_refunds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 500);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.pause(), Err(Error::OnlyAdminCanPause));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.pause(), Ok(()));
+        assert!(chit_fund.is_paused());
+        assert_eq!(chit_fund.pause(), Err(Error::AlreadyPaused));
+        assert_eq!(chit_fund.end_cycle(), Err(Error::ContractPaused));
+        assert_eq!(chit_fund.draw(), Err(Error::ContractPaused));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.join(), Err(Error::ContractPaused));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Err(Error::ContractPaused));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.unpause(), Ok(()));
+        assert_eq!(chit_fund.unpause(), Err(Error::NotPaused));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        // Refunds stay available while paused.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.pause(), Ok(()));
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.claim_refund(), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_upgrade_and_migrate_guards() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        assert_eq!(chit_fund.storage_version, STORAGE_VERSION);
+        assert_eq!(chit_fund.migrate(0), Err(Error::AlreadyMigrated));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.upgrade(Hash::from([0x01; 32])), Err(Error::OnlyAdminCanUpgrade));
+        assert_eq!(chit_fund.migrate(0), Err(Error::OnlyAdminCanUpgrade));
+
+        // Storage left behind by an older version is migrated exactly once.
+        chit_fund.storage_version = STORAGE_VERSION - 1;
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.migrate(0), Ok(true));
+        assert_eq!(chit_fund.storage_version, STORAGE_VERSION);
+        assert_eq!(chit_fund.migrate(0), Err(Error::AlreadyMigrated));
+
+        let config = FundConfig {
+            co_admins: vec![accounts.bob, accounts.charlie],
+            approval_threshold: 2,
+            ..Default::default()
+        };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        assert_eq!(chit_fund.upgrade(Hash::from([0x01; 32])), Err(Error::RequiresMultisig));
+    }
+
+    #[ink::test]
+    fn test_round_history() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 2, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 400);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        for round in 1..=2u32 {
+            for member in [accounts.bob, accounts.charlie] {
+                test::set_caller::<DefaultEnvironment>(member);
+                test::set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            test::set_block_timestamp::<DefaultEnvironment>(round as u64 * 1_000);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.get_round_history(round), None);
+            assert_eq!(chit_fund.draw(), Ok(()));
+            assert_eq!(
+                chit_fund.get_round_history(round),
+                Some(RoundRecord {
+                    round,
+                    winner: chit_fund.get_winners()[round as usize - 1],
+                    prize: 200,
+                    commission: 0,
+                    total_collected: 200,
+                    timestamp: round as u64 * 1_000,
+                    bonus: 0,
+                    snapshot: RoundSnapshot { members: 2, tickets: 2, expected: 200 },
+                    collected: 200,
+                    penalties: 0,
+                    dividends: 0,
+                    defaults: 0,
+                })
+            );
+            if round < 2 {
+                assert_eq!(chit_fund.begin_cycle(), Ok(()));
+            }
+        }
+        let winners = chit_fund.get_all_winners();
+        assert_eq!(winners, vec![(1, chit_fund.get_winners()[0]), (2, chit_fund.get_winners()[1])]);
+    }
+
+    #[ink::test]
+    fn test_member_index_stays_dense_after_leave() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 3, 100);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.leave(), Ok(()));
+
+        // The last member takes the leaver's slot.
+        assert_eq!(chit_fund.participant_count(), 2);
+        assert_eq!(chit_fund.get_participants(), vec![accounts.django, accounts.charlie]);
+        assert_eq!(chit_fund.members.get(accounts.django), Some(MemberInfo { index: 0, tickets: 1, wins: 0, has_won: false }));
+        assert!(!chit_fund.is_member(accounts.bob));
+        assert_eq!(chit_fund.select_winner(2), Some(accounts.django));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!(chit_fund.get_participants(), vec![accounts.django, accounts.charlie, accounts.bob]);
+    }
+
+    #[ink::test]
+    fn test_start_fund_requires_min_participants() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { min_participants: 2, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Err(Error::FundNotStarted));
+        assert_eq!(chit_fund.start_fund(), Err(Error::OnlyAdminCanStart));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.start_fund(), Err(Error::NotEnoughParticipants));
+        assert_eq!(chit_fund.end_cycle(), Err(Error::FundNotStarted));
+        assert_eq!(chit_fund.draw(), Err(Error::FundNotStarted));
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::FundNotStarted));
+
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        assert_eq!(chit_fund.join(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.start_fund(), Ok(()));
+        assert_eq!(chit_fund.start_fund(), Err(Error::FundAlreadyStarted));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+    }
+
+
+
+    #[ink::test]
+    fn test_phase_transitions() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 2, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 400);
+        assert_eq!(chit_fund.get_phase(), FundPhase::Recruiting);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.get_phase(), FundPhase::ContributionOpen);
+
+        // Membership is locked once the fund has started.
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.join(), Err(Error::MembershipLocked));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.join_on_behalf(accounts.charlie), Err(Error::MembershipLocked));
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::ChitFundNotFinished));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.get_phase(), FundPhase::DrawPending);
+        assert_eq!(chit_fund.end_cycle(), Err(Error::ChitFundAlreadyFinished));
+
+        let events = test::recorded_events().collect::<Vec<_>>();
+        let event = Event::decode(&mut &events.last().unwrap().data[..]).unwrap();
+        assert!(matches!(event, Event::CycleEnded(_)));
+        let event = Event::decode(&mut &events[events.len() - 2].data[..]).unwrap();
+        match event {
+            Event::PhaseChanged(changed) => {
+                assert_eq!(changed.from, FundPhase::ContributionOpen);
+                assert_eq!(changed.to, FundPhase::DrawPending);
+            }
+            _ => panic!("expected PhaseChanged"),
+        }
+
+        // The only member wins, which completes the fund.
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.get_phase(), FundPhase::Completed);
+        assert_eq!(chit_fund.draw(), Err(Error::ChitFundTerminated));
+    }
+
+    #[ink::test]
+    fn test_settle_round() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 1_000 }, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 400);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.settle_round(), Err(Error::ContributionsIncomplete));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        // Any member can settle a fully paid round before its deadline.
+        assert_eq!(chit_fund.settle_round(), Ok(()));
+        assert_eq!(chit_fund.get_winners().len(), 1);
+        assert_eq!(chit_fund.current_round, 2);
+        assert_eq!(chit_fund.get_phase(), FundPhase::ContributionOpen);
+        assert_eq!(chit_fund.total_amount, 0);
+
+        // Past the deadline a round with a defaulter closes without a draw.
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        assert_eq!(chit_fund.settle_round(), Ok(()));
+        assert_eq!(chit_fund.get_winners().len(), 1);
+        assert_eq!(chit_fund.current_round, 3);
+        assert_eq!(chit_fund.get_phase(), FundPhase::ContributionOpen);
+        assert_eq!(chit_fund.total_amount, 100);
+    }
+
+    #[ink::test]
+    fn test_prize_goes_to_payout_address() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 1, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 100);
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.set_payout_address(accounts.eve), Err(Error::NotParticipant));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!(chit_fund.set_payout_address(accounts.eve), Ok(()));
+        assert_eq!(chit_fund.payout_address_of(accounts.bob), accounts.eve);
+        start_fund(&mut chit_fund);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        let bob_before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        let eve_before = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap(), bob_before);
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap() - eve_before, 100);
+        assert_eq!(chit_fund.get_winners(), vec![accounts.bob]);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.set_payout_address(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.payout_address_of(accounts.bob), accounts.bob);
+    }
+
+    #[ink::test]
+    fn test_deposit_for_member() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit_for(accounts.charlie), Err(Error::NotParticipant));
+        assert_eq!(chit_fund.deposit_for(accounts.bob), Ok(()));
+        assert!(chit_fund.has_paid(accounts.bob, 1));
+        assert_eq!(chit_fund.total_contributed.get(accounts.bob), Some(100));
+        assert_eq!(chit_fund.total_contributed.get(accounts.eve), None);
+        assert_eq!(chit_fund.deposit_for(accounts.bob), Err(Error::AlreadyDeposited));
+
+        let events = test::recorded_events().collect::<Vec<_>>();
+        match Event::decode(&mut &events.last().unwrap().data[..]).unwrap() {
+            Event::FundDeposited(deposited) => {
+                assert_eq!(deposited.account, Some(accounts.bob));
+                assert_eq!(deposited.payer, Some(accounts.eve));
+                assert_eq!(deposited.amount, 100);
+            }
+            _ => panic!("expected FundDeposited"),
+        }
+    }
+
+    #[ink::test]
+    fn test_members_vote_out_defaulter() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { max_missed_rounds: 2, collateral: 300, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 2_000);
+        let members = [accounts.bob, accounts.charlie, accounts.django, accounts.eve];
+        test::set_value_transferred::<DefaultEnvironment>(300);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        // Charlie skips the first two rounds while everyone else pays.
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        for _ in 0..2 {
+            for member in [accounts.bob, accounts.django, accounts.eve] {
+                test::set_caller::<DefaultEnvironment>(member);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            if chit_fund.get_current_round() == 2 {
+                test::set_caller::<DefaultEnvironment>(accounts.bob);
+                assert_eq!(chit_fund.propose_expulsion(accounts.charlie), Err(Error::NotEnoughMissedRounds));
+                test::set_caller::<DefaultEnvironment>(accounts.alice);
+            }
+            assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        }
+        assert_eq!(chit_fund.collateral_of(accounts.charlie), 100);
+        assert!(!chit_fund.is_paid_up(accounts.charlie));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.propose_expulsion(accounts.charlie), Err(Error::NotPaidUp));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.propose_expulsion(accounts.charlie), Ok(()));
+        assert_eq!(chit_fund.propose_expulsion(accounts.charlie), Err(Error::ExpulsionAlreadyProposed));
+        let vote = chit_fund.get_expulsion_vote(3, accounts.charlie).unwrap();
+        assert_eq!((vote.votes, vote.electorate), (1, 3));
+        assert_eq!(chit_fund.execute_expulsion(accounts.charlie), Err(Error::QuorumNotReached));
+        assert_eq!(chit_fund.vote_expulsion(accounts.charlie), Err(Error::AlreadyVoted));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.vote_expulsion(accounts.charlie), Err(Error::CannotVoteOnOwnExpulsion));
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.vote_expulsion(accounts.charlie), Ok(()));
+        // Paying the open round does not cure the missed ones.
+        call_as(accounts.charlie, 100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        call_as(accounts.eve, 0);
+
+        // Charlie's remaining collateral goes into the penalty pool and the rounds shrink to
+        // three. The open round's deposit is refunded and taken out of the pot.
+        let (pot, penalty_pool) = (chit_fund.get_pot(), chit_fund.penalty_pool);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(chit_fund.execute_expulsion(accounts.charlie), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(after - before, 100);
+        assert_eq!(chit_fund.get_pot(), pot - 100);
+        assert!(chit_fund.pot + chit_fund.total_amount <= chit_fund.held);
+        assert_eq!(chit_fund.penalty_pool, penalty_pool + 100);
+        assert_eq!(chit_fund.collateral_of(accounts.charlie), 0);
+        assert!(!chit_fund.is_member(accounts.charlie));
+        assert_eq!(chit_fund.participant_count(), 3);
+        assert_eq!(chit_fund.execute_expulsion(accounts.charlie), Err(Error::ProposalAlreadyExecuted));
+    }
+
+    #[ink::test]
+    fn test_only_paid_up_members_can_win() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { paid_up_winners_only: true, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.eligible_count(), 3);
+
+        // Charlie skips the first round, which no longer holds the draw up.
+        for member in [accounts.bob, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 200);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert!(!chit_fund.is_eligible(accounts.charlie));
+        assert_eq!(chit_fund.eligible_count(), 2);
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert!(!chit_fund.has_won(accounts.charlie));
+        assert_eq!(chit_fund.eligible_count(), 1);
+
+        let events = test::recorded_events().collect::<Vec<_>>();
+        let winner = events
+            .iter()
+            .rev()
+            .find_map(|event| match Event::decode(&mut &event.data[..]).unwrap() {
+                Event::DrawWinner(winner) => Some(winner),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(winner.eligible, 2);
+        assert_ne!(winner.victor, Some(accounts.charlie));
+    }
+
+    #[ink::test]
+    fn test_prize_is_contribution_times_members() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { commission_bps: 1_000, schedule: ScheduleSource::Timestamp { millis_per_round: 1_000 }, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 2_000);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.scheduled_prize(), 300);
+
+        // Charlie misses the first round, which closes at its deadline without a draw.
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        for member in [accounts.bob, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        assert_eq!(chit_fund.settle_round(), Ok(()));
+        assert_eq!(chit_fund.get_round_history(1), None);
+        assert_eq!(chit_fund.total_amount, 200);
+
+        // Each later round pays exactly three contributions less 10% commission, and the
+        // first round's 200 carries over.
+        for round in 2..=4u32 {
+            for member in members {
+                test::set_caller::<DefaultEnvironment>(member);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            assert_eq!(chit_fund.settle_round(), Ok(()));
+            let record = chit_fund.get_round_history(round).unwrap();
+            assert_eq!(record.prize, 300);
+            assert_eq!(record.commission, 30);
+            assert_eq!(record.total_collected, 500);
+            assert_eq!(chit_fund.prizes_won.get(record.winner), Some(300));
+        }
+        assert_eq!(chit_fund.commission_balance, 90);
+        assert_eq!(chit_fund.get_phase(), FundPhase::Completed);
+
+        // The carried-over 200 is shared as dividends, the last winner taking the remainder.
+        assert_eq!(chit_fund.total_amount, 0);
+        let last_winner = chit_fund.get_round_history(4).unwrap().winner;
+        for member in members {
+            let expected = if member == last_winner { 68 } else { 66 };
+            assert_eq!(chit_fund.get_pending_dividends(member), expected);
+        }
+    }
+
+    #[ink::test]
+    fn test_payouts_reject_reentrant_calls() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 2, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 400);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+
+        // Calls arriving while a payout holds the lock, as a recipient calling back in would.
+        chit_fund.reentrancy_lock = true;
+        assert_eq!(chit_fund.draw(), Err(Error::ReentrantCall));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.claim_dividends(), Err(Error::ReentrantCall));
+        assert_eq!(chit_fund.claim_refund(), Err(Error::ReentrantCall));
+        assert_eq!(chit_fund.claim_prize(), Err(Error::ReentrantCall));
+
+        // The lock is released once a payout has finished, whether or not it succeeded.
+        chit_fund.reentrancy_lock = false;
+        assert_eq!(chit_fund.claim_dividends(), Err(Error::NoDividends));
+        assert!(!chit_fund.reentrancy_lock);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert!(!chit_fund.reentrancy_lock);
+        assert_eq!(chit_fund.get_winners().len(), 1);
+    }
+
+    #[ink::test]
+    fn test_events_carry_round_data() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 1_000 }, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 2, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 400);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.round_deadline(), Some(1_000));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(40);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_value_transferred::<DefaultEnvironment>(60);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+
+        let events = test::recorded_events()
+            .map(|event| Event::decode(&mut &event.data[..]).unwrap())
+            .collect::<Vec<_>>();
+        let deposits = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::FundDeposited(deposited) => {
+                    Some((deposited.round, deposited.paid_towards_round, deposited.total_contributed))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(deposits, vec![(1, 40, 0), (1, 100, 100), (1, 100, 100)]);
+        for event in &events {
+            match event {
+                Event::CycleEnded(ended) => assert_eq!(ended.round, 1),
+                Event::DrawWinner(winner) => assert_eq!((winner.round, winner.remaining), (1, 1)),
+                Event::NewCycleBegan(began) => {
+                    assert_eq!((began.round, began.deadline), (2, Some(2_000)))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[ink::test]
+    fn test_prize_paid_in_installments() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig {
+            payout_schedule: PayoutSchedule::Installments(3),
+            paid_up_winners_only: true,
+            ..Default::default()
+        };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let winner = chit_fund.get_winners()[0];
+        assert_eq!(chit_fund.get_payout_plan(winner).map(|plan| plan.amount), Some(300));
+
+        // Only the first tranche is available straight after the draw.
+        assert_eq!(chit_fund.claimable_installment(winner), 100);
+        test::set_caller::<DefaultEnvironment>(winner);
+        let before = test::get_account_balance::<DefaultEnvironment>(winner).unwrap();
+        assert_eq!(chit_fund.claim_installment(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(winner).unwrap();
+        assert_eq!(after - before, 100);
+        assert_eq!(chit_fund.claim_installment(), Err(Error::NoPrizeToClaim));
+
+        // The winner skips the next round, which is covered from their unclaimed tranches.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        for member in members.into_iter().filter(|member| *member != winner) {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.get_payout_plan(winner).map(|plan| plan.slashed), Some(100));
+        assert_eq!(chit_fund.total_amount, 300);
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.claimable_installment(winner), 100);
+        test::set_caller::<DefaultEnvironment>(winner);
+        assert_eq!(chit_fund.claim_installment(), Ok(()));
+
+        // The slashed tranche is gone for good.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.get_phase(), FundPhase::Completed);
+        assert_eq!(chit_fund.claimable_installment(winner), 0);
+    }
+
+    #[ink::test]
+    fn test_yield_strategy_guards() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 2, 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!(chit_fund.set_yield_strategy(Some(accounts.frank)), Err(Error::OnlyAdminCanManageYield));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.invest_pot(), Err(Error::OnlyAdminCanManageYield));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.invest_pot(), Err(Error::NoYieldStrategy));
+        assert_eq!(chit_fund.set_yield_strategy(Some(accounts.frank)), Ok(()));
+        assert_eq!(chit_fund.invest_pot(), Err(Error::NothingToInvest));
+
+        // The kill switch stops investing until a strategy is set again.
+        assert_eq!(chit_fund.kill_yield_strategy(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.invest_pot(), Err(Error::YieldStrategyKilled));
+        assert_eq!(chit_fund.set_yield_strategy(Some(accounts.frank)), Ok(()));
+        assert!(!chit_fund.yield_killed);
+
+        // Nothing is out with the strategy, so the round closes without calling it.
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.invest_pot(), Err(Error::ChitFundHasFinished));
+        assert_eq!(chit_fund.invested, 0);
+    }
+
+    #[ink::test]
+    fn test_roles_delegate_admin_duties() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { commission_bps: 1_000, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 2, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 400);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.grant_role(accounts.bob, Role::Organizer), Err(Error::OnlyAdminCanManageRoles));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.grant_role(accounts.bob, Role::Organizer), Ok(()));
+        assert_eq!(chit_fund.grant_role(accounts.bob, Role::Organizer), Err(Error::RoleAlreadyGranted));
+        assert_eq!(chit_fund.grant_role(accounts.eve, Role::Treasurer), Ok(()));
+        assert_eq!(chit_fund.grant_role(accounts.frank, Role::Auditor), Ok(()));
+        assert!(chit_fund.has_role(accounts.alice, Role::Auditor));
+
+        // The organizer runs the round on the admin's behalf.
+        for member in [accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.start_fund(), Ok(()));
+        for member in [accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.withdraw_commission(), Err(Error::OnlyAdminCanWithdraw));
+
+        // The treasurer releases the commission, which still goes to the admin.
+        test::set_caller::<DefaultEnvironment>(accounts.frank);
+        assert_eq!(chit_fund.withdraw_commission(), Err(Error::OnlyAdminCanWithdraw));
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::OnlyOwnerCanBeginCycle));
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(chit_fund.withdraw_commission(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.alice).unwrap();
+        assert_eq!(after - before, 20);
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.revoke_role(accounts.bob, Role::Organizer), Ok(()));
+        assert_eq!(chit_fund.revoke_role(accounts.bob, Role::Organizer), Err(Error::RoleNotGranted));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::OnlyOwnerCanBeginCycle));
+    }
+
+    #[ink::test]
+    fn test_poke_defaulters() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 1_000 }, late_fee_bps: 1_000, poke_bounty: 5, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.poke_defaulters(), Err(Error::TooEarlyToPoke));
+        test::set_block_timestamp::<DefaultEnvironment>(500);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap();
+        assert_eq!(chit_fund.poke_defaulters(), Ok(()));
+        // The penalty pool is empty, so there is no bounty yet.
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap(), before);
+        let reminders = test::recorded_events()
+            .filter(|event| matches!(Event::decode(&mut &event.data[..]).unwrap(), Event::ReminderIssued(_)))
+            .count();
+        assert_eq!(reminders, 2);
+        assert_eq!(chit_fund.poke_defaulters(), Err(Error::AlreadyPoked));
+
+        // A late payer refills the penalty pool, which funds the next round's bounty.
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        test::set_value_transferred::<DefaultEnvironment>(110);
+        for member in [accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        test::set_block_timestamp::<DefaultEnvironment>(1_600);
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.poke_defaulters(), Ok(()));
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap(), before + 5);
+        assert_eq!(chit_fund.penalty_pool, 15);
+    }
+
+    #[ink::test]
+    fn test_terms_acceptance_and_amendment() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let terms = Hash::from([0x01; 32]);
+        let amended = Hash::from([0x02; 32]);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { terms_hash: Some(terms), terms_uri: b"ipfs://terms".to_vec(), ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        assert_eq!(chit_fund.get_terms(), (Some(terms), b"ipfs://terms".to_vec()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Err(Error::TermsNotAccepted));
+        assert_eq!(chit_fund.accept_terms(amended), Err(Error::TermsMismatch));
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.accept_terms(terms), Ok(()));
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+
+        // New terms need a majority of the three members.
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.propose_terms_amendment(amended, Vec::new()), Err(Error::NotParticipant));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.propose_terms_amendment(amended, b"ipfs://amended".to_vec()), Ok(0));
+        assert_eq!(chit_fund.vote_terms_amendment(0), Err(Error::AlreadyVoted));
+        assert_eq!(chit_fund.get_terms().0, Some(terms));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.vote_terms_amendment(0), Ok(()));
+        assert_eq!(chit_fund.get_terms(), (Some(amended), b"ipfs://amended".to_vec()));
+        assert_eq!(chit_fund.vote_terms_amendment(0), Err(Error::ProposalAlreadyExecuted));
+
+        // Acceptance of the old terms no longer lets anyone in.
+        assert!(!chit_fund.has_accepted_terms(accounts.bob));
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.accept_terms(terms), Err(Error::TermsMismatch));
+        assert_eq!(chit_fund.accept_terms(amended), Ok(()));
+        assert_eq!(chit_fund.join(), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_cure_default_within_grace_period() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig {
+            max_missed_rounds: 2,
+            late_fee_bps: 1_000,
+            grace_period: 5_000,
+            ..Default::default()
+        };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.cure_default(), Err(Error::NotInDefault));
+
+        // Charlie misses two rounds and becomes liable to expulsion at time 1_000.
+        test::set_block_timestamp::<DefaultEnvironment>(1_000);
+        for _ in 0..2 {
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        }
+        assert_eq!(chit_fund.arrears_of(accounts.charlie), 200);
+        assert_eq!(chit_fund.cure_cost(accounts.charlie), 220);
+        assert_eq!(chit_fund.grace_period_ends(accounts.charlie), Some(6_000));
+        assert_eq!(chit_fund.expel(accounts.charlie), Err(Error::GracePeriodActive));
+        assert_eq!(chit_fund.missed_rounds.get(accounts.charlie), Some(2));
+
+        // Paying short is refused; paying arrears plus late fees restores the member.
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(200);
+        assert_eq!(chit_fund.cure_default(), Err(Error::IncorrectContributionAmount));
+        test::set_value_transferred::<DefaultEnvironment>(220);
+        assert_eq!(chit_fund.cure_default(), Ok(()));
+        assert_eq!(chit_fund.pot, 200);
+        assert_eq!(chit_fund.penalty_pool, 20);
+        assert_eq!(chit_fund.total_contributed.get(accounts.charlie), Some(200));
+        assert_eq!(chit_fund.grace_period_ends(accounts.charlie), None);
+        assert_eq!(chit_fund.missed_rounds.get(accounts.charlie), None);
+        let events = test::recorded_events().collect::<Vec<_>>();
+        let Event::DefaultCured(cured) = Event::decode(&mut &events.last().unwrap().data[..]).unwrap() else {
+            panic!("expected DefaultCured");
+        };
+        assert_eq!((cured.missed_rounds, cured.arrears, cured.penalty), (2, 200, 20));
+
+        // Once Bob has defaulted too and his grace period has run out, he can be expelled.
+        for _ in 0..2 {
+            test::set_caller::<DefaultEnvironment>(accounts.charlie);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        }
+        assert_eq!(chit_fund.expel(accounts.charlie), Err(Error::NotEnoughMissedRounds));
+        assert_eq!(chit_fund.expel(accounts.bob), Err(Error::GracePeriodActive));
+        test::set_block_timestamp::<DefaultEnvironment>(6_000);
+        assert_eq!(chit_fund.expel(accounts.bob), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_transfer_membership() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { collateral: 50, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(50);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        // Bob asks to hand his seat to Eve; two of the other two members have to agree.
+        assert_eq!(chit_fund.transfer_membership(accounts.charlie), Err(Error::AlreadyJoined));
+        assert_eq!(chit_fund.transfer_membership(accounts.eve), Ok(()));
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Err(Error::CannotVoteOnOwnTransfer));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Err(Error::AlreadyVoted));
+        assert!(chit_fund.is_member(accounts.bob));
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Ok(()));
+
+        // Eve holds the seat with Bob's history, and Bob is out.
+        assert!(!chit_fund.is_member(accounts.bob));
+        assert_eq!(chit_fund.get_participants(), vec![accounts.eve, accounts.charlie, accounts.django]);
+        assert!(chit_fund.deposits.contains((accounts.eve, 1)));
+        assert_eq!(chit_fund.total_contributed.get(accounts.eve), Some(100));
+        assert_eq!(chit_fund.collateral.get(accounts.eve), Some(50));
+        assert_eq!(chit_fund.transferred_seats.get(accounts.bob), Some(accounts.eve));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit(), Err(Error::NotParticipant));
+
+        // The admin can approve a transfer outright, but not of a seat that has won.
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.transfer_membership(accounts.frank), Ok(()));
+        assert_eq!(chit_fund.approve_membership_transfer(accounts.charlie), Err(Error::OnlyAdminCanApproveTransfer));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.approve_membership_transfer(accounts.charlie), Ok(()));
+        assert!(chit_fund.is_member(accounts.frank));
+        assert_eq!(chit_fund.approve_membership_transfer(accounts.charlie), Err(Error::NoSeatTransfer));
+        let mut info = chit_fund.members.get(accounts.django).unwrap();
+        info.wins = 1;
+        info.has_won = true;
+        chit_fund.members.insert(accounts.django, &info);
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.transfer_membership(accounts.bob), Err(Error::SeatAlreadyWon));
+    }
+
+    #[ink::test]
+    fn test_error_codes_are_stable() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        // Frontends match on these codes, so they must not move.
+        assert_eq!(Error::ParticipantsAlreadyFull.code(), 0);
+        assert_eq!(Error::NotParticipant.code(), 7);
+        assert_eq!(Error::ReentrantCall.code(), 85);
+        assert_eq!(Error::ReentrantCall.encode(), vec![85]);
+        assert_eq!(Error::from_code(84), Some(Error::QuorumNotReached));
+        assert_eq!(Error::from_code(u8::MAX), None);
+        assert_eq!(
+            chit_fund.describe_error(Error::AlreadyJoined.code()),
+            Some(String::from("The account is already a member."))
+        );
+        assert_eq!(chit_fund.describe_error(u8::MAX), None);
+        let mut code = 0;
+        while let Some(error) = Error::from_code(code) {
+            assert_eq!(error.code(), code);
+            assert!(!error.description().is_empty());
+            code += 1;
+        }
+    }
+
+    #[ink::test]
+    fn test_catch_up_after_downtime() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 1_000 }, collateral: 50, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(50);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        // Nobody pays while the fund sits idle for two rounds.
+        for now in [1_000, 2_000] {
+            test::set_block_timestamp::<DefaultEnvironment>(now);
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        }
+        assert_eq!(chit_fund.get_current_round(), 3);
+        assert_eq!(chit_fund.missed_rounds.get(accounts.bob), Some(2));
+        assert_eq!(chit_fund.arrears_of(accounts.bob), 150);
+        assert_eq!(chit_fund.collateral.get(accounts.bob), Some(0));
+
+        // Without a catch-up only the open round can be paid.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(300);
+        assert_eq!(chit_fund.deposit_many(vec![2, 3]), Err(Error::RoundNotPayable));
+        test::set_block_timestamp::<DefaultEnvironment>(8_000);
+        assert!(chit_fund.is_round_expired());
+        assert_eq!(chit_fund.catch_up_round(1), Err(Error::OnlyAdminCanCatchUp));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.catch_up_round(4), Err(Error::RoundNotPayable));
+        assert_eq!(chit_fund.catch_up_round(1), Ok(()));
+        assert!(!chit_fund.is_round_expired());
+
+        // Bob settles every round in one go, each booked to its own round.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit_many(vec![]), Err(Error::RoundNotPayable));
+        test::set_value_transferred::<DefaultEnvironment>(299);
+        assert_eq!(chit_fund.deposit_many(vec![3, 1, 2]), Err(Error::IncorrectContributionAmount));
+        test::set_value_transferred::<DefaultEnvironment>(300);
+        assert_eq!(chit_fund.deposit_many(vec![3, 1, 2]), Ok(()));
+        for round in 1..=3 {
+            assert!(chit_fund.deposits.contains((accounts.bob, round)));
+        }
+        assert_eq!(chit_fund.missed_rounds.get(accounts.bob), Some(0));
+        assert_eq!(chit_fund.arrears_of(accounts.bob), 0);
+        assert_eq!(chit_fund.collateral.get(accounts.bob), Some(50));
+        assert_eq!(chit_fund.total_contributed.get(accounts.bob), Some(300));
+        assert_eq!(chit_fund.pot, 250);
+        assert_eq!(chit_fund.deposit_many(vec![3]), Err(Error::AlreadyDeposited));
+
+        // The catch-up ends with the round.
+        test::set_block_timestamp::<DefaultEnvironment>(9_000);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.catch_up_since, None);
+    }
+
+    #[ink::test]
+    fn test_deposits_closed_between_cycles() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert!(!chit_fund.deposits_open());
+        start_fund(&mut chit_fund);
+        assert!(chit_fund.deposits_open());
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert!(!chit_fund.deposits_open());
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Err(Error::DepositsClosed));
+        assert_eq!(chit_fund.deposit_many(vec![2]), Err(Error::DepositsClosed));
+        assert_eq!(chit_fund.pot, 0);
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        assert!(chit_fund.deposits_open());
+        assert_eq!(chit_fund.pause(), Ok(()));
+        assert!(!chit_fund.deposits_open());
+    }
+
+    #[ink::test]
+    fn test_sweep_surplus_leaves_member_funds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { collateral: 50, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        let contract = test::callee::<DefaultEnvironment>();
+        test::set_account_balance::<DefaultEnvironment>(contract, 0);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(50);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(60);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        // Off-chain the payments are not credited to the contract, so credit them by hand.
+        test::set_account_balance::<DefaultEnvironment>(contract, 260);
+        assert_eq!(chit_fund.held, 260);
+        assert_eq!(chit_fund.surplus(), 0);
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.sweep_surplus(accounts.alice), Err(Error::NoSurplus));
+
+        // Someone sends 40 straight to the contract. Only that can be swept.
+        test::set_account_balance::<DefaultEnvironment>(contract, 300);
+        assert_eq!(chit_fund.surplus(), 40);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.sweep_surplus(accounts.bob), Err(Error::OnlyAdminCanWithdraw));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.django).unwrap();
+        assert_eq!(chit_fund.sweep_surplus(accounts.django), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.django).unwrap();
+        assert_eq!(after - before, 40);
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(contract), Ok(260));
+        assert_eq!(chit_fund.held, 260);
+        assert_eq!(chit_fund.sweep_surplus(accounts.django), Err(Error::NoSurplus));
+
+        // Paying out lowers what is held in step with the balance.
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.leave(), Ok(()));
+        assert_eq!(chit_fund.held, 150);
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(contract), Ok(150));
+        assert_eq!(chit_fund.surplus(), 0);
+    }
+
+    #[ink::test]
+    fn test_member_statement() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig {
+            commission_bps: 1_000,
+            late_fee_bps: 1_000,
+            collateral: 50,
+            paid_up_winners_only: true,
+            ..Default::default()
+        };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(50);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        // Charlie misses the first round, which his collateral covers, and cures it.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        test::set_value_transferred::<DefaultEnvironment>(60);
+        assert_eq!(chit_fund.cure_default(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.draw(), Ok(()));
+
+        let (_, winner) = chit_fund.get_all_winners()[0];
+        let statement = chit_fund.get_member_statement(winner);
+        assert_eq!(statement.won_round, Some(1));
+        // Bob's contribution and Charlie's slashed collateral; the cure went to the next pot.
+        assert_eq!((statement.prize, statement.prize_after_commission), (150, 135));
+
+        let charlie = chit_fund.get_member_statement(accounts.charlie);
+        assert!(charlie.is_member);
+        assert_eq!(charlie.total_contributed, 50);
+        assert_eq!(charlie.penalties_paid, 10);
+        assert_eq!(charlie.collateral_held, 0);
+        assert_eq!((charlie.rounds_defaulted, charlie.rounds_outstanding, charlie.arrears), (1, 0, 0));
+        let bob = chit_fund.get_member_statement(accounts.bob);
+        assert_eq!((bob.total_contributed, bob.penalties_paid, bob.rounds_defaulted), (100, 0, 0));
+        assert_eq!(bob.collateral_held, 50);
+        assert!(!chit_fund.get_member_statement(accounts.eve).is_member);
+    }
+
+    #[ink::test]
+    fn test_rounding_and_remainder_policy() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        // Each policy places the two units left over from splitting a discount of 59.
+        for (instance, policy) in [RemainderPolicy::Winner, RemainderPolicy::Pot, RemainderPolicy::Organizer]
+            .into_iter()
+            .enumerate()
+        {
+            // A fresh contract account per fund keeps their storage apart.
+            test::set_callee::<DefaultEnvironment>(AccountId::from([0xf0 + instance as u8; 32]));
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            let config = FundConfig {
+                mode: FundMode::Auction,
+                commission_bps: 250,
+                commission_rounding: Rounding::Up,
+                remainder_policy: policy,
+                decimals: 2,
+                ..Default::default()
+            };
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+            assert_eq!(chit_fund.to_base_units(3), Some(300));
+            assert_eq!(chit_fund.set_monthly_contribution_units(1), Ok(()));
+            assert_eq!(chit_fund.monthly_contribution, 100);
+            test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 300);
+            for member in members {
+                test::set_caller::<DefaultEnvironment>(member);
+                test::set_value_transferred::<DefaultEnvironment>(0);
+                assert_eq!(chit_fund.join(), Ok(()));
+            }
+            start_fund(&mut chit_fund);
+            for member in members {
+                test::set_caller::<DefaultEnvironment>(member);
+                test::set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            test::set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(chit_fund.bid(241), Ok(()));
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.close_bidding(), Ok(()));
+
+            let record = chit_fund.get_round_history(1).unwrap();
+            let (prize, carried, organizer) = match policy {
+                RemainderPolicy::Winner => (243, 0, 0),
+                RemainderPolicy::Pot => (241, 2, 0),
+                RemainderPolicy::Organizer => (241, 0, 2),
+            };
+            assert_eq!(record.prize, prize);
+            // 2.5% of the prize, rounded up to a whole unit.
+            assert_eq!(record.commission, 7);
+            assert_eq!(chit_fund.total_amount, carried);
+            assert_eq!(chit_fund.commission_balance, record.commission + organizer);
+            let dividends: Balance = members.iter().map(|member| chit_fund.dividends.get(member).unwrap()).sum();
+            assert_eq!(dividends + prize + carried + organizer, 300);
+        }
+    }
+
+    #[ink::test]
+    fn test_min_reputation_needs_a_registry() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { min_reputation: Some(0), ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        // Without a registry no reputation can be checked, so joining fails closed.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Err(Error::RegistryUnavailable));
+        assert_eq!(chit_fund.member_count, 0);
+    }
+
+    #[ink::test]
+    fn test_block_number_schedule() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { schedule: ScheduleSource::BlockNumber { blocks_per_round: 3 }, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        test::set_block_timestamp::<DefaultEnvironment>(60_000);
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.get_schedule(), ScheduleSource::BlockNumber { blocks_per_round: 3 });
+
+        // The deadline is counted in blocks; the timestamp is estimated from the block time.
+        let start = chit_fund.round_start;
+        assert_eq!(chit_fund.round_deadline(), Some(start + 3));
+        assert_eq!(
+            chit_fund.round_deadlines(),
+            Some(Deadline { block_number: start as u32 + 3, timestamp: 60_000 + 3 * EXPECTED_BLOCK_TIME })
+        );
+        // Time passing without new blocks does not end the round.
+        test::set_block_timestamp::<DefaultEnvironment>(1_000_000);
+        assert!(!chit_fund.is_round_expired());
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Err(Error::RoundNotExpired));
+        for _ in 0..3 {
+            test::advance_block::<DefaultEnvironment>();
+        }
+        assert!(chit_fund.is_round_expired());
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_timestamp_schedule_deadlines() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config =
+            FundConfig { schedule: ScheduleSource::Timestamp { millis_per_round: 10 * EXPECTED_BLOCK_TIME }, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        let block = ink::env::block_number::<DefaultEnvironment>();
+        let deadline = chit_fund.round_start + 10 * EXPECTED_BLOCK_TIME;
+        assert_eq!(chit_fund.round_deadline(), Some(deadline));
+        assert_eq!(chit_fund.round_deadlines(), Some(Deadline { block_number: block + 10, timestamp: deadline }));
+    }
+
+    #[ink::test]
+    fn test_dispute_window_holds_payout() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { dispute_period: 5, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+
+        // The winner is announced but not paid until the window closes.
+        let pending = chit_fund.get_pending_payout().unwrap();
+        assert_eq!((pending.round, pending.prize), (1, 300));
+        assert!(!chit_fund.has_won(pending.winner));
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::PayoutPending));
+        assert_eq!(chit_fund.draw(), Err(Error::PayoutPending));
+        assert_eq!(chit_fund.finalize_payout(), Err(Error::DisputeWindowOpen));
+
+        let others: Vec<AccountId> = members.into_iter().filter(|member| *member != pending.winner).collect();
+        let reason = Hash::from([0x07; 32]);
+        test::set_caller::<DefaultEnvironment>(pending.winner);
+        assert_eq!(chit_fund.raise_dispute(reason), Err(Error::CannotDisputeOwnWin));
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.raise_dispute(reason), Err(Error::NotParticipant));
+        test::set_caller::<DefaultEnvironment>(others[0]);
+        assert_eq!(chit_fund.support_dispute(), Err(Error::NoDispute));
+        assert_eq!(chit_fund.raise_dispute(reason), Ok(()));
+        assert_eq!(chit_fund.raise_dispute(reason), Err(Error::DisputeAlreadyRaised));
+        assert_eq!(chit_fund.support_dispute(), Err(Error::AlreadyVoted));
+        assert!(chit_fund.get_pending_payout().is_some());
+
+        // A majority of the other members upholds the dispute and the round is drawn again.
+        test::set_caller::<DefaultEnvironment>(others[1]);
+        assert_eq!(chit_fund.support_dispute(), Ok(()));
+        let dispute = chit_fund.get_dispute(1, pending.winner).unwrap();
+        assert_eq!((dispute.raised_by, dispute.reason_hash, dispute.votes, dispute.upheld), (others[0], reason, 2, true));
+        assert_eq!(chit_fund.get_pending_payout(), None);
+        assert_eq!(chit_fund.raise_dispute(reason), Err(Error::NoPendingPayout));
+
+        test::advance_block::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let redrawn = chit_fund.get_pending_payout().unwrap();
+        advance_blocks(6);
+        let challenger = members.into_iter().find(|member| *member != redrawn.winner).unwrap();
+        test::set_caller::<DefaultEnvironment>(challenger);
+        assert_eq!(chit_fund.raise_dispute(reason), Err(Error::DisputeWindowClosed));
+
+        // Once the window has closed without an upheld dispute the winner is paid.
+        test::set_caller::<DefaultEnvironment>(redrawn.winner);
+        assert_eq!(chit_fund.finalize_payout(), Ok(()));
+        assert!(chit_fund.has_won(redrawn.winner));
+        assert_eq!(chit_fund.get_round_history(1).map(|record| record.winner), Some(redrawn.winner));
+        assert_eq!(chit_fund.finalize_payout(), Err(Error::NoPendingPayout));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+    }
+
+    #[ink::test]
+    fn test_terminate_contract_sends_dust_to_treasury() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_callee::<DefaultEnvironment>(AccountId::from([0xfe; 32]));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { treasury: Some(accounts.eve), ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!(chit_fund.terminate_contract(), Err(Error::OnlyAdminCanTerminate));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.terminate_contract(), Err(Error::FundStillRunning));
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+
+        chit_fund.dividends.insert(accounts.bob, &5);
+        assert_eq!(chit_fund.terminate_contract(), Err(Error::ClaimsOutstanding));
+        chit_fund.dividends.remove(accounts.bob);
+
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 7);
+        let terminate = move || {
+            let _ = chit_fund.terminate_contract();
+        };
+        test::assert_contract_termination::<DefaultEnvironment, _>(terminate, accounts.eve, 7);
+    }
+
+    #[ink::test]
+    fn test_receipt_id_carries_the_payment() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let fund = AccountId::from([0xfe; 32]);
+        let crate::psp34::Id::Bytes(bytes) = crate::psp34::receipt_id(fund, accounts.bob, 3, 100) else {
+            panic!("receipt ids are bytes");
+        };
+        let decoded = <(AccountId, AccountId, u32, Balance)>::decode(&mut &bytes[..]);
+        assert_eq!(decoded, Ok((fund, accounts.bob, 3, 100)));
+        assert_ne!(crate::psp34::receipt_id(fund, accounts.bob, 4, 100), crate::psp34::receipt_id(fund, accounts.bob, 3, 100));
+    }
+
+    #[ink::test]
+    fn test_rotation_pays_in_fixed_order() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { mode: FundMode::Rotation, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        let order = vec![accounts.django, accounts.bob, accounts.charlie];
+        assert_eq!(chit_fund.set_rotation(order.clone()), Err(Error::OnlyAdminCanSetRotation));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.set_rotation(vec![accounts.bob, accounts.bob, accounts.charlie]), Err(Error::InvalidRotation));
+        assert_eq!(chit_fund.set_rotation(vec![accounts.bob, accounts.charlie]), Err(Error::InvalidRotation));
+        assert_eq!(chit_fund.set_rotation(order.clone()), Ok(()));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.set_rotation(order.clone()), Err(Error::FundAlreadyStarted));
+        assert_eq!(chit_fund.get_rotation(), order);
+
+        for expected in order {
+            assert_eq!(chit_fund.next_in_rotation(), Some(expected));
+            for member in members {
+                test::set_caller::<DefaultEnvironment>(member);
+                test::set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(chit_fund.deposit(), Ok(()));
+            }
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.draw(), Ok(()));
+            assert!(chit_fund.has_won(expected));
+            if chit_fund.get_phase() != FundPhase::Completed {
+                assert_eq!(chit_fund.begin_cycle(), Ok(()));
+            }
+        }
+        assert_eq!(chit_fund.next_in_rotation(), None);
+        assert_eq!(chit_fund.get_phase(), FundPhase::Completed);
+    }
+
+    #[ink::test]
+    fn test_rotation_defaults_to_join_order() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { mode: FundMode::Rotation, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        for member in [accounts.charlie, accounts.bob] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.get_rotation(), vec![accounts.charlie, accounts.bob, accounts.django]);
+        assert_eq!(chit_fund.next_in_rotation(), Some(accounts.charlie));
+    }
+
+    #[ink::test]
+    fn test_rotation_must_cover_late_joiners() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { mode: FundMode::Rotation, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.set_rotation(vec![accounts.charlie, accounts.bob]), Ok(()));
+        // A member joining after the order was set invalidates it until it is set again.
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.join(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.start_fund(), Err(Error::InvalidRotation));
+        let order = vec![accounts.django, accounts.charlie, accounts.bob];
+        assert_eq!(chit_fund.set_rotation(order.clone()), Ok(()));
+        assert_eq!(chit_fund.start_fund(), Ok(()));
+        assert_eq!(chit_fund.get_rotation(), order);
+    }
+
+    #[ink::test]
+    fn test_set_parameters_before_start() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        let schedule = ScheduleSource::BlockNumber { blocks_per_round: 100 };
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        assert_eq!(chit_fund.set_parameters(3, 200, schedule, 300), Err(Error::OnlyAdminCanSetParameters));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.set_parameters(1, 200, schedule, 300), Err(Error::InvalidParticipantLimit));
+        assert_eq!(chit_fund.set_parameters(3, 0, schedule, 300), Err(Error::InvalidContributionAmount));
+        assert_eq!(chit_fund.set_parameters(3, 200, schedule, 20_000), Ok(()));
+        assert_eq!(chit_fund.max_participants, 3);
+        assert_eq!(chit_fund.get_monthly_contribution(), 200);
+        assert_eq!(chit_fund.get_schedule(), schedule);
+        assert_eq!(chit_fund.commission_bps, MAX_BPS);
+
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.set_parameters(3, 100, schedule, 0), Err(Error::ParametersLocked));
+    }
+
+    #[ink::test]
+    fn test_set_parameters_locked_once_collateral_is_held() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { collateral: 50, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        let schedule = chit_fund.get_schedule();
+        assert_eq!(chit_fund.set_parameters(4, 100, schedule, 0), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        assert_eq!(chit_fund.join(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.set_parameters(4, 200, schedule, 0), Err(Error::ParametersLocked));
+    }
+
+    #[ink::test]
+    fn test_admin_participation_is_explicit() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        assert_eq!(chit_fund.join(), Err(Error::AdminCannotParticipate));
+        assert_eq!(chit_fund.join_on_behalf(accounts.alice), Err(Error::AdminCannotParticipate));
+        assert_eq!(chit_fund.admin_register_members(vec![accounts.bob, accounts.alice]), Err(Error::AdminCannotParticipate));
+
+        // A member cannot take over a fund whose admin may not hold a seat.
+        assert_eq!(chit_fund.join_on_behalf(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.transfer_admin(accounts.bob), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.accept_admin(), Err(Error::AdminCannotParticipate));
+
+        // When the admin participates they join and win on the same terms as anyone else.
+        test::set_callee::<DefaultEnvironment>(AccountId::from([0xfd; 32]));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { admin_participates: true, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 1, 100, config);
+        assert_eq!(chit_fund.join(), Ok(()));
+        start_fund(&mut chit_fund);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert!(chit_fund.has_won(accounts.alice));
+    }
+
+    #[ink::test]
+    fn test_round_snapshot_drops_members_removed_before_draw() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { max_missed_rounds: 1, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        // Charlie never pays and is expelled while the round waits for its draw.
+        for member in [accounts.bob, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.get_round_snapshot(1), Some(RoundSnapshot { members: 3, tickets: 3, expected: 300 }));
+        assert_eq!(chit_fund.expel(accounts.charlie), Ok(()));
+        assert_eq!(chit_fund.get_round_snapshot(1), Some(RoundSnapshot { members: 2, tickets: 2, expected: 200 }));
+
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let record = chit_fund.get_round_history(1).unwrap();
+        assert_eq!(record.prize, 200);
+        assert_eq!(record.snapshot, RoundSnapshot { members: 2, tickets: 2, expected: 200 });
+        assert_eq!(chit_fund.get_round_snapshot(2), None);
+    }
+
+    #[ink::test]
+    fn test_get_contract_info() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let schedule = ScheduleSource::BlockNumber { blocks_per_round: 50 };
+        let config = FundConfig {
+            mode: FundMode::Auction,
+            schedule,
+            contribution_token: Some(accounts.frank),
+            commission_bps: 150,
+            ..Default::default()
+        };
+        let chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        assert_eq!(
+            chit_fund.get_contract_info(),
+            ContractInfo {
+                version: VERSION.into(),
+                mode: FundMode::Auction,
+                schedule,
+                token: Some(accounts.frank),
+                commission_bps: 150,
+                phase: FundPhase::Recruiting,
+            }
+        );
+        assert_eq!(VERSION, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[ink::test]
+    fn test_bonus_paid_to_first_winner() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        test::set_value_transferred::<DefaultEnvironment>(40);
+        assert_eq!(chit_fund.fund_bonus(50), Err(Error::IncorrectBonusAmount));
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        assert_eq!(chit_fund.fund_bonus(50), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.fund_bonus(50), Err(Error::OnlyAdminCanFundBonus));
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        // The bonus does not lock the fund's terms.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let schedule = chit_fund.get_schedule();
+        assert_eq!(chit_fund.set_parameters(5, 100, schedule, 0), Ok(()));
+        start_fund(&mut chit_fund);
+        test::set_value_transferred::<DefaultEnvironment>(50);
+        assert_eq!(chit_fund.fund_bonus(50), Err(Error::BonusFundingClosed));
+
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let record = chit_fund.get_round_history(1).unwrap();
+        assert_eq!((record.prize, record.bonus), (200, 50));
+        assert_eq!(chit_fund.get_bonus_pool(), 0);
+        // The bonus is not counted as part of the prize when refunds are worked out.
+        assert_eq!(chit_fund.prizes_won.get(record.winner), Some(200));
+    }
+
+    #[ink::test]
+    fn test_bonus_split_across_rounds_and_returned_on_cancel() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { bonus_split: BonusSplit::EvenlyAcrossRounds, ..Default::default() };
+        test::set_value_transferred::<DefaultEnvironment>(101);
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        assert_eq!(chit_fund.get_bonus_pool(), 101);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.get_round_history(1).unwrap().bonus, 33);
+        assert_eq!(chit_fund.get_bonus_pool(), 68);
+
+        // What is left of the bonus goes back to the organizer, not into the refund pool.
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+        assert_eq!(chit_fund.get_bonus_pool(), 0);
+        assert_eq!(chit_fund.commission_balance, 68);
+        assert_eq!(chit_fund.refund_pool, 0);
+    }
+
+    #[ink::test]
+    fn test_multi_ticket_member_pays_and_wins_per_ticket() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { max_tickets: 2, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 10_000);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join_with_tickets(3), Err(Error::InvalidTicketCount));
+        assert_eq!(chit_fund.join_with_tickets(2), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.join_with_tickets(2), Err(Error::ParticipantsAlreadyFull));
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!((chit_fund.get_tickets(accounts.bob), chit_fund.ticket_count), (2, 3));
+        assert_eq!(chit_fund.scheduled_prize(), 300);
+
+        // Tickets are laid out in join order, and a ticket that has won is skipped.
+        assert_eq!(chit_fund.select_winner(1), Some(accounts.bob));
+        assert_eq!(chit_fund.select_winner(2), Some(accounts.charlie));
+        start_fund(&mut chit_fund);
+
+        for round in 1..=3u32 {
+            // Bob owes a contribution for each of his two tickets.
+            test::set_caller::<DefaultEnvironment>(accounts.bob);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+            assert!(!chit_fund.has_paid_this_round(accounts.bob));
+            assert_eq!(chit_fund.deposit(), Ok(()));
+            assert!(chit_fund.has_paid_this_round(accounts.bob));
+            test::set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+            test::set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.draw(), Ok(()));
+            assert_eq!(chit_fund.get_round_history(round).unwrap().prize, 300);
+            if round < 3 {
+                assert_eq!(chit_fund.begin_cycle(), Ok(()));
+            }
+        }
+        let winners = chit_fund.get_winners();
+        assert_eq!(winners.iter().filter(|winner| **winner == accounts.bob).count(), 2);
+        assert_eq!(winners.iter().filter(|winner| **winner == accounts.charlie).count(), 1);
+        assert_eq!(chit_fund.prizes_won.get(accounts.bob), Some(600));
+        assert!(chit_fund.has_won(accounts.bob));
+        assert_eq!(chit_fund.phase, FundPhase::Completed);
+    }
+
+    #[ink::test]
+    fn test_draw_reads_eligibility_bitmap() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { paid_up_winners_only: true, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 200, 100, config);
+        let members: Vec<AccountId> = (0..150u8).map(|byte| AccountId::from([byte + 0x20; 32])).collect();
+        for member in &members {
+            test::set_caller::<DefaultEnvironment>(*member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        // The bitmap spans two words; the walk crosses into the second and wraps back.
+        assert!(chit_fund.eligibility.contains(1));
+        assert_eq!(chit_fund.eligible_count(), 150);
+        assert_eq!(chit_fund.select_winner(140), Some(members[140]));
+        chit_fund.eligibility.remove(1);
+        assert_eq!(chit_fund.eligible_count(), 128);
+        assert_eq!(chit_fund.select_winner(140), Some(members[0]));
+        chit_fund.eligibility.insert(1, &(u128::MAX >> (128 - 22)));
+
+        // Every member is eligible, so both ways find the same winner: the bitmap without
+        // loading a single member record, the full walk loading all of them. The bitmap
+        // reads the redraw policy, one word and the winner's index entry.
+        let contract = test::callee::<DefaultEnvironment>();
+        let (reads_before, _) = test::get_contract_storage_rw::<DefaultEnvironment>(&contract);
+        let winner = chit_fund.select_winner(7);
+        let (reads_after, _) = test::get_contract_storage_rw::<DefaultEnvironment>(&contract);
+        let bitmap_reads = reads_after - reads_before;
+        let walked = chit_fund
+            .get_participants()
+            .into_iter()
+            .cycle()
+            .skip(7)
+            .take(150)
+            .find(|account| chit_fund.is_eligible(*account));
+        let (reads_walked, _) = test::get_contract_storage_rw::<DefaultEnvironment>(&contract);
+        assert_eq!(winner, walked);
+        assert_eq!(bitmap_reads, 3);
+        assert!(reads_walked - reads_after >= 150);
+    }
+
+    #[ink::test]
+    fn test_guardian_can_pause_and_cancel_but_not_draw() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { guardian: Some(accounts.eve), ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.draw(), Err(Error::OnlyAdminCanDraw));
+        assert_eq!(chit_fund.withdraw_commission(), Err(Error::OnlyAdminCanWithdraw));
+        assert_eq!(chit_fund.void_round(), Err(Error::RoundStillDrawable));
+        assert_eq!(chit_fund.pause(), Ok(()));
+        assert_eq!(chit_fund.unpause(), Err(Error::OnlyAdminCanPause));
+
+        // The members replace the guardian by majority vote.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        let election = chit_fund.propose_guardian(Some(accounts.frank)).unwrap();
+        assert_eq!(chit_fund.get_guardian(), Some(accounts.eve));
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.vote_guardian(election), Err(Error::NotParticipant));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.vote_guardian(election), Ok(()));
+        assert_eq!(chit_fund.get_guardian(), Some(accounts.frank));
+        assert!(chit_fund.get_guardian_election(election).unwrap().executed);
+
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.cancel_fund(), Err(Error::OnlyAdminCanCancel));
+        test::set_caller::<DefaultEnvironment>(accounts.frank);
+        assert_eq!(chit_fund.cancel_fund(), Ok(()));
+        assert_eq!(chit_fund.phase, FundPhase::Cancelled);
+    }
+
+    #[ink::test]
+    fn test_deposit_batch_splits_payment() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+
+        test::set_caller::<DefaultEnvironment>(accounts.frank);
+        test::set_value_transferred::<DefaultEnvironment>(250);
+        let payroll = vec![(accounts.bob, 100), (accounts.charlie, 100), (accounts.django, 60)];
+        assert_eq!(chit_fund.deposit_batch(payroll), Err(Error::InvalidBatch));
+        assert_eq!(chit_fund.deposit_batch(vec![]), Err(Error::InvalidBatch));
+        assert_eq!(chit_fund.get_pot(), 0);
+
+        // Django's share only covers part of the round.
+        let payroll = vec![(accounts.bob, 100), (accounts.charlie, 100), (accounts.django, 50)];
+        assert_eq!(chit_fund.deposit_batch(payroll), Ok(()));
+        assert!(chit_fund.has_paid_this_round(accounts.bob));
+        assert!(chit_fund.has_paid_this_round(accounts.charlie));
+        assert!(!chit_fund.has_paid_this_round(accounts.django));
+        assert_eq!(chit_fund.get_pot(), 200);
+        assert_eq!(chit_fund.partial_deposits.get((accounts.django, 1)), Some(50));
+        assert_eq!(chit_fund.total_contributed.get(accounts.bob), Some(100));
+
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit_batch(vec![(accounts.bob, 100)]), Err(Error::AlreadyDeposited));
+        assert_eq!(chit_fund.deposit_batch(vec![(accounts.eve, 100)]), Err(Error::NotParticipant));
+    }
+
+    #[ink::test]
+    fn test_set_hook() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        assert_eq!(chit_fund.get_hook(), None);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.set_hook(Some(accounts.frank)), Err(Error::OnlyAdminCanSetHook));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.set_hook(Some(accounts.frank)), Ok(()));
+        assert_eq!(chit_fund.get_hook(), Some(accounts.frank));
+        assert_eq!(chit_fund.set_hook(None), Ok(()));
+        assert_eq!(chit_fund.get_hook(), None);
+    }
+
+    #[ink::test]
+    fn test_preview_draw_matches_settlement() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        let config = FundConfig { mode: FundMode::Auction, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        assert_eq!(chit_fund.preview_draw(), Err(Error::ChitFundNotFinished));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.preview_draw(), Err(Error::NoBids));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.bid(241), Ok(()));
+
+        let preview = chit_fund.preview_draw().unwrap();
+        assert_eq!(
+            preview,
+            DrawPreview {
+                round: 1,
+                winner: accounts.charlie,
+                prize: 243,
+                commission: 0,
+                bonus: 0,
+                payout: 243,
+                dividend_per_ticket: 19,
+                dividends: 57,
+            }
+        );
+        // Previewing leaves the round to be settled.
+        assert_eq!(chit_fund.get_winners(), vec![]);
+        assert_eq!(chit_fund.preview_draw(), Ok(preview.clone()));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(chit_fund.close_bidding(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.charlie).unwrap();
+        assert_eq!(after - before, preview.payout);
+        assert_eq!(chit_fund.dividends.get(accounts.bob), Some(preview.dividend_per_ticket));
+        assert_eq!(chit_fund.preview_draw(), Err(Error::RoundAlreadyDrawn));
+    }
+
+    #[ink::test]
+    fn test_preview_draw_names_the_random_winner() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        let mut chit_fund = ChitFund::new(accounts.alice, 3, 100);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        let preview = chit_fund.preview_draw().unwrap();
+        assert_eq!((preview.prize, preview.payout), (300, 300));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.get_winners(), vec![preview.winner]);
+    }
+
+    #[ink::test]
+    fn test_deposit_for_round_rejects_other_rounds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit_for_round(1), Err(Error::FundNotStarted));
+        start_fund(&mut chit_fund);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit_for_round(2), Err(Error::WrongRound));
+        assert_eq!(chit_fund.deposit_for_round(1), Ok(()));
+        assert_eq!(chit_fund.deposit_for_round(1), Err(Error::AlreadyDeposited));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.deposit(), Ok(()));
+
+        // A payment signed for round 1 that lands after the round has closed is refused
+        // instead of counting towards round 2.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit_for_round(1), Err(Error::WrongRound));
+        assert_eq!(chit_fund.deposit_for_round(2), Err(Error::WrongRound));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.deposit_for_round(1), Err(Error::WrongRound));
+        assert_eq!(chit_fund.deposit_for_round(2), Ok(()));
+        assert!(chit_fund.has_paid(accounts.bob, 2));
+    }
+
+    #[ink::test]
+    fn test_migration_runs_in_chunks() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        let mut chit_fund = ChitFund::new(accounts.alice, 10, 100);
+        let members: Vec<AccountId> = (0..5).map(|i| AccountId::from([0x20 + i; 32])).collect();
+        for member in &members {
+            test::set_caller::<DefaultEnvironment>(*member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        let bitmap = chit_fund.eligibility.get(0);
+        assert_eq!(bitmap, Some(0b11111));
+
+        // Code at version 1 left the eligibility bitmap empty.
+        chit_fund.storage_version = 1;
+        chit_fund.eligibility.remove(0);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.join(), Err(Error::MigrationPending));
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.migrate(2), Ok(false));
+        assert_eq!(chit_fund.migration_cursor.get(), Some(2));
+        assert_eq!(chit_fund.migrate(2), Ok(false));
+        assert_eq!(chit_fund.storage_version, 1);
+        assert_eq!(chit_fund.migrate(2), Ok(true));
+        assert_eq!(chit_fund.storage_version, STORAGE_VERSION);
+        assert_eq!(chit_fund.migration_cursor.get(), Some(0));
+        assert_eq!(chit_fund.eligibility.get(0), bitmap);
+        assert_eq!(chit_fund.migrate(2), Err(Error::AlreadyMigrated));
+
+        // There is no step from a version this code never wrote.
+        chit_fund.storage_version = 0;
+        assert_eq!(chit_fund.migrate(0), Err(Error::UnsupportedStorageVersion));
+    }
+
+    #[ink::test]
+    fn test_sealed_winner_claims_prize() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie];
+        let config = FundConfig { sealed_claim_period: 5, dispute_period: 5, ..Default::default() };
+        let mut chit_fund = fund_with_members(2, config, &members);
+        start_fund(&mut chit_fund);
+        let secrets = [[0x11; 32], [0x22; 32]];
+        let commitment = |round: u32| {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(round, &secrets[round as usize - 1]), &mut output);
+            Hash::from(output)
+        };
+        let play_round = |chit_fund: &mut ChitFund, round: u32| {
+            pay_round(chit_fund, &members);
+            assert_eq!(chit_fund.end_cycle(), Ok(()));
+            assert_eq!(chit_fund.draw(), Err(Error::RoundSecretMissing));
+            call_as(accounts.bob, 0);
+            assert_eq!(chit_fund.commit_round_secret(round, commitment(round)), Err(Error::OnlyAdminCanDraw));
+            call_as(accounts.alice, 0);
+            assert_eq!(chit_fund.commit_round_secret(round, commitment(round)), Ok(()));
+            assert_eq!(chit_fund.preview_draw().unwrap().winner, AccountId::from([0; 32]));
+            assert_eq!(chit_fund.draw(), Ok(()));
+        };
+
+        // The draw is not held for disputes and names nobody.
+        play_round(&mut chit_fund, 1);
+        assert_eq!(chit_fund.get_pending_payout(), None);
+        assert_eq!(chit_fund.get_winners(), vec![]);
+        assert_eq!(chit_fund.get_round_history(1).unwrap().winner, AccountId::from([0; 32]));
+        let record = chit_fund.get_draw_record(1).unwrap();
+        assert_eq!((record.winner, record.start_index, record.winner_index), (AccountId::from([0; 32]), 0, 0));
+        let sealed = chit_fund.get_sealed_prize(1).unwrap();
+        assert_eq!((sealed.commitment, sealed.amount), (commitment(1), 200));
+        assert_eq!(chit_fund.commit_round_secret(1, commitment(1)), Err(Error::RoundAlreadyDrawn));
+
+        // Membership records still show who has won.
+        let winner = members.into_iter().find(|member| chit_fund.has_won(*member)).unwrap();
+        let loser = members.into_iter().find(|member| *member != winner).unwrap();
+        call_as(winner, 0);
+        assert_eq!(chit_fund.claim_sealed_prize(1, secrets[1]), Err(Error::WrongClaimSecret));
+        call_as(loser, 0);
+        assert_eq!(chit_fund.claim_sealed_prize(1, secrets[0]), Err(Error::NoPrizeToClaim));
+        call_as(winner, 0);
+        let before = test::get_account_balance::<DefaultEnvironment>(winner).unwrap();
+        assert_eq!(chit_fund.claim_sealed_prize(1, secrets[0]), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(winner).unwrap();
+        assert_eq!(after - before, 200);
+        assert_eq!(chit_fund.get_winners(), vec![winner]);
+        assert_eq!(chit_fund.get_round_history(1).unwrap().winner, winner);
+        assert_eq!(chit_fund.claim_sealed_prize(1, secrets[0]), Err(Error::NoPrizeToClaim));
+
+        // A prize left unclaimed past its period is shared between the members.
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        play_round(&mut chit_fund, 2);
+        // The fund has completed, but cannot be wound up while the prize is unclaimed.
+        assert_eq!(chit_fund.get_phase(), FundPhase::Completed);
+        assert_eq!(chit_fund.terminate_contract(), Err(Error::ClaimsOutstanding));
+        assert_eq!(chit_fund.forfeit_sealed_prize(2), Err(Error::SealedPrizeNotExpired));
+        advance_blocks(6);
+        call_as(loser, 0);
+        assert_eq!(chit_fund.claim_sealed_prize(2, secrets[1]), Err(Error::SealedPrizeExpired));
+        assert_eq!(chit_fund.forfeit_sealed_prize(2), Ok(()));
+        for member in members {
+            assert_eq!(chit_fund.dividends.get(member), Some(100));
+        }
+        assert_eq!(chit_fund.get_sealed_prize(2), None);
+    }
+
+    #[ink::test]
+    fn test_reclaim_if_draw_never_happens() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        let config = FundConfig { escrow_timeout: 3, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in [accounts.bob, accounts.charlie] {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.escrow_deadline(), Some(3));
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.reclaim_if_expired(), Err(Error::EscrowNotExpired));
+        advance_blocks(4);
+        // The admin can no longer draw the round once it has expired.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.draw(), Err(Error::EscrowExpired));
+
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.reclaim_if_expired(), Err(Error::NothingToRefund));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(chit_fund.reclaim_if_expired(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(after - before, 100);
+        assert!(chit_fund.is_round_voided(1));
+        assert_eq!(chit_fund.reclaim_if_expired(), Err(Error::NothingToRefund));
+
+        // Charlie's contribution stays theirs after the fund moves on.
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        assert_eq!(chit_fund.refund_due(accounts.charlie), 100);
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.claim_refund(), Ok(()));
+        assert_eq!(chit_fund.total_amount, 0);
     }
-}
 
-//     #[test]
-//     fn test_join() {
-//         let admin = random_account_id();
-//         let max_participants = 5;
-//         let monthly_contribution = 100;
-//         let mut chit_fund = ChitFund::new(admin, max_participants, monthly_contribution);
-
-//         // Test successful join.
-//         let participant = random_account_id();
-//         chit_fund.join();
-//         assert_eq!(chit_fund.participants, vec![participant]);
-
-//         // Test join after participants are full.
-//         for i in 0..max_participants {
-//             let participant = random_account_id();
-//             assert_eq!(chit_fund.participants.len() as u32, i + 1);
-//         }
-//         let participant = random_account_id();
-//         // ink::env::debug_println!("got a call from {:?}", chit_fund.join());
-
-//         assert_eq!(chit_fund.join(), Err(Error::ParticipantsAlreadyFull));
-//         // ink::env::debug_println!("value: {}", chit_fund.join().try_into().unwrap());
-
-//         // Test join after chit fund is finished.
-//         chit_fund.finished = true;
-//         let participant = random_account_id();
-//         assert_eq!(chit_fund.join(), Err(Error::ChitFundHasFinished));
-
-//         // Test join when already joined.
-//         let participant = chit_fund.participants[0];
-//         assert_eq!(chit_fund.join(), Err(Error::AlreadyJoined));
-//     }
-
-//     #[test]
-//     fn test_begin_cycle() {
-//         let admin = random_account_id();
-//         let max_participants = 5;
-//         let monthly_contribution = 100;
-//         let mut chit_fund = ChitFund::new(admin, max_participants, monthly_contribution);
-
-//         // Test successful begin cycle.
-//         chit_fund.finished = true;
-//         chit_fund.total_amount = 500;
-//         chit_fund.begin_cycle();
-//         assert_eq!(chit_fund.pot, 0);
-//         assert_eq!(chit_fund.finished, false);
-
-//         // Test begin cycle with non-admin caller.
-//         let non_admin = random_account_id();
-//         assert_eq!(chit_fund.begin_cycle(), Err(Error::OnlyOwnerCanBeginCycle));
-
-//         // Test begin cycle with chit fund not finished.
-//         chit_fund.finished = false;
-//         assert_eq!(chit_fund.begin_cycle(), Err(Error::ChitFundNotFinished));
-//     }
-// }
+    #[ink::test]
+    fn test_advance_against_prize() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        let config = FundConfig { max_advance_bps: 2_000, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 1_000);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        // At most 20% of the 300 prize.
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.request_advance(61), Err(Error::InvalidAdvanceAmount));
+        assert_eq!(chit_fund.request_advance(50), Ok(()));
+        assert_eq!(chit_fund.vote_advance(accounts.bob), Err(Error::CannotVoteOnOwnAdvance));
+        test::set_caller::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(chit_fund.vote_advance(accounts.django), Err(Error::NoAdvanceRequest));
+        assert_eq!(chit_fund.vote_advance(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.vote_advance(accounts.bob), Err(Error::AlreadyVoted));
+        // The second vote is a majority, but there is no commission to lend from yet.
+        test::set_caller::<DefaultEnvironment>(accounts.django);
+        assert_eq!(chit_fund.vote_advance(accounts.bob), Err(Error::InsufficientAdvanceFunds));
+
+        chit_fund.commission_balance = 80;
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(chit_fund.vote_advance(accounts.bob), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.bob).unwrap();
+        assert_eq!(after - before, 50);
+        assert_eq!(chit_fund.commission_balance, 30);
+        assert!(chit_fund.get_advance(accounts.bob).unwrap().paid);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.leave(), Err(Error::AdvanceOutstanding));
+        assert_eq!(chit_fund.transfer_membership(accounts.eve), Err(Error::AdvanceOutstanding));
+        assert_eq!(chit_fund.request_advance(10), Err(Error::AdvanceOutstanding));
+
+        // The advance only comes out of bob's own prize.
+        start_fund(&mut chit_fund);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        let winner = chit_fund.preview_draw().unwrap().winner;
+        let before = test::get_account_balance::<DefaultEnvironment>(winner).unwrap();
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(winner).unwrap();
+        if winner == accounts.bob {
+            assert_eq!(after - before, 250);
+            assert_eq!(chit_fund.get_advance(accounts.bob), None);
+            assert_eq!(chit_fund.commission_balance, 80);
+        } else {
+            assert_eq!(after - before, 300);
+            assert!(chit_fund.get_advance(accounts.bob).is_some());
+        }
+    }
+
+    #[ink::test]
+    fn test_set_payout_preference() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let stablecoin = AccountId::from([0x50; 32]);
+        let preference = PayoutPreference { token: stablecoin, min_rate: 990_000_000_000 };
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        test::set_value_transferred::<DefaultEnvironment>(0);
+        let mut chit_fund = ChitFund::new(accounts.alice, 5, 100);
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.set_payout_preference(Some(preference.clone())), Err(Error::NotParticipant));
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!(chit_fund.set_payout_preference(Some(preference.clone())), Err(Error::NoDexRouter));
+
+        let token = AccountId::from([0x51; 32]);
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { dex_router: Some(AccountId::from([0x52; 32])), contribution_token: Some(token), ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 5, 100, config);
+        // Joining a token fund calls the token, so bob is enrolled directly.
+        chit_fund.members.insert(accounts.bob, &MemberInfo { index: 0, tickets: 1, wins: 0, has_won: false });
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        let same_token = PayoutPreference { token, min_rate: RATE_SCALE };
+        assert_eq!(chit_fund.set_payout_preference(Some(same_token)), Err(Error::InvalidPayoutPreference));
+        assert_eq!(chit_fund.set_payout_preference(Some(preference.clone())), Ok(()));
+        assert_eq!(chit_fund.get_payout_preference(accounts.bob), Some(preference));
+        assert_eq!(chit_fund.set_payout_preference(None), Ok(()));
+        assert_eq!(chit_fund.get_payout_preference(accounts.bob), None);
+    }
+
+    #[ink::test]
+    fn test_join() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mut chit_fund = fund_with_members(2, FundConfig::default(), &[accounts.bob]);
+        assert_eq!(chit_fund.get_participants(), vec![accounts.bob]);
+        assert_eq!(chit_fund.join(), Err(Error::AdminCannotParticipate));
+        call_as(accounts.bob, 0);
+        assert_eq!(chit_fund.join(), Err(Error::AlreadyJoined));
+
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.pause(), Ok(()));
+        call_as(accounts.charlie, 0);
+        assert_eq!(chit_fund.join(), Err(Error::ContractPaused));
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.unpause(), Ok(()));
+        call_as(accounts.charlie, 0);
+        assert_eq!(chit_fund.join(), Ok(()));
+        call_as(accounts.django, 0);
+        assert_eq!(chit_fund.join(), Err(Error::ParticipantsAlreadyFull));
+    }
+
+    #[ink::test]
+    fn test_begin_cycle() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie];
+        let mut chit_fund = fund_with_members(2, FundConfig::default(), &members);
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::FundNotStarted));
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::ChitFundNotFinished));
+
+        pay_round(&mut chit_fund, &members);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        call_as(accounts.bob, 0);
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::OnlyOwnerCanBeginCycle));
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        assert_eq!((chit_fund.current_round, chit_fund.pot), (2, 0));
+        assert_eq!(chit_fund.phase, FundPhase::ContributionOpen);
+        assert_eq!(chit_fund.begin_cycle(), Err(Error::ChitFundNotFinished));
+    }
+
+    #[ink::test]
+    fn test_redraw_policies() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        let mut chit_fund = fund_with_members(3, FundConfig { redraw_policy: RedrawPolicy::Rehash, ..Default::default() }, &members);
+        start_fund(&mut chit_fund);
+        pay_round(&mut chit_fund, &members);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        // The draw records the policy and how many tickets it tried.
+        let record: DrawRecord = chit_fund.get_draw_record(1).unwrap();
+        assert_eq!(record.redraw_policy, RedrawPolicy::Rehash);
+        assert_eq!(record.probes, 0);
+
+        // A seed landing on the first winner's ticket has to be redrawn.
+        let winner = record.winner;
+        let seed = chit_fund.members.get(winner).unwrap().index as u64;
+        let next = chit_fund.member_index.get((seed as u32 + 1) % 3).unwrap();
+        let rehashed = chit_fund.select_winner(seed).unwrap();
+        assert_ne!(rehashed, winner);
+
+        chit_fund.redraw_policy.set(&RedrawPolicy::LinearProbe);
+        assert_eq!(chit_fund.select_winner(seed), Some(next));
+
+        chit_fund.redraw_policy.set(&RedrawPolicy::DeferToNextBlock);
+        assert_eq!(chit_fund.select_winner(seed), None);
+        assert_eq!(chit_fund.select_winner(seed + 1), Some(next));
+    }
+
+    #[ink::test]
+    fn test_fund_summary() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        let config = FundConfig {
+            commission_bps: 1_000,
+            schedule: ScheduleSource::BlockNumber { blocks_per_round: 10 },
+            paid_up_winners_only: true,
+            ..Default::default()
+        };
+        let mut chit_fund = fund_with_members(3, config, &members);
+        assert_eq!(chit_fund.get_fund_summary().expected_completion, None);
+        start_fund(&mut chit_fund);
+        let deadline = chit_fund.round_deadline().unwrap();
+        let summary = chit_fund.get_fund_summary();
+        assert_eq!(summary.rounds_remaining, 3);
+        assert_eq!(summary.expected_completion.map(|completion| completion.block_number as u64), Some(deadline + 20));
+
+        // Django misses the first round.
+        pay_round(&mut chit_fund, &members[..2]);
+        advance_blocks(10);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let summary = chit_fund.get_fund_summary();
+        assert_eq!(
+            summary,
+            FundSummary {
+                total_collected: 200,
+                total_paid_out: 180,
+                commission_earned: 20,
+                penalties_collected: 0,
+                dividends_distributed: 0,
+                defaults: 1,
+                rounds_completed: 1,
+                rounds_remaining: 2,
+                expected_completion: summary.expected_completion,
+            }
+        );
+    }
+
+    #[ink::test]
+    fn test_join_with_referral() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let config = FundConfig { commission_bps: 1_000, referral_bonus_bps: 1_000, ..Default::default() };
+        let mut chit_fund = fund_with_members(3, config, &[accounts.bob]);
+
+        call_as(accounts.charlie, 0);
+        assert_eq!(chit_fund.join_with_referral(accounts.charlie), Err(Error::InvalidReferrer));
+        assert_eq!(chit_fund.join_with_referral(accounts.eve), Err(Error::InvalidReferrer));
+        assert_eq!(chit_fund.join_with_referral(accounts.bob), Ok(()));
+        call_as(accounts.django, 0);
+        assert_eq!(chit_fund.join(), Ok(()));
+        assert_eq!(chit_fund.get_referrer(accounts.charlie), Some(accounts.bob));
+        assert_eq!(chit_fund.get_referrer(accounts.django), None);
+        assert_eq!(chit_fund.get_referral_count(accounts.bob), 1);
+
+        // The bonus waits for the first draw's commission.
+        call_as(accounts.alice, 0);
+        start_fund(&mut chit_fund);
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        pay_round(&mut chit_fund, &members);
+        assert_eq!(chit_fund.referral_bonuses_due.get().unwrap_or_default().len(), 1);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        assert!(chit_fund.referral_bonuses_due.get().unwrap_or_default().is_empty());
+        assert_eq!(chit_fund.commission_balance, 20);
+        assert_eq!(chit_fund.dividends.get(accounts.bob), Some(10));
+
+        // Only the first contribution earns a bonus.
+        assert_eq!(chit_fund.begin_cycle(), Ok(()));
+        pay_round(&mut chit_fund, &members);
+        assert!(chit_fund.referral_bonuses_due.get().unwrap_or_default().is_empty());
+    }
+
+    #[ink::test]
+    fn test_fallback_refuses_or_records_donations() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mut chit_fund = fund_with_members(2, FundConfig::default(), &[accounts.bob]);
+        call_as(accounts.eve, 50);
+        assert_eq!(chit_fund.fallback(), Err(Error::UnknownMessage));
+        assert_eq!(chit_fund.get_donations(), 0);
+
+        chit_fund.accept_donations.set(&true);
+        call_as(accounts.eve, 0);
+        assert_eq!(chit_fund.fallback(), Err(Error::UnknownMessage));
+        call_as(accounts.eve, 50);
+        assert_eq!(chit_fund.fallback(), Ok(()));
+        assert_eq!(chit_fund.get_donations(), 50);
+        // Donations are not the members' money, so they count as surplus.
+        test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), chit_fund.held + 50);
+        assert_eq!(chit_fund.surplus(), 50);
+    }
+
+    // A fund of three whose first round has been drawn and is in its dispute window,
+    // with the admin as the caller. Returns the winner awaiting payout.
+    fn fund_with_pending_winner() -> (ChitFund, AccountId) {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        let config = FundConfig { dispute_period: 5, max_missed_rounds: 1, ..Default::default() };
+        let mut chit_fund = fund_with_members(3, config, &members);
+        start_fund(&mut chit_fund);
+        pay_round(&mut chit_fund, &members);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        assert_eq!(chit_fund.draw(), Ok(()));
+        let winner = chit_fund.get_pending_payout().unwrap().winner;
+        (chit_fund, winner)
+    }
+
+    #[ink::test]
+    fn test_pending_winner_cannot_leave() {
+        let (mut chit_fund, winner) = fund_with_pending_winner();
+        call_as(winner, 0);
+        assert_eq!(chit_fund.leave(), Err(Error::WinnerPayoutPending));
+        assert!(chit_fund.is_member(winner));
+    }
+
+    #[ink::test]
+    fn test_pending_winner_cannot_be_expelled() {
+        let (mut chit_fund, winner) = fund_with_pending_winner();
+        chit_fund.missed_rounds.insert(winner, &1);
+        assert_eq!(chit_fund.expel(winner), Err(Error::WinnerPayoutPending));
+        assert!(chit_fund.is_member(winner));
+    }
+
+    #[ink::test]
+    fn test_pending_winner_cannot_be_voted_out() {
+        let (mut chit_fund, winner) = fund_with_pending_winner();
+        call_as(test::default_accounts::<DefaultEnvironment>().eve, 0);
+        assert_eq!(chit_fund.execute_expulsion(winner), Err(Error::WinnerPayoutPending));
+        assert!(chit_fund.is_member(winner));
+    }
+
+    #[ink::test]
+    fn test_pending_winner_cannot_transfer_seat() {
+        let (mut chit_fund, winner) = fund_with_pending_winner();
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        call_as(winner, 0);
+        assert_eq!(chit_fund.transfer_membership(accounts.eve), Err(Error::WinnerPayoutPending));
+
+        // Nor can a transfer requested before the draw go through.
+        chit_fund.seat_transfers.insert(
+            winner,
+            &SeatTransfer {
+                new_account: accounts.eve,
+                votes: 0,
+                electorate: 2,
+            },
+        );
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.approve_membership_transfer(winner), Err(Error::WinnerPayoutPending));
+        assert!(chit_fund.is_member(winner));
+        assert!(!chit_fund.is_member(accounts.eve));
+    }
+
+    #[ink::test]
+    fn test_migrate_root_cell_written_by_version_1() {
+        // The root cell as version 1 wrote it: the packed fields it had, in order.
+        #[derive(Encode, Decode)]
+        struct RootV1 {
+            admin: AccountId,
+            max_participants: u32,
+            monthly_contribution: Balance,
+            current_round: u32,
+            pot: Balance,
+            total_amount: Balance,
+            member_count: u32,
+            ticket_count: u32,
+            winner_count: u32,
+            phase: FundPhase,
+            reveal_deadline: u32,
+            randomness_source: RandomnessSource,
+            rounds_completed: u32,
+            mode: FundMode,
+            commission_bps: u16,
+            commission_balance: Balance,
+            schedule: ScheduleSource,
+            round_start: u64,
+            late_fee_bps: u16,
+            max_missed_rounds: u32,
+            penalty_pool: Balance,
+            refund_pool: Balance,
+            refund_basis: Balance,
+            pending_admin: Option<AccountId>,
+            co_admins: Vec<AccountId>,
+            approval_threshold: u32,
+            next_proposal_id: u32,
+            contribution_token: Option<AccountId>,
+            invite_only: bool,
+            collateral_required: Balance,
+            paused: bool,
+            storage_version: u32,
+            min_participants: u32,
+            paid_up_winners_only: bool,
+            reentrancy_lock: bool,
+            payout_schedule: PayoutSchedule,
+            yield_strategy: Option<AccountId>,
+            yield_killed: bool,
+            invested: Balance,
+            poke_bounty: Balance,
+            last_poked_round: u32,
+            terms_hash: Option<Hash>,
+            terms_uri: Vec<u8>,
+            next_amendment_id: u32,
+            grace_period: u64,
+            catch_up_since: Option<u32>,
+            held: Balance,
+            decimals: u8,
+            commission_rounding: Rounding,
+            remainder_policy: RemainderPolicy,
+            blacklist_registry: Option<AccountId>,
+            min_reputation: Option<i32>,
+            penalty_pool_distributed: bool,
+            dispute_period: u32,
+            pending_payout: Option<PendingPayout>,
+            treasury: Option<AccountId>,
+            receipt_collection: Option<AccountId>,
+            rotation: Vec<AccountId>,
+            rotation_position: u32,
+            admin_participates: bool,
+            bonus_pool: Balance,
+            bonus_split: BonusSplit,
+            max_tickets: u32,
+        }
+
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        let fund = fund_with_members(10, FundConfig::default(), &members);
+        let root = RootV1 {
+            admin: fund.admin,
+            max_participants: fund.max_participants,
+            monthly_contribution: fund.monthly_contribution,
+            current_round: fund.current_round,
+            pot: fund.pot,
+            total_amount: fund.total_amount,
+            member_count: fund.member_count,
+            ticket_count: fund.ticket_count,
+            winner_count: fund.winner_count,
+            phase: fund.phase,
+            reveal_deadline: fund.reveal_deadline,
+            randomness_source: fund.randomness_source,
+            rounds_completed: fund.rounds_completed,
+            mode: fund.mode,
+            commission_bps: fund.commission_bps,
+            commission_balance: fund.commission_balance,
+            schedule: fund.schedule,
+            round_start: fund.round_start,
+            late_fee_bps: fund.late_fee_bps,
+            max_missed_rounds: fund.max_missed_rounds,
+            penalty_pool: fund.penalty_pool,
+            refund_pool: fund.refund_pool,
+            refund_basis: fund.refund_basis,
+            pending_admin: fund.pending_admin,
+            co_admins: fund.co_admins.clone(),
+            approval_threshold: fund.approval_threshold,
+            next_proposal_id: fund.next_proposal_id,
+            contribution_token: fund.contribution_token,
+            invite_only: fund.invite_only,
+            collateral_required: fund.collateral_required,
+            paused: fund.paused,
+            storage_version: 1,
+            min_participants: fund.min_participants,
+            paid_up_winners_only: fund.paid_up_winners_only,
+            reentrancy_lock: fund.reentrancy_lock,
+            payout_schedule: fund.payout_schedule,
+            yield_strategy: fund.yield_strategy,
+            yield_killed: fund.yield_killed,
+            invested: fund.invested,
+            poke_bounty: fund.poke_bounty,
+            last_poked_round: fund.last_poked_round,
+            terms_hash: fund.terms_hash,
+            terms_uri: fund.terms_uri.clone(),
+            next_amendment_id: fund.next_amendment_id,
+            grace_period: fund.grace_period,
+            catch_up_since: fund.catch_up_since,
+            held: fund.held,
+            decimals: fund.decimals,
+            commission_rounding: fund.commission_rounding,
+            remainder_policy: fund.remainder_policy,
+            blacklist_registry: fund.blacklist_registry,
+            min_reputation: fund.min_reputation,
+            penalty_pool_distributed: fund.penalty_pool_distributed,
+            dispute_period: fund.dispute_period,
+            pending_payout: fund.pending_payout.clone(),
+            treasury: fund.treasury,
+            receipt_collection: fund.receipt_collection,
+            rotation: fund.rotation.clone(),
+            rotation_position: fund.rotation_position,
+            admin_participates: fund.admin_participates,
+            bonus_pool: fund.bonus_pool,
+            bonus_split: fund.bonus_split,
+            max_tickets: fund.max_tickets,
+        };
+        // Version 1 kept no eligibility bitmap either.
+        fund.eligibility.remove(0);
+        let key = <ChitFund as ink::storage::traits::StorageKey>::KEY;
+        ink::env::set_contract_storage(&key, &root);
+
+        let mut chit_fund: ChitFund = ink::env::get_contract_storage(&key).unwrap().unwrap();
+        assert_eq!(chit_fund.storage_version, 1);
+        assert_eq!(chit_fund.get_participants(), members.to_vec());
+        assert_eq!(chit_fund.eligible_count(), 0);
+        // Nothing that reads the bitmap runs before the conversion, paused or not.
+        call_as(accounts.bob, 0);
+        assert_eq!(chit_fund.leave(), Err(Error::MigrationPending));
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.expel(accounts.charlie), Err(Error::MigrationPending));
+        assert_eq!(chit_fund.execute_expulsion(accounts.charlie), Err(Error::MigrationPending));
+        call_as(accounts.bob, 0);
+        assert_eq!(chit_fund.transfer_membership(accounts.eve), Err(Error::MigrationPending));
+
+        call_as(accounts.alice, 0);
+        assert_eq!(chit_fund.migrate(0), Ok(true));
+        assert_eq!(chit_fund.eligible_count(), 3);
+        assert_eq!(chit_fund.get_guardian(), None);
+
+        // The converted fund writes its root cell back in the layout it was read from.
+        let mut cell = Vec::new();
+        ink::storage::traits::Storable::encode(&chit_fund, &mut cell);
+        assert_eq!(cell, RootV1 { storage_version: STORAGE_VERSION, ..root }.encode());
+    }
+
+    #[ink::test]
+    fn test_renewed_seat_transfer_request_takes_fresh_votes() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django, accounts.frank];
+        let mut chit_fund = fund_with_members(5, FundConfig::default(), &members);
+        call_as(accounts.bob, 0);
+        assert_eq!(chit_fund.transfer_membership(accounts.eve), Ok(()));
+        call_as(accounts.charlie, 0);
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Ok(()));
+
+        // Asking again for the same account replaces the request and its votes, so the
+        // members who backed the first one can back this one too.
+        call_as(accounts.bob, 0);
+        assert_eq!(chit_fund.transfer_membership(accounts.eve), Ok(()));
+        assert_eq!(chit_fund.get_seat_transfer(accounts.bob).unwrap().votes, 0);
+        call_as(accounts.charlie, 0);
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Ok(()));
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Err(Error::AlreadyVoted));
+        call_as(accounts.django, 0);
+        assert_eq!(chit_fund.vote_membership_transfer(accounts.bob), Ok(()));
+        assert!(chit_fund.is_member(accounts.eve));
+        assert!(!chit_fund.is_member(accounts.bob));
+    }
+
+    #[ink::test]
+    fn test_transferred_seat_keeps_its_lowest_bid() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        let config = FundConfig { mode: FundMode::Auction, ..Default::default() };
+        let mut chit_fund = ChitFund::with_config(accounts.alice, 3, 100, config);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(chit_fund.join(), Ok(()));
+        }
+        start_fund(&mut chit_fund);
+        for member in members {
+            test::set_caller::<DefaultEnvironment>(member);
+            test::set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(chit_fund.deposit(), Ok(()));
+        }
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(chit_fund.bid(240), Ok(()));
+
+        // Bob hands his seat to Eve while his bid is the lowest one.
+        assert_eq!(chit_fund.transfer_membership(accounts.eve), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.approve_membership_transfer(accounts.bob), Ok(()));
+
+        // The prize goes to Eve, who holds the seat, and not to Bob.
+        let before = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap_or(0);
+        assert_eq!(chit_fund.close_bidding(), Ok(()));
+        let after = test::get_account_balance::<DefaultEnvironment>(accounts.eve).unwrap();
+        assert_eq!(after - before, 240);
+        assert_eq!(chit_fund.get_winners(), vec![accounts.eve]);
+        assert!(chit_fund.members.get(accounts.eve).unwrap().has_won);
+    }
+
+    #[ink::test]
+    fn test_yield_kill_switch_and_recovery_do_not_need_the_strategy() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mut chit_fund = fund_with_members(2, FundConfig::default(), &[accounts.bob, accounts.charlie]);
+        start_fund(&mut chit_fund);
+        assert_eq!(chit_fund.set_yield_strategy(Some(accounts.frank)), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        test::set_value_transferred::<DefaultEnvironment>(100);
+        assert_eq!(chit_fund.deposit(), Ok(()));
 
+        // Only the organizer or the treasurer can invest or recover the pot.
+        assert_eq!(chit_fund.invest_pot(), Err(Error::OnlyAdminCanManageYield));
+        assert_eq!(chit_fund.recover_invested(), Err(Error::OnlyAdminCanManageYield));
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.grant_role(accounts.eve, Role::Treasurer), Ok(()));
+        test::set_caller::<DefaultEnvironment>(accounts.eve);
+        assert_eq!(chit_fund.recover_invested(), Err(Error::NothingToRecover));
 
+        // With funds out, the kill switch only stops further investing.
+        chit_fund.invested = 100;
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(chit_fund.kill_yield_strategy(), Ok(()));
+        assert!(chit_fund.yield_killed);
+        assert_eq!(chit_fund.invested, 100);
+        assert_eq!(chit_fund.set_yield_strategy(None), Err(Error::YieldStrategyInUse));
+
+        // Funds the strategy failed to return keep it in place until they are recovered.
+        chit_fund.invested = 0;
+        chit_fund.stranded.set(&100);
+        assert_eq!(chit_fund.set_yield_strategy(Some(accounts.frank)), Err(Error::YieldStrategyInUse));
+        chit_fund.yield_killed = false;
+        assert_eq!(chit_fund.invest_pot(), Err(Error::YieldStrategyInUse));
+    }
+
+    #[ink::test]
+    fn test_auction_dividends_follow_the_round_snapshot() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let members = [accounts.bob, accounts.charlie, accounts.django];
+        let config = FundConfig { mode: FundMode::Auction, ..Default::default() };
+        let mut chit_fund = fund_with_members(3, config, &members);
+        start_fund(&mut chit_fund);
+        pay_round(&mut chit_fund, &members);
+        assert_eq!(chit_fund.end_cycle(), Ok(()));
+
+        // Django leaves while the round waits for its winner, taking his ticket with him.
+        call_as(accounts.django, 0);
+        assert_eq!(chit_fund.leave(), Ok(()));
+        call_as(accounts.charlie, 0);
+        assert_eq!(chit_fund.bid(150), Ok(()));
+
+        // The preview and the settlement split the discount over the same two tickets.
+        call_as(accounts.alice, 0);
+        let preview = chit_fund.preview_draw().unwrap();
+        assert_eq!(preview.dividend_per_ticket, 25);
+        assert_eq!(chit_fund.close_bidding(), Ok(()));
+        for member in [accounts.bob, accounts.charlie] {
+            assert_eq!(chit_fund.get_pending_dividends(member), preview.dividend_per_ticket);
+        }
+        assert_eq!(chit_fund.get_pending_dividends(accounts.django), 0);
+    }
+}
@@ -0,0 +1,33 @@
+// Storage migrations run by `migrate` after `upgrade` has swapped in new code. Every layout
+// version but the current one has a step that converts it into the next version. Steps
+// walk the membership by index, a chunk per call, so funds too large to convert within one
+// block's weight are converted over several calls; `migration_cursor` keeps the place.
+
+use core::ops::Range;
+
+// Members converted by a `migrate` call that does not set its own limit.
+pub const DEFAULT_BATCH: u32 = 50;
+
+// How a step converts each member, named after what the version it upgrades to added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    // 1 -> 2: draw eligibility is indexed in a bitmap by member. Funds that ran on version
+    // 1 have an empty bitmap, so every member's bit is worked out from their record.
+    IndexEligibility,
+}
+
+// The step that upgrades storage from `version`, None for versions this code never wrote.
+pub fn step_from(version: u32) -> Option<Step> {
+    match version {
+        1 => Some(Step::IndexEligibility),
+        _ => None,
+    }
+}
+
+// Member indices a call starting at `cursor` converts, taking at most `limit` of `total`.
+// A limit of zero means `DEFAULT_BATCH`.
+pub fn chunk(cursor: u32, limit: u32, total: u32) -> Range<u32> {
+    let limit = if limit == 0 { DEFAULT_BATCH } else { limit };
+    let end = cursor.saturating_add(limit).min(total);
+    cursor.min(end)..end
+}
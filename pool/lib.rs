@@ -0,0 +1,813 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Hosts many chit funds in one contract instance, each keyed by the fund id `create_fund`
+// hands out, so an organizer starting a group pays for a storage entry rather than a
+// deployment. Every fund keeps its own settings, members, pot and rounds in Mappings. The
+// contract's balance is shared, but a fund only ever pays out its own pot. Funds here run
+// the plain format: a fixed contribution each round, paid by every member, and a draw among
+// the members who have not won yet, seeded from secrets the members commit and reveal. A
+// fund that stalls can be cancelled, and whoever paid the open round takes it back. Funds
+// that need auctions, collateral, schedules and the rest are deployed as their own ChitFund
+// through the factory.
+#[ink::contract]
+mod pool {
+    use ink::env::hash::{Blake2x256, HashOutput};
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    // Number of blocks secrets can be revealed for after the last member pays a round.
+    pub const REVEAL_PERIOD: BlockNumber = 10;
+
+    // Blocks a full fund's round can stay open without a draw before any member can cancel
+    // the fund, about 30 days at 6 second blocks.
+    pub const ROUND_TIMEOUT: BlockNumber = 432_000;
+
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Fund {
+        pub organizer: AccountId,
+        pub max_participants: u32,
+        pub monthly_contribution: Balance,
+        pub member_count: u32,
+        // Round taking contributions, from 1. Contributions open once the fund is full.
+        pub current_round: u32,
+        // Contributions to the current round, and how many members have paid it.
+        pub pot: Balance,
+        pub deposit_count: u32,
+        // Block the current round started taking contributions at.
+        pub round_opened_at: BlockNumber,
+        // Secrets committed and revealed for the current round, the revealed secrets XORed
+        // together, and the last block they can be revealed in.
+        pub commit_count: u32,
+        pub reveal_count: u32,
+        pub entropy: [u8; 32],
+        pub reveal_deadline: BlockNumber,
+        // Set once every member has won a round.
+        pub completed: bool,
+        // Set by `cancel_fund`; members who paid the current round can take it back.
+        pub cancelled: bool,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Member {
+        // Position in the fund's `member_index`, in joining order.
+        pub index: u32,
+        pub has_won: bool,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Commitment {
+        pub round: u32,
+        pub hash: Hash,
+        pub revealed: bool,
+    }
+
+    #[ink(storage)]
+    pub struct ChitFundPool {
+        pub funds: Mapping<u32, Fund>,
+        pub next_fund_id: u32,
+        // Members of each fund by (fund id, account), plus a dense index over them by
+        // (fund id, index) for the draw to walk.
+        pub members: Mapping<(u32, AccountId), Member>,
+        pub member_index: Mapping<(u32, u32), AccountId>,
+        // Last round each member has paid for, by (fund id, account). A round is only drawn
+        // once every member has paid it, so no one can have a gap behind it.
+        pub last_paid: Mapping<(u32, AccountId), u32>,
+        // Winner of each drawn round, by (fund id, round).
+        pub winners: Mapping<(u32, u32), AccountId>,
+        // Each member's latest commitment, by (fund id, account).
+        pub commitments: Mapping<(u32, AccountId), Commitment>,
+    }
+
+    #[ink(event)]
+    pub struct FundCreated {
+        #[ink(topic)]
+        organizer: Option<AccountId>,
+        fund_id: u32,
+        max_participants: u32,
+        monthly_contribution: Balance,
+    }
+
+    #[ink(event)]
+    pub struct MemberJoined {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        fund_id: u32,
+    }
+
+    #[ink(event)]
+    pub struct MemberLeft {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        fund_id: u32,
+    }
+
+    #[ink(event)]
+    pub struct ContributionMade {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        fund_id: u32,
+        round: u32,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct WinnerDrawn {
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        fund_id: u32,
+        round: u32,
+        prize: Balance,
+    }
+
+    #[ink(event)]
+    pub struct SecretCommitted {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        fund_id: u32,
+        round: u32,
+    }
+
+    #[ink(event)]
+    pub struct SecretRevealed {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        fund_id: u32,
+        round: u32,
+    }
+
+    #[ink(event)]
+    pub struct FundCancelled {
+        #[ink(topic)]
+        cancelled_by: Option<AccountId>,
+        fund_id: u32,
+        round: u32,
+    }
+
+    #[ink(event)]
+    pub struct RefundClaimed {
+        #[ink(topic)]
+        account: Option<AccountId>,
+        fund_id: u32,
+        amount: Balance,
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        NoSuchFund,
+        InvalidFundSettings,
+        FundFull,
+        AlreadyJoined,
+        NotParticipant,
+        FundNotFull,
+        FundCompleted,
+        IncorrectContributionAmount,
+        AlreadyDeposited,
+        OnlyOrganizerCanDraw,
+        RoundNotFullyFunded,
+        TransferFailed,
+        ArithmeticOverflow,
+        FundStarted,
+        FundCancelled,
+        FundNotCancelled,
+        OnlyOrganizerCanCancel,
+        RoundNotExpired,
+        NothingToRefund,
+        CommitPeriodOver,
+        AlreadyCommitted,
+        NoCommitment,
+        AlreadyRevealed,
+        InvalidReveal,
+        RevealPeriodOver,
+        RevealPeriodOpen,
+    }
+
+    impl Default for ChitFundPool {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl ChitFundPool {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                funds: Mapping::default(),
+                next_fund_id: 0,
+                members: Mapping::default(),
+                member_index: Mapping::default(),
+                last_paid: Mapping::default(),
+                winners: Mapping::default(),
+                commitments: Mapping::default(),
+            }
+        }
+
+        // Open a new fund organized by the caller and return its id. It runs one round per
+        // member once `max_participants` members have joined.
+        #[ink(message)]
+        pub fn create_fund(&mut self, max_participants: u32, monthly_contribution: Balance) -> Result<u32, Error> {
+            if max_participants < 2 || monthly_contribution == 0 {
+                return Err(Error::InvalidFundSettings);
+            }
+            let organizer = self.env().caller();
+            let fund_id = self.next_fund_id;
+            self.next_fund_id = fund_id.checked_add(1).ok_or(Error::ArithmeticOverflow)?;
+            self.funds.insert(
+                fund_id,
+                &Fund {
+                    organizer,
+                    max_participants,
+                    monthly_contribution,
+                    member_count: 0,
+                    current_round: 1,
+                    pot: 0,
+                    deposit_count: 0,
+                    round_opened_at: 0,
+                    commit_count: 0,
+                    reveal_count: 0,
+                    entropy: [0; 32],
+                    reveal_deadline: 0,
+                    completed: false,
+                    cancelled: false,
+                },
+            );
+            self.env().emit_event(FundCreated {
+                organizer: Some(organizer),
+                fund_id,
+                max_participants,
+                monthly_contribution,
+            });
+            Ok(fund_id)
+        }
+
+        // Take a seat in the fund. Seats can be taken until the fund is full.
+        #[ink(message)]
+        pub fn join(&mut self, fund_id: u32) -> Result<(), Error> {
+            let account = self.env().caller();
+            let mut fund = self.active_fund(fund_id)?;
+            if fund.member_count >= fund.max_participants {
+                return Err(Error::FundFull);
+            }
+            if self.members.contains((fund_id, account)) {
+                return Err(Error::AlreadyJoined);
+            }
+            let index = fund.member_count;
+            self.members.insert((fund_id, account), &Member { index, has_won: false });
+            self.member_index.insert((fund_id, index), &account);
+            fund.member_count += 1;
+            if fund.member_count == fund.max_participants {
+                fund.round_opened_at = self.env().block_number();
+            }
+            self.funds.insert(fund_id, &fund);
+            self.env().emit_event(MemberJoined {
+                account: Some(account),
+                fund_id,
+            });
+            Ok(())
+        }
+
+        // Give up the caller's seat. Only possible until the fund is full, before anything
+        // has been paid in.
+        #[ink(message)]
+        pub fn leave(&mut self, fund_id: u32) -> Result<(), Error> {
+            let account = self.env().caller();
+            let mut fund = self.active_fund(fund_id)?;
+            if fund.member_count >= fund.max_participants {
+                return Err(Error::FundStarted);
+            }
+            let member = self.members.take((fund_id, account)).ok_or(Error::NotParticipant)?;
+            // Move the last member into the freed position to keep the index dense.
+            let last = fund.member_count - 1;
+            if member.index != last {
+                if let Some(moved) = self.member_index.get((fund_id, last)) {
+                    if let Some(mut moved_member) = self.members.get((fund_id, moved)) {
+                        moved_member.index = member.index;
+                        self.members.insert((fund_id, moved), &moved_member);
+                    }
+                    self.member_index.insert((fund_id, member.index), &moved);
+                }
+            }
+            self.member_index.remove((fund_id, last));
+            fund.member_count = last;
+            self.funds.insert(fund_id, &fund);
+            self.env().emit_event(MemberLeft {
+                account: Some(account),
+                fund_id,
+            });
+            Ok(())
+        }
+
+        // Pay the caller's contribution to the fund's current round.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self, fund_id: u32) -> Result<(), Error> {
+            let account = self.env().caller();
+            let mut fund = self.active_fund(fund_id)?;
+            if !self.members.contains((fund_id, account)) {
+                return Err(Error::NotParticipant);
+            }
+            if fund.member_count < fund.max_participants {
+                return Err(Error::FundNotFull);
+            }
+            let amount = self.env().transferred_value();
+            if amount != fund.monthly_contribution {
+                return Err(Error::IncorrectContributionAmount);
+            }
+            let round = fund.current_round;
+            if self.has_deposited(fund_id, account, round) {
+                return Err(Error::AlreadyDeposited);
+            }
+            self.last_paid.insert((fund_id, account), &round);
+            fund.pot = fund.pot.checked_add(amount).ok_or(Error::ArithmeticOverflow)?;
+            fund.deposit_count += 1;
+            // The last payment closes commitments and opens the reveals.
+            if fund.deposit_count == fund.member_count {
+                fund.reveal_deadline = self.env().block_number().saturating_add(REVEAL_PERIOD);
+            }
+            self.funds.insert(fund_id, &fund);
+            self.env().emit_event(ContributionMade {
+                account: Some(account),
+                fund_id,
+                round,
+                amount,
+            });
+            Ok(())
+        }
+
+        // Commit to a secret for the fund's current round. The commitment is the Blake2x256
+        // hash of the SCALE encoded `(caller, secret)` pair. Commitments close when the last
+        // member pays the round.
+        #[ink(message)]
+        pub fn commit(&mut self, fund_id: u32, commitment: Hash) -> Result<(), Error> {
+            let account = self.env().caller();
+            let mut fund = self.active_fund(fund_id)?;
+            if !self.members.contains((fund_id, account)) {
+                return Err(Error::NotParticipant);
+            }
+            if fund.member_count < fund.max_participants {
+                return Err(Error::FundNotFull);
+            }
+            if fund.deposit_count >= fund.member_count {
+                return Err(Error::CommitPeriodOver);
+            }
+            let round = fund.current_round;
+            if self.commitments.get((fund_id, account)).is_some_and(|existing| existing.round == round) {
+                return Err(Error::AlreadyCommitted);
+            }
+            self.commitments.insert(
+                (fund_id, account),
+                &Commitment {
+                    round,
+                    hash: commitment,
+                    revealed: false,
+                },
+            );
+            fund.commit_count += 1;
+            self.funds.insert(fund_id, &fund);
+            self.env().emit_event(SecretCommitted {
+                account: Some(account),
+                fund_id,
+                round,
+            });
+            Ok(())
+        }
+
+        // Reveal the secret committed for the fund's current round, once every member has
+        // paid it and until `REVEAL_PERIOD` blocks after the last payment.
+        #[ink(message)]
+        pub fn reveal(&mut self, fund_id: u32, secret: Hash) -> Result<(), Error> {
+            let account = self.env().caller();
+            let mut fund = self.active_fund(fund_id)?;
+            if fund.member_count < fund.max_participants || fund.deposit_count < fund.member_count {
+                return Err(Error::RoundNotFullyFunded);
+            }
+            if self.env().block_number() > fund.reveal_deadline {
+                return Err(Error::RevealPeriodOver);
+            }
+            let round = fund.current_round;
+            let mut commitment = self
+                .commitments
+                .get((fund_id, account))
+                .filter(|commitment| commitment.round == round)
+                .ok_or(Error::NoCommitment)?;
+            if commitment.revealed {
+                return Err(Error::AlreadyRevealed);
+            }
+            if Self::commitment_of(account, secret) != commitment.hash {
+                return Err(Error::InvalidReveal);
+            }
+            for (byte, secret_byte) in fund.entropy.iter_mut().zip(secret.as_ref()) {
+                *byte ^= secret_byte;
+            }
+            fund.reveal_count += 1;
+            commitment.revealed = true;
+            self.commitments.insert((fund_id, account), &commitment);
+            self.funds.insert(fund_id, &fund);
+            self.env().emit_event(SecretRevealed {
+                account: Some(account),
+                fund_id,
+                round,
+            });
+            Ok(())
+        }
+
+        // The commitment a member has to submit for the given secret.
+        pub fn commitment_of(account: AccountId, secret: Hash) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(account, secret), &mut output);
+            Hash::from(output)
+        }
+
+        // Draw the current round's winner among the members who have not won yet and pay
+        // them the round's pot. Only the fund's organizer can draw, once every member has
+        // paid the round and every committed secret is revealed or the reveal period is over.
+        #[ink(message)]
+        pub fn draw(&mut self, fund_id: u32) -> Result<(), Error> {
+            let mut fund = self.active_fund(fund_id)?;
+            if self.env().caller() != fund.organizer {
+                return Err(Error::OnlyOrganizerCanDraw);
+            }
+            if fund.member_count < fund.max_participants || fund.deposit_count < fund.member_count {
+                return Err(Error::RoundNotFullyFunded);
+            }
+            if fund.reveal_count < fund.commit_count && self.env().block_number() <= fund.reveal_deadline {
+                return Err(Error::RevealPeriodOpen);
+            }
+            let round = fund.current_round;
+            let start = (self.draw_seed(fund_id, &fund) % fund.member_count as u64) as u32;
+            // A round is drawn for each member, so someone who has not won is always left.
+            let (winner, mut member) = (0..fund.member_count)
+                .filter_map(|offset| {
+                    let account = self.member_index.get((fund_id, (start + offset) % fund.member_count))?;
+                    let member = self.members.get((fund_id, account))?;
+                    (!member.has_won).then_some((account, member))
+                })
+                .next()
+                .ok_or(Error::FundCompleted)?;
+
+            let prize = fund.pot;
+            member.has_won = true;
+            self.members.insert((fund_id, winner), &member);
+            self.winners.insert((fund_id, round), &winner);
+            fund.pot = 0;
+            fund.deposit_count = 0;
+            fund.current_round = round.checked_add(1).ok_or(Error::ArithmeticOverflow)?;
+            fund.round_opened_at = self.env().block_number();
+            fund.commit_count = 0;
+            fund.reveal_count = 0;
+            fund.entropy = [0; 32];
+            fund.completed = fund.current_round > fund.member_count;
+            self.funds.insert(fund_id, &fund);
+            self.env().transfer(winner, prize).map_err(|_| Error::TransferFailed)?;
+            self.env().emit_event(WinnerDrawn {
+                winner: Some(winner),
+                fund_id,
+                round,
+                prize,
+            });
+            Ok(())
+        }
+
+        // Stop the fund. The organizer can cancel it at any time; a member can once the fund
+        // is full and its current round has been open for `ROUND_TIMEOUT` blocks without a
+        // draw. Contributions to the current round can then be taken back with `claim_refund`.
+        #[ink(message)]
+        pub fn cancel_fund(&mut self, fund_id: u32) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let mut fund = self.active_fund(fund_id)?;
+            if caller != fund.organizer {
+                if !self.members.contains((fund_id, caller)) {
+                    return Err(Error::OnlyOrganizerCanCancel);
+                }
+                let expires_at = fund.round_opened_at.saturating_add(ROUND_TIMEOUT);
+                if fund.member_count < fund.max_participants || self.env().block_number() <= expires_at {
+                    return Err(Error::RoundNotExpired);
+                }
+            }
+            fund.cancelled = true;
+            self.funds.insert(fund_id, &fund);
+            self.env().emit_event(FundCancelled {
+                cancelled_by: Some(caller),
+                fund_id,
+                round: fund.current_round,
+            });
+            Ok(())
+        }
+
+        // Take back the caller's contribution to the round a cancelled fund stopped in.
+        // Earlier rounds have already been paid out to their winners.
+        #[ink(message)]
+        pub fn claim_refund(&mut self, fund_id: u32) -> Result<(), Error> {
+            let account = self.env().caller();
+            let mut fund = self.funds.get(fund_id).ok_or(Error::NoSuchFund)?;
+            if !fund.cancelled {
+                return Err(Error::FundNotCancelled);
+            }
+            let round = fund.current_round;
+            if !self.has_deposited(fund_id, account, round) {
+                return Err(Error::NothingToRefund);
+            }
+            let amount = fund.monthly_contribution;
+            fund.pot = fund.pot.checked_sub(amount).ok_or(Error::ArithmeticOverflow)?;
+            fund.deposit_count = fund.deposit_count.saturating_sub(1);
+            self.last_paid.insert((fund_id, account), &(round - 1));
+            self.funds.insert(fund_id, &fund);
+            self.env().transfer(account, amount).map_err(|_| Error::TransferFailed)?;
+            self.env().emit_event(RefundClaimed {
+                account: Some(account),
+                fund_id,
+                amount,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_fund(&self, fund_id: u32) -> Option<Fund> {
+            self.funds.get(fund_id)
+        }
+
+        // Members of the fund in joining order.
+        #[ink(message)]
+        pub fn get_members(&self, fund_id: u32) -> Vec<AccountId> {
+            let count = self.funds.get(fund_id).map_or(0, |fund| fund.member_count);
+            (0..count).filter_map(|index| self.member_index.get((fund_id, index))).collect()
+        }
+
+        #[ink(message)]
+        pub fn is_member(&self, fund_id: u32, account: AccountId) -> bool {
+            self.members.contains((fund_id, account))
+        }
+
+        #[ink(message)]
+        pub fn has_deposited(&self, fund_id: u32, account: AccountId, round: u32) -> bool {
+            self.last_paid.get((fund_id, account)).is_some_and(|paid| paid >= round)
+        }
+
+        #[ink(message)]
+        pub fn get_winner(&self, fund_id: u32, round: u32) -> Option<AccountId> {
+            self.winners.get((fund_id, round))
+        }
+
+        fn active_fund(&self, fund_id: u32) -> Result<Fund, Error> {
+            let fund = self.funds.get(fund_id).ok_or(Error::NoSuchFund)?;
+            if fund.completed {
+                return Err(Error::FundCompleted);
+            }
+            if fund.cancelled {
+                return Err(Error::FundCancelled);
+            }
+            Ok(fund)
+        }
+
+        // Seed for a fund's draw, hashed from the secrets revealed for the round and the
+        // current block, so neither the organizer nor any single member can pick the winner.
+        // The fund and round are mixed in so draws of different funds in the same block land
+        // differently.
+        fn draw_seed(&self, fund_id: u32, fund: &Fund) -> u64 {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            let input = (
+                fund.entropy,
+                fund_id,
+                fund.current_round,
+                self.env().block_number(),
+                self.env().block_timestamp(),
+            );
+            ink::env::hash_encoded::<Blake2x256, _>(&input, &mut output);
+            let mut seed = [0u8; 8];
+            seed.copy_from_slice(&output[..8]);
+            u64::from_le_bytes(seed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pool::{ChitFundPool, Error, ROUND_TIMEOUT};
+    use ink::env::{test, DefaultEnvironment};
+    use ink::primitives::{AccountId, Hash};
+
+    fn call_as(account: AccountId, value: u128) {
+        test::set_caller::<DefaultEnvironment>(account);
+        test::set_value_transferred::<DefaultEnvironment>(value);
+    }
+
+    #[ink::test]
+    fn test_create_and_join_funds() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mut pool = ChitFundPool::new();
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.create_fund(1, 100), Err(Error::InvalidFundSettings));
+        assert_eq!(pool.create_fund(2, 0), Err(Error::InvalidFundSettings));
+        assert_eq!(pool.create_fund(2, 100), Ok(0));
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.create_fund(3, 50), Ok(1));
+
+        // Joining one fund says nothing about the other.
+        call_as(accounts.charlie, 0);
+        assert_eq!(pool.join(0), Ok(()));
+        assert_eq!(pool.join(0), Err(Error::AlreadyJoined));
+        assert_eq!(pool.join(1), Ok(()));
+        assert_eq!(pool.join(2), Err(Error::NoSuchFund));
+        call_as(accounts.django, 0);
+        assert_eq!(pool.join(0), Ok(()));
+        call_as(accounts.eve, 0);
+        assert_eq!(pool.join(0), Err(Error::FundFull));
+
+        assert_eq!(pool.get_members(0), vec![accounts.charlie, accounts.django]);
+        assert_eq!(pool.get_members(1), vec![accounts.charlie]);
+        let fund = pool.get_fund(1).unwrap();
+        assert_eq!(fund.organizer, accounts.bob);
+        assert_eq!(fund.monthly_contribution, 50);
+        assert_eq!(fund.member_count, 1);
+    }
+
+    #[ink::test]
+    fn test_contributions_open_once_the_fund_is_full() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mut pool = ChitFundPool::new();
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.create_fund(2, 100), Ok(0));
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.join(0), Ok(()));
+        call_as(accounts.bob, 100);
+        assert_eq!(pool.deposit(0), Err(Error::FundNotFull));
+        call_as(accounts.charlie, 0);
+        assert_eq!(pool.join(0), Ok(()));
+
+        call_as(accounts.eve, 100);
+        assert_eq!(pool.deposit(0), Err(Error::NotParticipant));
+        call_as(accounts.bob, 90);
+        assert_eq!(pool.deposit(0), Err(Error::IncorrectContributionAmount));
+        call_as(accounts.bob, 100);
+        assert_eq!(pool.deposit(0), Ok(()));
+        assert_eq!(pool.deposit(0), Err(Error::AlreadyDeposited));
+        assert!(pool.has_deposited(0, accounts.bob, 1));
+        assert!(!pool.has_deposited(0, accounts.charlie, 1));
+
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.draw(0), Err(Error::RoundNotFullyFunded));
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.draw(0), Err(Error::OnlyOrganizerCanDraw));
+    }
+
+    #[ink::test]
+    fn test_funds_pay_out_their_own_pots() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let contract = test::callee::<DefaultEnvironment>();
+        let mut pool = ChitFundPool::new();
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.create_fund(2, 100), Ok(0));
+        assert_eq!(pool.create_fund(2, 30), Ok(1));
+        for fund_id in [0, 1] {
+            for member in [accounts.bob, accounts.charlie] {
+                call_as(member, 0);
+                assert_eq!(pool.join(fund_id), Ok(()));
+            }
+        }
+        // The off-chain environment does not credit transferred value, so fund both pots.
+        test::set_account_balance::<DefaultEnvironment>(contract, 520);
+
+        for round in 1..=2 {
+            for member in [accounts.bob, accounts.charlie] {
+                call_as(member, 100);
+                assert_eq!(pool.deposit(0), Ok(()));
+            }
+            call_as(accounts.charlie, 30);
+            assert_eq!(pool.deposit(1), Ok(()));
+
+            call_as(accounts.alice, 0);
+            assert_eq!(pool.draw(0), Ok(()));
+            assert_eq!(pool.get_fund(0).unwrap().pot, 0);
+            // The other fund's round is still waiting on bob, with its pot untouched.
+            assert_eq!(pool.draw(1), Err(Error::RoundNotFullyFunded));
+            assert_eq!(pool.get_fund(1).unwrap().pot, 30);
+            call_as(accounts.bob, 30);
+            assert_eq!(pool.deposit(1), Ok(()));
+            call_as(accounts.alice, 0);
+            assert_eq!(pool.draw(1), Ok(()));
+            assert!(pool.get_winner(0, round).is_some());
+            assert!(pool.get_winner(1, round).is_some());
+        }
+
+        // Each member won once in each fund, and both funds are done.
+        for fund_id in [0, 1] {
+            let mut winners = vec![pool.get_winner(fund_id, 1).unwrap(), pool.get_winner(fund_id, 2).unwrap()];
+            winners.sort();
+            let mut members = vec![accounts.bob, accounts.charlie];
+            members.sort();
+            assert_eq!(winners, members);
+            assert!(pool.get_fund(fund_id).unwrap().completed);
+            assert_eq!(pool.draw(fund_id), Err(Error::FundCompleted));
+        }
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(contract), Ok(0));
+    }
+
+    #[ink::test]
+    fn test_members_can_leave_until_the_fund_is_full() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mut pool = ChitFundPool::new();
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.create_fund(3, 100), Ok(0));
+        for member in [accounts.bob, accounts.charlie] {
+            call_as(member, 0);
+            assert_eq!(pool.join(0), Ok(()));
+        }
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.leave(0), Ok(()));
+        assert_eq!(pool.leave(0), Err(Error::NotParticipant));
+        assert_eq!(pool.get_members(0), vec![accounts.charlie]);
+
+        for member in [accounts.django, accounts.eve] {
+            call_as(member, 0);
+            assert_eq!(pool.join(0), Ok(()));
+        }
+        call_as(accounts.charlie, 0);
+        assert_eq!(pool.leave(0), Err(Error::FundStarted));
+    }
+
+    #[ink::test]
+    fn test_cancelled_fund_refunds_the_open_round() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let contract = test::callee::<DefaultEnvironment>();
+        let mut pool = ChitFundPool::new();
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.create_fund(3, 100), Ok(0));
+        for member in [accounts.bob, accounts.charlie, accounts.django] {
+            call_as(member, 0);
+            assert_eq!(pool.join(0), Ok(()));
+        }
+        test::set_account_balance::<DefaultEnvironment>(contract, 200);
+        for member in [accounts.bob, accounts.charlie] {
+            call_as(member, 100);
+            assert_eq!(pool.deposit(0), Ok(()));
+        }
+
+        // Django never pays, so the round cannot be drawn. A member can only cancel once
+        // the round has been open for the timeout.
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.claim_refund(0), Err(Error::FundNotCancelled));
+        assert_eq!(pool.cancel_fund(0), Err(Error::RoundNotExpired));
+        call_as(accounts.eve, 0);
+        assert_eq!(pool.cancel_fund(0), Err(Error::OnlyOrganizerCanCancel));
+        for _ in 0..=ROUND_TIMEOUT {
+            test::advance_block::<DefaultEnvironment>();
+        }
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.cancel_fund(0), Ok(()));
+        call_as(accounts.django, 100);
+        assert_eq!(pool.deposit(0), Err(Error::FundCancelled));
+
+        for member in [accounts.bob, accounts.charlie] {
+            call_as(member, 0);
+            let before = test::get_account_balance::<DefaultEnvironment>(member).unwrap();
+            assert_eq!(pool.claim_refund(0), Ok(()));
+            let after = test::get_account_balance::<DefaultEnvironment>(member).unwrap();
+            assert_eq!(after - before, 100);
+            assert_eq!(pool.claim_refund(0), Err(Error::NothingToRefund));
+        }
+        call_as(accounts.django, 0);
+        assert_eq!(pool.claim_refund(0), Err(Error::NothingToRefund));
+        assert_eq!(pool.get_fund(0).unwrap().pot, 0);
+        assert_eq!(test::get_account_balance::<DefaultEnvironment>(contract), Ok(0));
+    }
+
+    #[ink::test]
+    fn test_draw_waits_for_committed_secrets() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let contract = test::callee::<DefaultEnvironment>();
+        let mut pool = ChitFundPool::new();
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.create_fund(2, 100), Ok(0));
+        for member in [accounts.bob, accounts.charlie] {
+            call_as(member, 0);
+            assert_eq!(pool.join(0), Ok(()));
+        }
+        test::set_account_balance::<DefaultEnvironment>(contract, 200);
+        let secret = Hash::from([0x07; 32]);
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.commit(0, ChitFundPool::commitment_of(accounts.bob, secret)), Ok(()));
+        assert_eq!(pool.commit(0, Hash::from([0x01; 32])), Err(Error::AlreadyCommitted));
+        for member in [accounts.bob, accounts.charlie] {
+            call_as(member, 100);
+            assert_eq!(pool.deposit(0), Ok(()));
+        }
+        call_as(accounts.charlie, 0);
+        assert_eq!(pool.commit(0, Hash::from([0x01; 32])), Err(Error::CommitPeriodOver));
+        assert_eq!(pool.reveal(0, secret), Err(Error::NoCommitment));
+
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.draw(0), Err(Error::RevealPeriodOpen));
+        call_as(accounts.bob, 0);
+        assert_eq!(pool.reveal(0, Hash::from([0x08; 32])), Err(Error::InvalidReveal));
+        assert_eq!(pool.reveal(0, secret), Ok(()));
+        assert_eq!(pool.reveal(0, secret), Err(Error::AlreadyRevealed));
+        assert_eq!(pool.get_fund(0).unwrap().entropy, [0x07; 32]);
+
+        call_as(accounts.alice, 0);
+        assert_eq!(pool.draw(0), Ok(()));
+        let fund = pool.get_fund(0).unwrap();
+        assert_eq!((fund.commit_count, fund.reveal_count, fund.entropy), (0, 0, [0; 32]));
+    }
+}
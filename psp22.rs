@@ -0,0 +1,96 @@
+// Minimal cross-contract calls into a PSP22 token, used when a fund collects its
+// contributions in a token instead of the native currency.
+
+use ink::env::call::{build_call, ExecutionInput, Selector};
+use ink::prelude::{string::String, vec::Vec};
+use ink::primitives::AccountId;
+
+use crate::CustomEnvironment;
+
+type Balance = <CustomEnvironment as ink::env::Environment>::Balance;
+
+// The error type of the PSP22 standard, mirrored so replies can be decoded.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+pub enum PSP22Error {
+    Custom(String),
+    InsufficientBalance,
+    InsufficientAllowance,
+    ZeroRecipientAddress,
+    ZeroSenderAddress,
+    SafeTransferCheckFailed(String),
+}
+
+// Why a call into the token failed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TokenError {
+    // The token answered with an error.
+    Token(PSP22Error),
+    // The call itself could not be made or its reply could not be decoded.
+    CallFailed,
+}
+
+pub fn transfer(token: AccountId, to: AccountId, value: Balance) -> Result<(), TokenError> {
+    build_call::<CustomEnvironment>()
+        .call(token)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                .push_arg(to)
+                .push_arg(value)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<Result<(), PSP22Error>>()
+        .try_invoke()
+        .map_err(|_| TokenError::CallFailed)?
+        .map_err(|_| TokenError::CallFailed)?
+        .map_err(TokenError::Token)
+}
+
+pub fn transfer_from(
+    token: AccountId,
+    from: AccountId,
+    to: AccountId,
+    value: Balance,
+) -> Result<(), TokenError> {
+    build_call::<CustomEnvironment>()
+        .call(token)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                .push_arg(from)
+                .push_arg(to)
+                .push_arg(value)
+                .push_arg(Vec::<u8>::new()),
+        )
+        .returns::<Result<(), PSP22Error>>()
+        .try_invoke()
+        .map_err(|_| TokenError::CallFailed)?
+        .map_err(|_| TokenError::CallFailed)?
+        .map_err(TokenError::Token)
+}
+
+pub fn approve(token: AccountId, spender: AccountId, value: Balance) -> Result<(), TokenError> {
+    build_call::<CustomEnvironment>()
+        .call(token)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::approve")))
+                .push_arg(spender)
+                .push_arg(value),
+        )
+        .returns::<Result<(), PSP22Error>>()
+        .try_invoke()
+        .map_err(|_| TokenError::CallFailed)?
+        .map_err(|_| TokenError::CallFailed)?
+        .map_err(TokenError::Token)
+}
+
+pub fn balance_of(token: AccountId, owner: AccountId) -> Result<Balance, TokenError> {
+    build_call::<CustomEnvironment>()
+        .call(token)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::balance_of")))
+                .push_arg(owner),
+        )
+        .returns::<Balance>()
+        .try_invoke()
+        .map_err(|_| TokenError::CallFailed)?
+        .map_err(|_| TokenError::CallFailed)
+}
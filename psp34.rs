@@ -0,0 +1,59 @@
+// Minimal cross-contract calls into a PSP34 collection that mints deposit receipts, so
+// members can show third parties what they have paid.
+
+use ink::env::call::{build_call, ExecutionInput, Selector};
+use ink::prelude::{string::String, vec::Vec};
+use ink::primitives::AccountId;
+
+use crate::CustomEnvironment;
+
+type Balance = <CustomEnvironment as ink::env::Environment>::Balance;
+
+// The token id type of the PSP34 standard.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+pub enum Id {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bytes(Vec<u8>),
+}
+
+// The error type of the PSP34 standard, mirrored so replies can be decoded.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+pub enum PSP34Error {
+    Custom(String),
+    SelfApprove,
+    NotApproved,
+    TokenExists,
+    TokenNotExists,
+    SafeTransferCheckFailed(String),
+}
+
+// The call failed, was refused, or its reply could not be decoded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReceiptError;
+
+// Id of the receipt for `member`'s payment of `amount` towards `round` of `fund`. The
+// SCALE encoding of all four is the receipt's metadata: it makes the id unique across
+// funds and lets anyone holding the receipt decode what was paid.
+pub fn receipt_id(fund: AccountId, member: AccountId, round: u32, amount: Balance) -> Id {
+    Id::Bytes(scale::Encode::encode(&(fund, member, round, amount)))
+}
+
+// Mint `id` to `to`. The fund has to be allowed to mint on the collection.
+pub fn mint(collection: AccountId, to: AccountId, id: Id) -> Result<(), ReceiptError> {
+    build_call::<CustomEnvironment>()
+        .call(collection)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP34Mintable::mint")))
+                .push_arg(to)
+                .push_arg(id),
+        )
+        .returns::<Result<(), PSP34Error>>()
+        .try_invoke()
+        .map_err(|_| ReceiptError)?
+        .map_err(|_| ReceiptError)?
+        .map_err(|_| ReceiptError)
+}
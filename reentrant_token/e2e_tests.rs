@@ -0,0 +1,196 @@
+// End-to-end tests in which the token a fund pays out in calls back into the fund in the
+// middle of the payout. Each one checks that the nested call is turned away and the
+// payout goes out once. Run them like the fund's own, with
+// `cargo test --features e2e-tests` and a node on the default port.
+
+use chit_fund::{ChitFundRef, CustomEnvironment, FundConfig, Role};
+use ink_e2e::{AccountKeyring, MessageBuilder};
+
+use crate::{ReentrantTokenRef, PSP22};
+
+type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const CONTRIBUTION: u128 = 1_000_000;
+
+fn fund_at(fund: ink::primitives::AccountId) -> MessageBuilder<CustomEnvironment, ChitFundRef> {
+    MessageBuilder::from_account_id(fund)
+}
+
+fn token_at(token: ink::primitives::AccountId) -> MessageBuilder<CustomEnvironment, ReentrantTokenRef> {
+    MessageBuilder::from_account_id(token)
+}
+
+#[ink_e2e::test(additional_contracts = "../Cargo.toml", environment = chit_fund::CustomEnvironment)]
+async fn leave_refunds_once(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    let admin = ink_e2e::account_id(AccountKeyring::Alice);
+    let bob = ink_e2e::account_id(AccountKeyring::Bob);
+    let token = client
+        .instantiate("reentrant_token", &ink_e2e::alice(), ReentrantTokenRef::new(), 0, None)
+        .await
+        .expect("instantiate token failed")
+        .account_id;
+    let config = FundConfig {
+        contribution_token: Some(token),
+        ..Default::default()
+    };
+    let constructor = ChitFundRef::with_config(admin, 2, CONTRIBUTION, config);
+    let fund = client
+        .instantiate("chit_fund", &ink_e2e::alice(), constructor, 0, None)
+        .await
+        .expect("instantiate fund failed")
+        .account_id;
+    for account in [token, bob] {
+        let mint = token_at(token).call(|token| token.mint(account, 10 * CONTRIBUTION));
+        client.call(&ink_e2e::alice(), mint, 0, None).await.expect("mint failed");
+    }
+
+    // The token takes a seat itself and pays the first round.
+    let join = token_at(token).call(|token| token.call_fund(fund, ink::selector_bytes!("join")));
+    client.call(&ink_e2e::alice(), join, 0, None).await.expect("join failed");
+    let join = fund_at(fund).call(|fund| fund.join());
+    client.call(&ink_e2e::bob(), join, 0, None).await.expect("join failed");
+    let start = fund_at(fund).call(|fund| fund.start_fund());
+    client.call(&ink_e2e::alice(), start, 0, None).await.expect("start_fund failed");
+    let deposit = token_at(token).call(|token| token.call_fund(fund, ink::selector_bytes!("deposit")));
+    client.call(&ink_e2e::alice(), deposit, 0, None).await.expect("deposit failed");
+
+    // Leaving pays the contribution back, and the payout tries to leave again.
+    let arm = token_at(token).call(|token| token.arm(fund, ink::selector_bytes!("leave"), None));
+    client.call(&ink_e2e::alice(), arm, 0, None).await.expect("arm failed");
+    let leave = token_at(token).call(|token| token.call_fund(fund, ink::selector_bytes!("leave")));
+    client.call(&ink_e2e::alice(), leave, 0, None).await.expect("leave failed");
+
+    let reentered = token_at(token).call(|token| token.reentered());
+    let reentered = client.call_dry_run(&ink_e2e::alice(), &reentered, 0, None).await.return_value();
+    assert_eq!(reentered, Some(false));
+    let is_member = fund_at(fund).call(|fund| fund.is_member(token));
+    assert!(!client.call_dry_run(&ink_e2e::alice(), &is_member, 0, None).await.return_value());
+    for (account, expected) in [(token, 10 * CONTRIBUTION), (fund, 0)] {
+        let balance = token_at(token).call(|token| token.balance_of(account));
+        let balance = client.call_dry_run(&ink_e2e::alice(), &balance, 0, None).await.return_value();
+        assert_eq!(balance, expected);
+    }
+    Ok(())
+}
+
+#[ink_e2e::test(additional_contracts = "../Cargo.toml", environment = chit_fund::CustomEnvironment)]
+async fn commission_is_withdrawn_once(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    let admin = ink_e2e::account_id(AccountKeyring::Alice);
+    let members = [ink_e2e::bob(), ink_e2e::charlie()];
+    let token = client
+        .instantiate("reentrant_token", &ink_e2e::alice(), ReentrantTokenRef::new(), 0, None)
+        .await
+        .expect("instantiate token failed")
+        .account_id;
+    // A tenth of each prize is commission.
+    let config = FundConfig {
+        contribution_token: Some(token),
+        commission_bps: 1_000,
+        ..Default::default()
+    };
+    let constructor = ChitFundRef::with_config(admin, 2, CONTRIBUTION, config);
+    let fund = client
+        .instantiate("chit_fund", &ink_e2e::alice(), constructor, 0, None)
+        .await
+        .expect("instantiate fund failed")
+        .account_id;
+    for account in [AccountKeyring::Bob, AccountKeyring::Charlie] {
+        let mint = token_at(token).call(|token| token.mint(ink_e2e::account_id(account), 10 * CONTRIBUTION));
+        client.call(&ink_e2e::alice(), mint, 0, None).await.expect("mint failed");
+    }
+
+    for member in &members {
+        let join = fund_at(fund).call(|fund| fund.join());
+        client.call(member, join, 0, None).await.expect("join failed");
+    }
+    let start = fund_at(fund).call(|fund| fund.start_fund());
+    client.call(&ink_e2e::alice(), start, 0, None).await.expect("start_fund failed");
+    for member in &members {
+        let deposit = fund_at(fund).call(|fund| fund.deposit());
+        client.call(member, deposit, 0, None).await.expect("deposit failed");
+    }
+    let end_cycle = fund_at(fund).call(|fund| fund.end_cycle());
+    client.call(&ink_e2e::alice(), end_cycle, 0, None).await.expect("end_cycle failed");
+    let draw = fund_at(fund).call(|fund| fund.draw());
+    client.call(&ink_e2e::alice(), draw, 0, None).await.expect("draw failed");
+
+    // The token is made a treasurer, so the commission payout can try to withdraw again.
+    let grant = fund_at(fund).call(|fund| fund.grant_role(token, Role::Treasurer));
+    client.call(&ink_e2e::alice(), grant, 0, None).await.expect("grant_role failed");
+    let arm = token_at(token).call(|token| token.arm(fund, ink::selector_bytes!("withdraw_commission"), None));
+    client.call(&ink_e2e::alice(), arm, 0, None).await.expect("arm failed");
+    let withdraw = fund_at(fund).call(|fund| fund.withdraw_commission());
+    client.call(&ink_e2e::alice(), withdraw, 0, None).await.expect("withdraw_commission failed");
+
+    let reentered = token_at(token).call(|token| token.reentered());
+    let reentered = client.call_dry_run(&ink_e2e::alice(), &reentered, 0, None).await.return_value();
+    assert_eq!(reentered, Some(false));
+    let balance = token_at(token).call(|token| token.balance_of(admin));
+    let balance = client.call_dry_run(&ink_e2e::alice(), &balance, 0, None).await.return_value();
+    assert_eq!(balance, 2 * CONTRIBUTION / 10);
+    Ok(())
+}
+
+#[ink_e2e::test(additional_contracts = "../Cargo.toml", environment = chit_fund::CustomEnvironment)]
+async fn expulsion_refunds_once(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    let admin = ink_e2e::account_id(AccountKeyring::Alice);
+    let members = [ink_e2e::bob(), ink_e2e::charlie()];
+    let token = client
+        .instantiate("reentrant_token", &ink_e2e::alice(), ReentrantTokenRef::new(), 0, None)
+        .await
+        .expect("instantiate token failed")
+        .account_id;
+    // Members post two contributions of collateral and can be expelled after one miss.
+    let config = FundConfig {
+        contribution_token: Some(token),
+        collateral: 2 * CONTRIBUTION,
+        max_missed_rounds: 1,
+        ..Default::default()
+    };
+    let constructor = ChitFundRef::with_config(admin, 3, CONTRIBUTION, config);
+    let fund = client
+        .instantiate("chit_fund", &ink_e2e::alice(), constructor, 0, None)
+        .await
+        .expect("instantiate fund failed")
+        .account_id;
+    for account in [token, ink_e2e::account_id(AccountKeyring::Bob), ink_e2e::account_id(AccountKeyring::Charlie)] {
+        let mint = token_at(token).call(|token| token.mint(account, 10 * CONTRIBUTION));
+        client.call(&ink_e2e::alice(), mint, 0, None).await.expect("mint failed");
+    }
+
+    // The token joins and then misses the first round.
+    let join = token_at(token).call(|token| token.call_fund(fund, ink::selector_bytes!("join")));
+    client.call(&ink_e2e::alice(), join, 0, None).await.expect("join failed");
+    for member in &members {
+        let join = fund_at(fund).call(|fund| fund.join());
+        client.call(member, join, 0, None).await.expect("join failed");
+    }
+    let start = fund_at(fund).call(|fund| fund.start_fund());
+    client.call(&ink_e2e::alice(), start, 0, None).await.expect("start_fund failed");
+    for member in &members {
+        let deposit = fund_at(fund).call(|fund| fund.deposit());
+        client.call(member, deposit, 0, None).await.expect("deposit failed");
+    }
+    let end_cycle = fund_at(fund).call(|fund| fund.end_cycle());
+    client.call(&ink_e2e::alice(), end_cycle, 0, None).await.expect("end_cycle failed");
+
+    // Expelling refunds what is left of its collateral, and the refund tries to leave too.
+    let arm = token_at(token).call(|token| token.arm(fund, ink::selector_bytes!("leave"), None));
+    client.call(&ink_e2e::alice(), arm, 0, None).await.expect("arm failed");
+    let balance_of = |account| token_at(token).call(|token| token.balance_of(account));
+    let token_before = client.call_dry_run(&ink_e2e::alice(), &balance_of(token), 0, None).await.return_value();
+    let fund_before = client.call_dry_run(&ink_e2e::alice(), &balance_of(fund), 0, None).await.return_value();
+    let expel = fund_at(fund).call(|fund| fund.expel(token));
+    client.call(&ink_e2e::alice(), expel, 0, None).await.expect("expel failed");
+
+    let reentered = token_at(token).call(|token| token.reentered());
+    let reentered = client.call_dry_run(&ink_e2e::alice(), &reentered, 0, None).await.return_value();
+    assert_eq!(reentered, Some(false));
+    let is_member = fund_at(fund).call(|fund| fund.is_member(token));
+    assert!(!client.call_dry_run(&ink_e2e::alice(), &is_member, 0, None).await.return_value());
+    let token_after = client.call_dry_run(&ink_e2e::alice(), &balance_of(token), 0, None).await.return_value();
+    let fund_after = client.call_dry_run(&ink_e2e::alice(), &balance_of(fund), 0, None).await.return_value();
+    assert!(token_after > token_before);
+    assert_eq!(token_after - token_before, fund_before - fund_after);
+    Ok(())
+}
@@ -0,0 +1,175 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// A PSP22 token for the end-to-end tests that turns on the fund paying out in it. Once
+// armed, the next transfer the fund makes calls straight back into the fund, the way a
+// malicious token or member contract would, and records whether the call went through.
+// It keeps no allowances, so a fund can pull contributions from any holder.
+
+#[cfg(all(test, feature = "e2e-tests"))]
+mod e2e_tests;
+
+use ink::prelude::{string::String, vec::Vec};
+use ink::primitives::AccountId;
+
+type Balance = <chit_fund::CustomEnvironment as ink::env::Environment>::Balance;
+
+// The error type of the PSP22 standard.
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PSP22Error {
+    Custom(String),
+    InsufficientBalance,
+    InsufficientAllowance,
+    ZeroRecipientAddress,
+    ZeroSenderAddress,
+    SafeTransferCheckFailed(String),
+}
+
+// The part of PSP22 a fund calls.
+#[ink::trait_definition]
+pub trait PSP22 {
+    #[ink(message)]
+    fn balance_of(&self, owner: AccountId) -> Balance;
+
+    #[ink(message)]
+    fn transfer(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error>;
+
+    #[ink(message)]
+    fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error>;
+
+    #[ink(message)]
+    fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error>;
+}
+
+#[ink::contract(env = chit_fund::CustomEnvironment)]
+mod reentrant_token {
+    use crate::{PSP22Error, PSP22};
+    use chit_fund::CustomEnvironment;
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::CallFlags;
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    // The fund message the next transfer out of `fund` calls, and the account it takes, if
+    // any.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Attack {
+        pub fund: AccountId,
+        pub selector: [u8; 4],
+        pub target: Option<AccountId>,
+    }
+
+    #[ink(storage)]
+    pub struct ReentrantToken {
+        balances: Mapping<AccountId, Balance>,
+        attack: Option<Attack>,
+        // Whether the last call back into a fund went through, once one has been tried.
+        reentered: Option<bool>,
+    }
+
+    impl ReentrantToken {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                balances: Mapping::default(),
+                attack: None,
+                reentered: None,
+            }
+        }
+
+        #[ink(message)]
+        pub fn mint(&mut self, account: AccountId, amount: Balance) {
+            let balance = self.balances.get(account).unwrap_or(0).saturating_add(amount);
+            self.balances.insert(account, &balance);
+        }
+
+        // Make the next transfer out of `fund` call the message with `selector` on it.
+        #[ink(message)]
+        pub fn arm(&mut self, fund: AccountId, selector: [u8; 4], target: Option<AccountId>) {
+            self.attack = Some(Attack { fund, selector, target });
+            self.reentered = None;
+        }
+
+        #[ink(message)]
+        pub fn reentered(&self) -> Option<bool> {
+            self.reentered
+        }
+
+        // Call a fund message that takes no arguments, such as `join`, `deposit` or `leave`,
+        // as this contract. The fund may call back into the token to collect or pay out, so
+        // the call lets it. Returns whether the message succeeded.
+        #[ink(message)]
+        pub fn call_fund(&mut self, fund: AccountId, selector: [u8; 4]) -> bool {
+            Self::call(fund, selector, None, true)
+        }
+
+        fn call(fund: AccountId, selector: [u8; 4], target: Option<AccountId>, allow_reentry: bool) -> bool {
+            let flags = CallFlags::default().set_allow_reentry(allow_reentry);
+            // Fund errors are fieldless, so they decode as their code.
+            let result = match target {
+                Some(target) => build_call::<CustomEnvironment>()
+                    .call(fund)
+                    .call_flags(flags)
+                    .exec_input(ExecutionInput::new(Selector::new(selector)).push_arg(target))
+                    .returns::<Result<(), u8>>()
+                    .try_invoke(),
+                None => build_call::<CustomEnvironment>()
+                    .call(fund)
+                    .call_flags(flags)
+                    .exec_input(ExecutionInput::new(Selector::new(selector)))
+                    .returns::<Result<(), u8>>()
+                    .try_invoke(),
+            };
+            matches!(result, Ok(Ok(Ok(()))))
+        }
+
+        fn move_balance(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let from_balance = self.balances.get(from).unwrap_or(0);
+            if from_balance < value {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            self.balances.insert(from, &(from_balance - value));
+            let to_balance = self.balances.get(to).unwrap_or(0).saturating_add(value);
+            self.balances.insert(to, &to_balance);
+            Ok(())
+        }
+    }
+
+    impl Default for ReentrantToken {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl PSP22 for ReentrantToken {
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or(0)
+        }
+
+        // Moves the tokens, then, if armed against the sender, calls back into it.
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, _data: Vec<u8>) -> Result<(), PSP22Error> {
+            let from = self.env().caller();
+            self.move_balance(from, to, value)?;
+            if let Some(attack) = self.attack.filter(|attack| attack.fund == from) {
+                self.attack = None;
+                self.reentered = Some(Self::call(attack.fund, attack.selector, attack.target, false));
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance, _data: Vec<u8>) -> Result<(), PSP22Error> {
+            self.move_balance(from, to, value)
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, _spender: AccountId, _value: Balance) -> Result<(), PSP22Error> {
+            Ok(())
+        }
+    }
+}
+
+pub use self::reentrant_token::{ReentrantToken, ReentrantTokenRef};
@@ -0,0 +1,68 @@
+// Cross-contract calls into the factory that created the fund, which keeps a blacklist of
+// accounts that were expelled from the organizer's earlier funds and every account's
+// reputation from the organizer's funds.
+
+use ink::env::call::{build_call, ExecutionInput, Selector};
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+
+use crate::CustomEnvironment;
+
+// The call could not be made or its reply could not be decoded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RegistryError;
+
+// Whether the fund's organizer has blacklisted the account.
+pub fn is_blacklisted(registry: AccountId, account: AccountId) -> Result<bool, RegistryError> {
+    build_call::<CustomEnvironment>()
+        .call(registry)
+        .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!("is_blacklisted"))).push_arg(account))
+        .returns::<bool>()
+        .try_invoke()
+        .map_err(|_| RegistryError)?
+        .map_err(|_| RegistryError)
+}
+
+// Blacklist the account with the fund's organizer after it was expelled.
+pub fn report_default(registry: AccountId, account: AccountId) -> Result<(), RegistryError> {
+    // The factory's own error type is not visible here; its code is enough to tell failure.
+    build_call::<CustomEnvironment>()
+        .call(registry)
+        .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!("report_default"))).push_arg(account))
+        .returns::<Result<(), u8>>()
+        .try_invoke()
+        .map_err(|_| RegistryError)?
+        .map_err(|_| RegistryError)?
+        .map_err(|_| RegistryError)
+}
+
+// The account's reputation with the fund's organizer.
+pub fn get_reputation(registry: AccountId, account: AccountId) -> Result<i32, RegistryError> {
+    build_call::<CustomEnvironment>()
+        .call(registry)
+        .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!("get_reputation"))).push_arg(account))
+        .returns::<i32>()
+        .try_invoke()
+        .map_err(|_| RegistryError)?
+        .map_err(|_| RegistryError)
+}
+
+// Report who paid a round on time and who defaulted on it.
+pub fn report_round(
+    registry: AccountId,
+    on_time: Vec<AccountId>,
+    defaulted: Vec<AccountId>,
+) -> Result<(), RegistryError> {
+    build_call::<CustomEnvironment>()
+        .call(registry)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("report_round")))
+                .push_arg(on_time)
+                .push_arg(defaulted),
+        )
+        .returns::<Result<(), u8>>()
+        .try_invoke()
+        .map_err(|_| RegistryError)?
+        .map_err(|_| RegistryError)?
+        .map_err(|_| RegistryError)
+}
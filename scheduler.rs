@@ -0,0 +1,86 @@
+// Deadline math for contribution rounds. A deployment times its rounds either in blocks or
+// in milliseconds, whichever its chain keeps more reliably, and the fund only ever deals in
+// ticks of that unit: round starts, deadlines, default dates and grace periods are all
+// stored and compared as ticks.
+
+use crate::CustomEnvironment;
+
+type BlockNumber = <CustomEnvironment as ink::env::Environment>::BlockNumber;
+type Timestamp = <CustomEnvironment as ink::env::Environment>::Timestamp;
+
+// Block time assumed when a deadline has to be shown in the unit the schedule does not use.
+pub const EXPECTED_BLOCK_TIME: Timestamp = 6_000;
+
+// How contribution rounds are timed. A round length of zero leaves rounds open until the
+// admin ends them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub enum ScheduleSource {
+    BlockNumber { blocks_per_round: BlockNumber },
+    Timestamp { millis_per_round: Timestamp },
+}
+
+impl Default for ScheduleSource {
+    fn default() -> Self {
+        ScheduleSource::Timestamp { millis_per_round: 0 }
+    }
+}
+
+// A deadline in both units. Whichever the schedule does not use is an estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Deadline {
+    pub block_number: BlockNumber,
+    pub timestamp: Timestamp,
+}
+
+impl ScheduleSource {
+    // Length of a round in ticks.
+    pub fn round_length(&self) -> u64 {
+        match *self {
+            ScheduleSource::BlockNumber { blocks_per_round } => blocks_per_round as u64,
+            ScheduleSource::Timestamp { millis_per_round } => millis_per_round,
+        }
+    }
+
+    pub fn has_deadline(&self) -> bool {
+        self.round_length() > 0
+    }
+
+    // The current tick.
+    pub fn now(&self, block_number: BlockNumber, timestamp: Timestamp) -> u64 {
+        match self {
+            ScheduleSource::BlockNumber { .. } => block_number as u64,
+            ScheduleSource::Timestamp { .. } => timestamp,
+        }
+    }
+
+    // Tick at which a round that started at `start` is due.
+    pub fn deadline(&self, start: u64) -> Option<u64> {
+        self.has_deadline().then(|| start.saturating_add(self.round_length()))
+    }
+
+    // Converts tick `at` into both units, estimating the other one from the current block
+    // and `EXPECTED_BLOCK_TIME`.
+    pub fn to_deadline(&self, at: u64, block_number: BlockNumber, timestamp: Timestamp) -> Deadline {
+        match self {
+            ScheduleSource::BlockNumber { .. } => {
+                let at = at.min(BlockNumber::MAX as u64) as BlockNumber;
+                let timestamp = if at >= block_number {
+                    timestamp.saturating_add((at - block_number) as Timestamp * EXPECTED_BLOCK_TIME)
+                } else {
+                    timestamp.saturating_sub((block_number - at) as Timestamp * EXPECTED_BLOCK_TIME)
+                };
+                Deadline { block_number: at, timestamp }
+            }
+            ScheduleSource::Timestamp { .. } => {
+                let blocks = if at >= timestamp {
+                    block_number.saturating_add((at - timestamp).div_ceil(EXPECTED_BLOCK_TIME) as BlockNumber)
+                } else {
+                    block_number.saturating_sub(((timestamp - at) / EXPECTED_BLOCK_TIME) as BlockNumber)
+                };
+                Deadline { block_number: blocks, timestamp: at }
+            }
+        }
+    }
+}
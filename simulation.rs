@@ -0,0 +1,162 @@
+// Randomized off-chain simulations of whole funds, checking that payouts stay fair however
+// members pay and whatever the draws pick. The off-chain environment does not credit
+// deposits to the contract, so each simulated deposit tops the contract balance up by
+// hand to keep its balance an honest record of the money held.
+
+use ink::env::{test, DefaultEnvironment};
+use ink::primitives::AccountId;
+
+use crate::my_contract::{FundPhase, MAX_BPS};
+use crate::{ChitFund, FundConfig, RemainderPolicy, Rounding, ScheduleSource};
+
+type Balance = u128;
+
+const SIMULATIONS: u64 = 1_000;
+const ROUND_DURATION: u64 = 1_000;
+// A fund that has not completed after this many rounds is stuck.
+const MAX_ROUNDS: u32 = 300;
+
+// xorshift64*, enough to vary the simulations reproducibly without pulling in a crate.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+}
+
+fn contract_balance() -> Balance {
+    test::get_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>()).unwrap()
+}
+
+fn fund_contract(amount: Balance) {
+    test::set_account_balance::<DefaultEnvironment>(
+        test::callee::<DefaultEnvironment>(),
+        contract_balance() + amount,
+    );
+}
+
+fn simulate(seed: u64) {
+    let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1);
+    let accounts = test::default_accounts::<DefaultEnvironment>();
+    let all: [AccountId; 6] =
+        [accounts.alice, accounts.bob, accounts.charlie, accounts.django, accounts.eve, accounts.frank];
+    let members = &all[..2 + rng.below(5) as usize];
+    let contribution = 1 + rng.below(1_000) as Balance;
+    let pay_percent = 75 + rng.below(26);
+    let config = FundConfig {
+        commission_bps: rng.below(MAX_BPS as u64 / 5) as u16,
+        commission_rounding: [Rounding::Down, Rounding::Up, Rounding::Nearest][rng.below(3) as usize],
+        remainder_policy: [RemainderPolicy::Winner, RemainderPolicy::Pot, RemainderPolicy::Organizer]
+            [rng.below(3) as usize],
+        schedule: ScheduleSource::Timestamp { millis_per_round: ROUND_DURATION },
+        // Alice runs the fund and pays and wins like everyone else.
+        admin_participates: true,
+        ..Default::default()
+    };
+
+    // Off-chain the contract runs as Alice unless told otherwise, which would turn payouts
+    // to her into transfers to itself.
+    test::set_callee::<DefaultEnvironment>(AccountId::from([0xff; 32]));
+    test::set_caller::<DefaultEnvironment>(accounts.alice);
+    let mut chit_fund = ChitFund::with_config(accounts.alice, members.len() as u32, contribution, config);
+    test::set_account_balance::<DefaultEnvironment>(test::callee::<DefaultEnvironment>(), 0);
+    test::set_value_transferred::<DefaultEnvironment>(0);
+    for member in members {
+        test::set_caller::<DefaultEnvironment>(*member);
+        assert_eq!(chit_fund.join(), Ok(()));
+    }
+    test::set_caller::<DefaultEnvironment>(accounts.alice);
+    assert_eq!(chit_fund.start_fund(), Ok(()));
+
+    let mut deposited: Balance = 0;
+    let mut now = 0;
+    let mut rounds = 0;
+    while chit_fund.get_phase() != FundPhase::Completed {
+        rounds += 1;
+        assert!(rounds <= MAX_ROUNDS, "seed {seed}: fund did not complete");
+        for member in members {
+            if !rng.chance(pay_percent) {
+                continue;
+            }
+            test::set_caller::<DefaultEnvironment>(*member);
+            test::set_value_transferred::<DefaultEnvironment>(contribution);
+            fund_contract(contribution);
+            assert_eq!(chit_fund.deposit(), Ok(()), "seed {seed}");
+            deposited += contribution;
+        }
+        // A round with defaulters can only be settled once its deadline has passed.
+        if chit_fund.defaulters(chit_fund.get_current_round()).is_empty() {
+            now += rng.below(ROUND_DURATION);
+        } else {
+            now += ROUND_DURATION;
+        }
+        for _ in 0..rng.below(3) {
+            test::advance_block::<DefaultEnvironment>();
+        }
+        test::set_block_timestamp::<DefaultEnvironment>(now);
+        test::set_caller::<DefaultEnvironment>(members[rng.below(members.len() as u64) as usize]);
+        assert_eq!(chit_fund.settle_round(), Ok(()), "seed {seed}");
+        assert!(chit_fund.winner_count <= chit_fund.participant_count(), "seed {seed}");
+        assert_eq!(contract_balance() + paid_out(&chit_fund), deposited, "seed {seed}");
+        assert_eq!(chit_fund.held, contract_balance(), "seed {seed}");
+        assert!(chit_fund.pot + chit_fund.total_amount <= chit_fund.held, "seed {seed}");
+    }
+
+    // Every member has won exactly once.
+    let mut winners = chit_fund.get_all_winners().into_iter().map(|(_, winner)| winner).collect::<Vec<_>>();
+    assert_eq!(winners.len(), members.len(), "seed {seed}");
+    winners.sort();
+    winners.dedup();
+    assert_eq!(winners.len(), members.len(), "seed {seed}");
+
+    // Once the commission and every dividend have been paid out, everything deposited has
+    // left the contract and nothing more.
+    test::set_caller::<DefaultEnvironment>(accounts.alice);
+    if chit_fund.commission_balance > 0 {
+        assert_eq!(chit_fund.withdraw_commission(), Ok(()), "seed {seed}");
+    }
+    for member in members {
+        test::set_caller::<DefaultEnvironment>(*member);
+        if chit_fund.get_pending_dividends(*member) > 0 {
+            assert_eq!(chit_fund.claim_dividends(), Ok(()), "seed {seed}");
+        }
+    }
+    assert_eq!(chit_fund.commission_balance, 0, "seed {seed}");
+    assert_eq!(contract_balance(), 0, "seed {seed}");
+    assert_eq!(chit_fund.held, 0, "seed {seed}");
+}
+
+// Prizes sent to winners so far, net of the commission kept back.
+fn paid_out(chit_fund: &ChitFund) -> Balance {
+    chit_fund
+        .get_all_winners()
+        .into_iter()
+        .map(|(round, _)| {
+            let record = chit_fund.get_round_history(round).unwrap();
+            record.prize - record.commission
+        })
+        .sum()
+}
+
+#[test]
+fn payouts_stay_fair_across_random_funds() {
+    for seed in 0..SIMULATIONS {
+        test::run_test::<DefaultEnvironment, _>(|_| {
+            simulate(seed);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
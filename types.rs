@@ -0,0 +1,290 @@
+// The contract's storage and error types, kept outside the contract module so off-chain
+// indexers and tools can depend on this crate with the `std` feature and decode storage,
+// events and call results with the very types the contract encodes. The `serde` feature
+// adds serde derives for turning them into JSON and the like.
+
+use ink::primitives::AccountId;
+
+use crate::CustomEnvironment;
+
+type Balance = <CustomEnvironment as ink::env::Environment>::Balance;
+type Timestamp = <CustomEnvironment as ink::env::Environment>::Timestamp;
+
+// Lifecycle of a fund. Membership is open only while recruiting; after that each
+// round moves from collecting contributions to awaiting its draw and back, until
+// everyone has won or the fund is cancelled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FundPhase {
+    // Accepting members until the admin calls `start_fund`.
+    #[default]
+    Recruiting,
+    // Collecting the current round's contributions until `end_cycle`.
+    ContributionOpen,
+    // Contributions closed; waiting for the draw or auction, then `begin_cycle`.
+    DrawPending,
+    // Every participant has won; the fund accepts no further activity.
+    Completed,
+    Cancelled,
+}
+
+// A participant's entry in `members`.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberInfo {
+    // Position in `member_index`.
+    pub index: u32,
+    // Seats held, and how many of them have won. `has_won` is set once every ticket has.
+    pub tickets: u32,
+    pub wins: u32,
+    pub has_won: bool,
+}
+
+// What happened in a settled round, kept so it can be read back from storage.
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundRecord {
+    pub round: u32,
+    #[cfg_attr(feature = "serde", serde(with = "account_id"))]
+    pub winner: AccountId,
+    // Gross prize awarded, before commission: every member's contribution, the winner's
+    // own included, less any auction discount.
+    pub prize: Balance,
+    pub commission: Balance,
+    // Funds held for the draw or auction when the round was settled, including any
+    // carried over from earlier rounds.
+    pub total_collected: Balance,
+    pub timestamp: Timestamp,
+    // Paid to the winner from the organizer's bonus pool, on top of the prize.
+    pub bonus: Balance,
+    // Who the round was settled between.
+    pub snapshot: RoundSnapshot,
+    // Contributions that went into the pot while the round was open, arrears included.
+    pub collected: Balance,
+    // Late fees paid since the previous round was settled.
+    pub penalties: Balance,
+    // Dividends credited for the round, including any credited after it was settled.
+    pub dividends: Balance,
+    // Members who missed the round's contribution.
+    pub defaults: u32,
+}
+
+// What the round awaiting settlement has taken in so far, moved into its `RoundRecord`
+// when it is settled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundTally {
+    pub collected: Balance,
+    pub penalties: Balance,
+    pub dividends: Balance,
+    pub defaults: u32,
+}
+
+// The membership a round was run with, fixed when its contributions close. Members who
+// leave or are expelled before the draw are taken out again, so the prize and any
+// dividends are worked out between the members actually in the round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundSnapshot {
+    pub members: u32,
+    pub tickets: u32,
+    // What the round should have collected: `monthly_contribution` for every ticket.
+    pub expected: Balance,
+}
+
+// Every error has a fixed code, the byte it is encoded as, so frontends can match on
+// it. New errors take the next free code; codes are never changed or reused. See
+// `errors.rs` for what each one means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Error {
+    ParticipantsAlreadyFull = 0,
+    ChitFundHasFinished = 1,
+    AlreadyJoined = 2,
+    CannotJoinMidCycle = 3,
+    OnlyOwnerCanBeginCycle = 4,
+    OnlyOwnerCanEndCycle = 5,
+    ChitFundNotFinished = 6,
+    NotParticipant = 7,
+    OnlyAdminCanDraw = 8,
+    ChitFundAlreadyFinished = 9,
+    FailedToGetWinner = 10,
+    OnlyAdminCanWithdraw = 11,
+    FundNotCancelled = 12,
+    TransferFailed = 13,
+    OnlyAdminCanChangeContribution = 14,
+    CannotChangeMidCycle = 15,
+    InvalidContributionAmount = 16,
+    OnlyAdminCanRegister = 17,
+    IncorrectContributionAmount = 18,
+    AlreadyDeposited = 19,
+    ContributionsIncomplete = 20,
+    AlreadyCommitted = 21,
+    NoCommitment = 22,
+    AlreadyRevealed = 23,
+    InvalidReveal = 24,
+    RevealPeriodOver = 25,
+    RevealPeriodOpen = 26,
+    CommitRevealDisabled = 27,
+    RandomnessUnavailable = 28,
+    ChitFundTerminated = 29,
+    PayoutTransferFailed = 30,
+    NoPrizeToClaim = 31,
+    ArithmeticOverflow = 32,
+    WrongFundMode = 33,
+    AlreadyWon = 34,
+    InvalidBid = 35,
+    BidTooHigh = 36,
+    NoBids = 37,
+    NoCommissionToWithdraw = 38,
+    RoundExpired = 39,
+    RoundNotExpired = 40,
+    OnlyAdminCanExpel = 41,
+    NotEnoughMissedRounds = 42,
+    CannotLeaveMidCycle = 43,
+    OnlyAdminCanCancel = 44,
+    FundCancelled = 45,
+    RefundAlreadyClaimed = 46,
+    NothingToRefund = 47,
+    OnlyAdminCanTransferAdmin = 48,
+    NotPendingAdmin = 49,
+    RequiresMultisig = 50,
+    MultisigDisabled = 51,
+    NotCoAdmin = 52,
+    ProposalNotFound = 53,
+    ProposalExpired = 54,
+    ProposalAlreadyExecuted = 55,
+    AlreadyApproved = 56,
+    NotEnoughApprovals = 57,
+    NativeValueNotAccepted = 58,
+    InsufficientAllowance = 59,
+    TokenTransferFailed = 60,
+    NotAllowlisted = 61,
+    OnlyAdminCanManageAllowlist = 62,
+    IncorrectCollateralAmount = 63,
+    NoCollateral = 64,
+    CollateralLocked = 65,
+    ContractPaused = 66,
+    OnlyAdminCanPause = 67,
+    AlreadyPaused = 68,
+    NotPaused = 69,
+    OnlyAdminCanUpgrade = 70,
+    UpgradeFailed = 71,
+    AlreadyMigrated = 72,
+    NoDividends = 73,
+    OnlyAdminCanStart = 74,
+    FundNotStarted = 75,
+    FundAlreadyStarted = 76,
+    NotEnoughParticipants = 77,
+    MembershipLocked = 78,
+    RoundAlreadyDrawn = 79,
+    NotPaidUp = 80,
+    ExpulsionAlreadyProposed = 81,
+    CannotVoteOnOwnExpulsion = 82,
+    AlreadyVoted = 83,
+    QuorumNotReached = 84,
+    ReentrantCall = 85,
+    OnlyAdminCanManageYield = 86,
+    NoYieldStrategy = 87,
+    YieldStrategyKilled = 88,
+    YieldStrategyInUse = 89,
+    YieldStrategyFailed = 90,
+    NothingToInvest = 91,
+    OnlyAdminCanManageRoles = 92,
+    RoleAlreadyGranted = 93,
+    RoleNotGranted = 94,
+    TooEarlyToPoke = 95,
+    AlreadyPoked = 96,
+    NoDefaulters = 97,
+    NoTerms = 98,
+    TermsMismatch = 99,
+    TermsNotAccepted = 100,
+    NotInDefault = 101,
+    GracePeriodActive = 102,
+    SeatAlreadyWon = 103,
+    SeatTransferred = 104,
+    NoSeatTransfer = 105,
+    OnlyAdminCanApproveTransfer = 106,
+    CannotVoteOnOwnTransfer = 107,
+    OnlyAdminCanCatchUp = 108,
+    RoundNotPayable = 109,
+    DepositsClosed = 110,
+    NoSurplus = 111,
+    Blacklisted = 112,
+    RegistryUnavailable = 113,
+    ReputationTooLow = 114,
+    OnlyAdminCanVoid = 115,
+    RoundStillDrawable = 116,
+    RoundVoided = 117,
+    FundNotCompleted = 118,
+    PenaltyPoolAlreadyDistributed = 119,
+    PayoutPending = 120,
+    NoPendingPayout = 121,
+    DisputeWindowClosed = 122,
+    DisputeWindowOpen = 123,
+    DisputeAlreadyRaised = 124,
+    NoDispute = 125,
+    CannotDisputeOwnWin = 126,
+    OnlyAdminCanTerminate = 127,
+    FundStillRunning = 128,
+    ClaimsOutstanding = 129,
+    OnlyAdminCanSetRotation = 130,
+    InvalidRotation = 131,
+    OnlyAdminCanSetParameters = 132,
+    ParametersLocked = 133,
+    InvalidParticipantLimit = 134,
+    AdminCannotParticipate = 135,
+    OnlyAdminCanFundBonus = 136,
+    BonusFundingClosed = 137,
+    IncorrectBonusAmount = 138,
+    InvalidTicketCount = 139,
+    InvalidBatch = 140,
+    OnlyAdminCanSetHook = 141,
+    WrongRound = 142,
+    MigrationPending = 143,
+    UnsupportedStorageVersion = 144,
+    SealedPrizeExpired = 145,
+    SealedPrizeNotExpired = 146,
+    EscrowNotExpired = 147,
+    EscrowExpired = 148,
+    AdvancesDisabled = 149,
+    InvalidAdvanceAmount = 150,
+    AdvanceOutstanding = 151,
+    NoAdvanceRequest = 152,
+    CannotVoteOnOwnAdvance = 153,
+    InsufficientAdvanceFunds = 154,
+    NoDexRouter = 155,
+    InvalidPayoutPreference = 156,
+    SwapFailed = 157,
+    DrawDeferred = 158,
+    InvalidReferrer = 159,
+    UnknownMessage = 160,
+    WinnerPayoutPending = 161,
+    SealingDisabled = 162,
+    RoundSecretMissing = 163,
+    WrongClaimSecret = 164,
+    NothingToRecover = 165,
+    CommissionNotWithdrawn = 166,
+}
+
+// `AccountId` has no serde support of its own, so it is written as its 32 bytes.
+#[cfg(feature = "serde")]
+mod account_id {
+    use ink::primitives::AccountId;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(account: &AccountId, serializer: S) -> Result<S::Ok, S::Error> {
+        AsRef::<[u8; 32]>::as_ref(account).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<AccountId, D::Error> {
+        <[u8; 32]>::deserialize(deserializer).map(AccountId::from)
+    }
+}
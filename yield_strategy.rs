@@ -0,0 +1,63 @@
+// Cross-contract calls into a yield strategy, such as a lending vault, that the fund can park
+// its collected contributions in while a round is open.
+
+use ink::env::call::{build_call, ExecutionInput, Selector};
+use ink::primitives::AccountId;
+
+use crate::CustomEnvironment;
+
+type Balance = <CustomEnvironment as ink::env::Environment>::Balance;
+
+// What a strategy contract has to implement. Funds collecting in the native currency send
+// the deposit as the call's value; token funds transfer the tokens to the strategy first.
+#[ink::trait_definition]
+pub trait YieldStrategy {
+    // Take `amount` into the strategy on behalf of the caller.
+    #[ink(message, payable)]
+    fn deposit(&mut self, amount: Balance) -> bool;
+
+    // Send everything held for the caller, principal and yield, back to it and return the
+    // amount sent.
+    #[ink(message)]
+    fn withdraw_all(&mut self) -> Balance;
+}
+
+// Why a call into the strategy failed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum StrategyError {
+    // The strategy refused the deposit.
+    Rejected,
+    // The call itself could not be made or its reply could not be decoded.
+    CallFailed,
+}
+
+pub fn deposit(strategy: AccountId, amount: Balance, value: Balance) -> Result<(), StrategyError> {
+    let accepted = build_call::<CustomEnvironment>()
+        .call(strategy)
+        .transferred_value(value)
+        .exec_input(
+            ExecutionInput::new(Selector::new(ink::selector_bytes!("YieldStrategy::deposit")))
+                .push_arg(amount),
+        )
+        .returns::<bool>()
+        .try_invoke()
+        .map_err(|_| StrategyError::CallFailed)?
+        .map_err(|_| StrategyError::CallFailed)?;
+    if accepted {
+        Ok(())
+    } else {
+        Err(StrategyError::Rejected)
+    }
+}
+
+pub fn withdraw_all(strategy: AccountId) -> Result<Balance, StrategyError> {
+    build_call::<CustomEnvironment>()
+        .call(strategy)
+        .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!(
+            "YieldStrategy::withdraw_all"
+        ))))
+        .returns::<Balance>()
+        .try_invoke()
+        .map_err(|_| StrategyError::CallFailed)?
+        .map_err(|_| StrategyError::CallFailed)
+}